@@ -19,12 +19,30 @@ impl Serialize for SerializeableNaiveDateTime {
     }
 }
 
+/// The timestamps of the exam behind a work unit, kept for turnaround analysis.
+#[derive(Default, Debug, Clone)]
+pub struct ExamTimestamps {
+    pub started: NaiveDateTime,
+    pub completed: Option<NaiveDateTime>,
+    pub preliminary: Option<NaiveDateTime>,
+    pub finalized: Option<NaiveDateTime>,
+}
+
 #[derive(Default, Debug, Serialize, Clone)]
 pub struct WorkUnit {
     datetime: SerializeableNaiveDateTime,
     rvu: f64,
     bvu: f64,
     exam_desc: String,
+    #[serde(skip)]
+    timestamps: ExamTimestamps,
+    #[serde(skip)]
+    signer_acct_id: u64,
+    #[serde(skip)]
+    exam_code: String,
+    //Number of studies this unit stands for. Only differs from 1 in growth scenarios.
+    #[serde(skip)]
+    studies: f64,
     //denominator: f64, //Used only for fractional type? No, was using in work adding! Get rid of this.
 }
 
@@ -49,6 +67,33 @@ impl WorkUnit {
     pub fn get_exam_desc(&self) -> &str {
         self.exam_desc.as_str()
     }
+    pub fn get_timestamps(&self) -> &ExamTimestamps {
+        &self.timestamps
+    }
+    pub fn with_timestamps(mut self, timestamps: ExamTimestamps) -> WorkUnit {
+        self.timestamps = timestamps;
+        self
+    }
+    pub fn get_signer_acct_id(&self) -> u64 {
+        self.signer_acct_id
+    }
+    pub fn with_signer(mut self, signer_acct_id: u64) -> WorkUnit {
+        self.signer_acct_id = signer_acct_id;
+        self
+    }
+    pub fn get_exam_code(&self) -> &str {
+        self.exam_code.as_str()
+    }
+    pub fn with_exam_code(mut self, exam_code: String) -> WorkUnit {
+        self.exam_code = exam_code;
+        self
+    }
+    /// Grows or shrinks the work this unit represents, e.g. 1.15 for 15% more exams like it.
+    pub fn scale(&mut self, factor: f64) {
+        self.rvu *= factor;
+        self.bvu *= factor;
+        self.studies *= factor;
+    }
     pub fn create(
         datetime: NaiveDateTime,
         rvu: f64,
@@ -61,6 +106,10 @@ impl WorkUnit {
             rvu,
             bvu,
             exam_desc,
+            timestamps: ExamTimestamps::default(),
+            signer_acct_id: 0,
+            exam_code: String::new(),
+            studies: 1.0,
             //denominator,
         }
     }
@@ -114,7 +163,14 @@ impl AnalysisDatum {
     pub fn add_workunit(&mut self, rhs: &WorkUnit) {
         self.total_rvu += rhs.get_absolute_rvu();
         self.total_bvu += rhs.get_absolute_bvu();
-        self.add_studies(rhs.exam_desc.to_string(), 1.0);// / rhs.denominator);
+        self.add_studies(rhs.exam_desc.to_string(), rhs.studies);// / rhs.denominator);
+    }
+
+    /// Adds only `scale` of the work unit, for work shared between rotations.
+    pub fn add_scaled_workunit(&mut self, rhs: &WorkUnit, scale: f64) {
+        self.total_rvu += rhs.get_absolute_rvu() * scale;
+        self.total_bvu += rhs.get_absolute_bvu() * scale;
+        self.add_studies(rhs.exam_desc.to_string(), rhs.studies * scale);
     }
 
     fn add_studies(&mut self, key: String, val: f64) {
@@ -1,2 +1,3 @@
 pub mod analysis_datum;
+pub mod scenario;
 pub mod volumes;
@@ -0,0 +1,275 @@
+use std::{collections::BTreeSet, error::Error, fs};
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+
+use crate::{
+    analysis::analysis_datum::{ExamTimestamps, WorkUnit},
+    coverage::{
+        coordinate::CoverageCoordinates,
+        work_coverage_map::{generics::WorkCoverageMap, maps::CoverageMap},
+    },
+    globals,
+    rotations::{
+        responsibility::{check, validate_days},
+        stringtypes::StringTypes,
+        time_modifiers::TimeSinceMidnight,
+    },
+    source_data::processing::{
+        categorization::build_salem_rvumap, processed_source::ProcessedSource,
+    },
+};
+
+const DEFAULT_NEW_VOLUME_START: &str = "08:00";
+const DEFAULT_NEW_VOLUME_STOP: &str = "17:00";
+
+/// Multiplies the existing work matching every listed field, e.g. `factor: 1.15` for 15% more.
+/// `exams` are subspecialties, as in manifests, and `exam_codes` are individual procedure codes.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GrowthFactor {
+    pub sites: Option<StringTypes>,
+    pub exams: Option<StringTypes>,
+    pub contexts: Option<StringTypes>,
+    pub exam_codes: Option<BTreeSet<String>>,
+    pub factor: f64,
+}
+
+/// Work that doesn't exist historically, such as a new scanner's exams. `exams_per_day` exams of `exam_code`
+/// are added on every date in the data falling on one of `days`, evenly spaced between `start` and `stop`.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct NewVolume {
+    pub site: String,
+    pub exam: String,
+    pub context: String,
+    pub exam_code: String,
+    pub exams_per_day: f64,
+    pub days: Option<StringTypes>,
+    pub start: Option<String>,
+    pub stop: Option<String>,
+}
+
+/// Changes to historical volume to plan for, applied to the work before coverage is analyzed.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GrowthScenario {
+    pub title: String,
+    pub growth: Option<Vec<GrowthFactor>>,
+    pub new_volume: Option<Vec<NewVolume>>,
+}
+
+fn all_day_names() -> Vec<String> {
+    globals::ALL_DAYS
+        .iter()
+        .map(|day| day.to_string())
+        .collect()
+}
+
+fn matches(constraint: &Option<StringTypes>, all: &[&str], value: &str) -> bool {
+    match constraint {
+        Some(constraint) => constraint.to_vec(all).contains(value),
+        None => true,
+    }
+}
+
+impl GrowthFactor {
+    fn applies(&self, coords: &CoverageCoordinates, work: &WorkUnit) -> bool {
+        matches(&self.sites, globals::FACILITIES, &coords.facility)
+            && matches(&self.exams, globals::SUBSPECIALTIES, &coords.subspecialty)
+            && matches(&self.contexts, globals::CONTEXTS, &coords.context)
+            && match &self.exam_codes {
+                Some(exam_codes) => exam_codes.contains(work.get_exam_code()),
+                None => true,
+            }
+    }
+}
+
+impl NewVolume {
+    fn hours(&self) -> Result<(TimeSinceMidnight, TimeSinceMidnight), String> {
+        let parse = |value: &Option<String>, default: &str| {
+            let value = value.as_deref().unwrap_or(default);
+            TimeSinceMidnight::parse_from_str(value)
+                .map_err(|_| format!("Malformed time {} for new {} volume.", value, self.exam))
+        };
+        Ok((
+            parse(&self.start, DEFAULT_NEW_VOLUME_START)?,
+            parse(&self.stop, DEFAULT_NEW_VOLUME_STOP)?,
+        ))
+    }
+
+    //Exam times on one date, evenly spaced through the hours. A fractional number of exams per day is spread over
+    //the next whole number of work units, each scaled down to match.
+    fn times(&self, date: NaiveDate) -> (Vec<NaiveDateTime>, f64) {
+        let (start, stop) = self.hours().expect("Validated when read");
+        let count = self.exams_per_day.ceil() as u64;
+        let interval = (stop.to_minutes() - start.to_minutes()) as f64 / count as f64;
+        let midnight = date.and_hms_opt(0, 0, 0).expect("Midnight is valid");
+        let times = (0..count)
+            .map(|n| {
+                let minutes = start.to_minutes() as f64 + (n as f64 + 0.5) * interval;
+                midnight + chrono::Duration::seconds((minutes * 60.0) as i64)
+            })
+            .collect();
+        (times, self.exams_per_day / count as f64)
+    }
+}
+
+impl GrowthScenario {
+    pub fn read(filename: &str) -> Result<GrowthScenario, Box<dyn Error>> {
+        let rdr = fs::File::open(filename)?;
+        let retval: GrowthScenario = serde_yaml::from_reader(rdr)?;
+        retval.validate()?;
+        Ok(retval)
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let mut errors: Vec<String> = Vec::new();
+        for growth in self.growth.iter().flatten() {
+            if growth.factor < 0.0 {
+                errors.push(format!("Growth factor {} is negative.", growth.factor));
+            }
+            if let Some(sites) = &growth.sites {
+                check(sites, globals::FACILITIES, "site", &mut errors);
+            }
+            if let Some(exams) = &growth.exams {
+                check(exams, globals::SUBSPECIALTIES, "subspecialty", &mut errors);
+            }
+            if let Some(contexts) = &growth.contexts {
+                check(contexts, globals::CONTEXTS, "context", &mut errors);
+            }
+        }
+        for new_volume in self.new_volume.iter().flatten() {
+            for (value, poss, desc) in [
+                (&new_volume.site, globals::FACILITIES, "site"),
+                (&new_volume.exam, globals::SUBSPECIALTIES, "subspecialty"),
+                (&new_volume.context, globals::CONTEXTS, "context"),
+            ] {
+                if !poss.contains(&value.as_str()) {
+                    errors.push(format!(
+                        "Invalid {} {}. Valid values are {:?}",
+                        desc, value, poss
+                    ));
+                }
+            }
+            if let Some(days) = &new_volume.days {
+                validate_days(days, &mut errors);
+            }
+            if new_volume.exams_per_day <= 0.0 {
+                errors.push(format!(
+                    "New {} volume has {} exams per day.",
+                    new_volume.exam, new_volume.exams_per_day
+                ));
+            }
+            match new_volume.hours() {
+                Ok((start, stop)) => {
+                    if start >= stop {
+                        errors.push(format!(
+                            "New {} volume starts at {} but stops at {}.",
+                            new_volume.exam, start, stop
+                        ));
+                    }
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            Err("Error in growth scenario.".into())
+        }
+    }
+
+    /// Scales the work in `coverage_map` by every growth factor matching it and adds the new volume
+    /// on each date the map already has work. New exams get the RVUs historical work does, from the same RVU map,
+    /// and BVUs from the BVU map.
+    pub fn apply(
+        &self,
+        coverage_map: &mut CoverageMap,
+        source: &ProcessedSource,
+    ) -> Result<(), Box<dyn Error>> {
+        let growth = self.growth.as_deref().unwrap_or_default();
+        let mut dates: BTreeSet<NaiveDate> = BTreeSet::new();
+        coverage_map.foreach_mut(|coords, coverage_and_workday| {
+            for work in coverage_and_workday.work_iterator_mut() {
+                dates.insert(work.get_datetime().date());
+                let factor: f64 = growth
+                    .iter()
+                    .filter(|growth| growth.applies(coords, work))
+                    .map(|growth| growth.factor)
+                    .product();
+                work.scale(factor);
+            }
+        });
+
+        let new_volumes = self.new_volume.as_deref().unwrap_or_default();
+        if new_volumes.is_empty() {
+            return Ok(());
+        }
+        let exam_rvu_map = build_salem_rvumap(&source.main_data)?;
+        let all_days = all_day_names();
+        let all_days: Vec<&str> = all_days.iter().map(|day| day.as_str()).collect();
+        for new_volume in new_volumes {
+            let (exam, rvu) = match source
+                .main_data
+                .iter()
+                .find(|exam| exam.exam_code == new_volume.exam_code)
+                .zip(exam_rvu_map.get(&new_volume.exam_code))
+            {
+                Some(x) => x,
+                None => {
+                    return Err(format!(
+                        "Exam code {} for new {} volume isn't in the source data.",
+                        new_volume.exam_code, new_volume.exam
+                    )
+                    .into());
+                }
+            };
+            let bvu = match source.bvu_map.get(&new_volume.exam_code) {
+                Some(x) => *x,
+                None => {
+                    return Err(format!(
+                        "Exam code {} for new {} volume isn't in the BVU map.",
+                        new_volume.exam_code, new_volume.exam
+                    )
+                    .into());
+                }
+            };
+
+            for date in &dates {
+                let weekday = date.weekday();
+                if !matches(&new_volume.days, &all_days, &weekday.to_string()) {
+                    continue;
+                }
+                let coords = CoverageCoordinates {
+                    facility: new_volume.site.to_string(),
+                    subspecialty: new_volume.exam.to_string(),
+                    context: new_volume.context.to_string(),
+                    weekday,
+                };
+                let (times, scale) = new_volume.times(*date);
+                for datetime in times {
+                    let mut work = WorkUnit::create(
+                        datetime,
+                        *rvu,
+                        bvu,
+                        exam.procedure_description.to_string(),
+                    )
+                    .with_exam_code(new_volume.exam_code.to_string())
+                    .with_timestamps(ExamTimestamps {
+                        started: datetime,
+                        ..Default::default()
+                    });
+                    work.scale(scale);
+                    coverage_map.add_work(&coords, work);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -49,6 +49,9 @@ impl CategorizedVolumes {
         }
         retval
     }
+    pub fn get_date_map(&self) -> &BTreeMap<NaiveDate, BTreeMap<String, VolumesMark>> {
+        &self.date_map
+    }
     pub fn retain<T>(&mut self, func:T)
     where T:FnMut(&NaiveDate, &mut BTreeMap<std::string::String, VolumesMark>)->bool
     {
@@ -0,0 +1,270 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::{
+    coverage::{
+        coordinate::CoverageCoordinates, malformed_coverage::CoverageError,
+        work_coverage_map::maps::CoverageMap,
+    },
+    globals::BUSINESS_DAYS,
+    rotations::time_modifiers::{TimeSinceMidnight, NEXT_MIDNIGHT, THIS_MIDNIGHT},
+    serialization::output::JSONFileOut,
+};
+
+const BUSINESS_HOURS_START: TimeSinceMidnight = TimeSinceMidnight::new(8 * 60);
+const BUSINESS_HOURS_END: TimeSinceMidnight = TimeSinceMidnight::new(17 * 60);
+
+//Issues during business hours are weighted more heavily since they're the ones clinicians notice.
+const BUSINESS_HOURS_WEIGHT: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum IssueKind {
+    NoCoverage,
+    Gap,
+    Overlap,
+    IncorrectFraction,
+}
+
+/// A single audit issue with the volume that actually fell within it across the whole date range.
+#[derive(Clone, Serialize)]
+pub struct RankedIssue {
+    #[serde(flatten)]
+    pub coordinates: CoverageCoordinates,
+    pub kind: IssueKind,
+    pub start: TimeSinceMidnight,
+    pub end: TimeSinceMidnight,
+    pub description: String,
+    pub rvus: f64,
+    pub bvus: f64,
+    pub affected_dates: usize,
+    pub business_hours: bool,
+    pub score: f64,
+}
+
+impl JSONFileOut for Vec<RankedIssue> {}
+
+fn during_business_hours(
+    coords: &CoverageCoordinates,
+    start: TimeSinceMidnight,
+    end: TimeSinceMidnight,
+) -> bool {
+    BUSINESS_DAYS.contains(&&coords.weekday) && start < BUSINESS_HOURS_END && end > BUSINESS_HOURS_START
+}
+
+fn score_issue(
+    coverage_map: &CoverageMap,
+    coords: &CoverageCoordinates,
+    kind: IssueKind,
+    start: TimeSinceMidnight,
+    end: TimeSinceMidnight,
+    description: String,
+) -> RankedIssue {
+    let mut rvus: f64 = 0.0;
+    let mut bvus: f64 = 0.0;
+    let mut dates: BTreeSet<NaiveDate> = BTreeSet::new();
+
+    if let Some(coverage_and_workday) = coverage_map.get_coverageandworkday(coords) {
+        for work_unit in coverage_and_workday.get_work_in_timespan(start, end) {
+            rvus += work_unit.get_absolute_rvu();
+            bvus += work_unit.get_absolute_bvu();
+            dates.insert(work_unit.get_datetime().date());
+        }
+    }
+
+    let business_hours = during_business_hours(coords, start, end);
+    let score = if business_hours {
+        rvus * BUSINESS_HOURS_WEIGHT
+    } else {
+        rvus
+    };
+
+    RankedIssue {
+        coordinates: coords.to_owned(),
+        kind,
+        start,
+        end,
+        description,
+        rvus,
+        bvus,
+        affected_dates: dates.len(),
+        business_hours,
+        score,
+    }
+}
+
+/// Scores every issue in the audit by the volume falling within it and returns them most severe first.
+pub fn rank_audit(
+    coverage_map: &CoverageMap,
+    audit_result: &HashMap<CoverageCoordinates, CoverageError>,
+) -> Vec<RankedIssue> {
+    let mut retval: Vec<RankedIssue> = Vec::new();
+
+    for (coords, errs) in audit_result {
+        match errs {
+            CoverageError::NoCoverage(_) => {
+                retval.push(score_issue(
+                    coverage_map,
+                    coords,
+                    IssueKind::NoCoverage,
+                    THIS_MIDNIGHT,
+                    NEXT_MIDNIGHT,
+                    "No coverage".to_string(),
+                ));
+            }
+            CoverageError::MalformedCoverage(errs) => {
+                for gap in &errs.gaps {
+                    retval.push(score_issue(
+                        coverage_map,
+                        coords,
+                        IssueKind::Gap,
+                        gap.start,
+                        gap.end,
+                        gap.description.to_string(),
+                    ));
+                }
+                for overlap in &errs.overlaps {
+                    retval.push(score_issue(
+                        coverage_map,
+                        coords,
+                        IssueKind::Overlap,
+                        overlap.start,
+                        overlap.end,
+                        overlap.description.to_string(),
+                    ));
+                }
+                if let Some(x) = errs.incorrect_fraction {
+                    retval.push(score_issue(
+                        coverage_map,
+                        coords,
+                        IssueKind::IncorrectFraction,
+                        THIS_MIDNIGHT,
+                        NEXT_MIDNIGHT,
+                        format!("Fractions sum to {}", x),
+                    ));
+                }
+                if let Some(x) = errs.incorrect_shared_fraction {
+                    retval.push(score_issue(
+                        coverage_map,
+                        coords,
+                        IssueKind::IncorrectFraction,
+                        THIS_MIDNIGHT,
+                        NEXT_MIDNIGHT,
+                        format!("Shared timespan fractions sum to {}", x),
+                    ));
+                }
+            }
+        }
+    }
+
+    retval.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.affected_dates.cmp(&a.affected_dates))
+            .then(a.coordinates.cmp(&b.coordinates))
+            .then(a.start.cmp(&b.start))
+    });
+
+    retval
+}
+
+pub fn ranked_audit_to_tsv(issues: &[RankedIssue], filename: &str) -> Result<(), Box<dyn Error>> {
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+
+    writeln!(
+        writer,
+        "Rank \u{0009} Site \u{0009} Exam \u{0009} Context \u{0009} Day of Week \u{0009} Issue \u{0009} Start \u{0009} End \u{0009} RVUs \u{0009} BVUs \u{0009} Dates \u{0009} Business Hours \u{0009} Score \u{0009} Description"
+    )?;
+    for (n, issue) in issues.iter().enumerate() {
+        writeln!(
+            writer,
+            "{} \u{0009} {} \u{0009} {} \u{0009} {} \u{0009} {} \u{0009} {:?} \u{0009} {} \u{0009} {} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {} \u{0009} {} \u{0009} {:.2} \u{0009} {}",
+            n + 1,
+            issue.coordinates.facility,
+            issue.coordinates.subspecialty,
+            issue.coordinates.context,
+            issue.coordinates.weekday,
+            issue.kind,
+            issue.start,
+            issue.end,
+            issue.rvus,
+            issue.bvus,
+            issue.affected_dates,
+            issue.business_hours,
+            issue.score,
+            issue.description
+        )?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::{analysis::coverage_audit::audit, work_coverage_map::testing};
+
+    #[test]
+    fn issues_are_ranked_by_weighted_volume_then_dates() {
+        //Every covered day has a gap from 09:00 to 12:00.
+        let manifest = testing::manifest(
+            "title: Test
+rotation_manifest:
+- rotation: Day
+  location: Remote
+  responsibilities:
+  - sites: SH
+    exams: General CT
+    contexts: ED
+    days: Mon/Sat/Sun
+    time_periods:
+    - 00:00 CD-09:00 CD
+    - 12:00 CD-24:00 CD
+",
+        );
+        let mut coverage_map = testing::coverage_map(&manifest);
+        //Two Mondays in business hours, so 2 RVUs count double.
+        testing::add_exam(&mut coverage_map, testing::at(8, 10, 0), 1.0, 1);
+        testing::add_exam(&mut coverage_map, testing::at(15, 10, 30), 1.0, 1);
+        //Covered, so not in any issue.
+        testing::add_exam(&mut coverage_map, testing::at(8, 13, 0), 5.0, 1);
+        //One Saturday and one Sunday, scoring the same as the Mondays over fewer dates.
+        testing::add_exam(&mut coverage_map, testing::at(6, 10, 0), 4.0, 1);
+        testing::add_exam(&mut coverage_map, testing::at(7, 11, 0), 4.0, 1);
+
+        let audit_result = audit(&mut coverage_map);
+        let issues = rank_audit(&coverage_map, &audit_result);
+
+        let ranked: Vec<(chrono::Weekday, f64, usize, bool, f64)> = issues
+            .iter()
+            .map(|issue| {
+                assert_eq!(issue.kind, IssueKind::Gap);
+                assert_eq!(issue.start.to_string(), "09:00");
+                assert_eq!(issue.end.to_string(), "12:00");
+                (
+                    issue.coordinates.weekday,
+                    issue.rvus,
+                    issue.affected_dates,
+                    issue.business_hours,
+                    issue.score,
+                )
+            })
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                (chrono::Weekday::Mon, 2.0, 2, true, 4.0),
+                (chrono::Weekday::Sat, 4.0, 1, false, 4.0),
+                (chrono::Weekday::Sun, 4.0, 1, false, 4.0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,511 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use crate::{
+    coverage::{
+        analysis::comparison::get_by_rotation_average,
+        work_coverage_map::{generics::WorkCoverageMap, maps::CoverageMap},
+    },
+    globals,
+    rotations::{
+        description::WrappedSortable,
+        manifest::Manifest,
+        optimizer::{OptimizerMeasure, OptimizerObjective, OptimizerSettings},
+        responsibility::RotationResponsibility,
+        time_modifiers::{RelativeTime, TimeSinceMidnight, NEXT_MIDNIGHT},
+        timespan::Timespan,
+    },
+};
+
+//Rotation, responsibility, and time period indices of a time span within a manifest.
+type SpanIndex = (usize, usize, usize);
+//The span ending at a boundary and the span starting there.
+type SpanPair = (SpanIndex, SpanIndex);
+
+//Improvements smaller than this are rounding noise and don't justify moving a boundary.
+const MIN_IMPROVEMENT: f64 = 1e-9;
+
+/// A handoff where one rotation's time periods end exactly when another's begin for the same work.
+/// The two may write the handoff differently, e.g. a night ending 08:00 ND hands off to a day starting 08:00 CD.
+/// Every pair of spans is moved together, so the work stays covered with no gaps or overlaps.
+/// Boundaries only move within their day, e.g. a CD boundary stays between 00:00 CD and 24:00 CD.
+/// `original` and `time` are written the way the ending spans write them.
+pub struct Boundary {
+    pub from_rotation: String,
+    pub to_rotation: String,
+    pub original: RelativeTime,
+    pub time: RelativeTime,
+    pairs: Vec<SpanPair>,
+}
+
+impl Boundary {
+    fn candidates(&self, manifest: &Manifest, grid_minutes: u64) -> Vec<TimeSinceMidnight> {
+        (0..NEXT_MIDNIGHT.to_minutes())
+            .step_by(grid_minutes as usize)
+            .map(TimeSinceMidnight::new)
+            .filter(|time| {
+                self.pairs.iter().all(|(ending, starting)| {
+                    let ending_span = span(manifest, ending);
+                    let starting_span = span(manifest, starting);
+                    let stop = ending_span.stop.with_time(*time);
+                    let start = starting_span.start.with_time(*time);
+                    responsibility(manifest, ending)
+                        .weekdays()
+                        .into_iter()
+                        .all(|day| ending_span.start.cmp_on(&stop, day) == Ordering::Less)
+                        && responsibility(manifest, starting)
+                            .weekdays()
+                            .into_iter()
+                            .all(|day| start.cmp_on(&starting_span.stop, day) == Ordering::Less)
+                })
+            })
+            .collect()
+    }
+
+    fn set_time(&mut self, manifest: &mut Manifest, time: TimeSinceMidnight) {
+        for (ending, starting) in &self.pairs {
+            let stop = span(manifest, ending).stop.with_time(time);
+            span_mut(manifest, ending).stop = stop;
+            let start = span(manifest, starting).start.with_time(time);
+            span_mut(manifest, starting).start = start;
+        }
+        self.time = self.time.with_time(time);
+    }
+}
+
+fn responsibility<'a>(manifest: &'a Manifest, index: &SpanIndex) -> &'a RotationResponsibility {
+    &manifest.rotation_manifest[index.0]
+        .responsibilities
+        .get()
+        .expect("Boundaries only index existing responsibilities")[index.1]
+}
+
+fn span<'a>(manifest: &'a Manifest, index: &SpanIndex) -> &'a Timespan {
+    &responsibility(manifest, index)
+        .time_periods
+        .get()
+        .as_ref()
+        .expect("Boundaries only index existing time periods")[index.2]
+}
+
+fn span_mut<'a>(manifest: &'a mut Manifest, index: &SpanIndex) -> &'a mut Timespan {
+    let responsibility = &mut manifest.rotation_manifest[index.0]
+        .responsibilities
+        .get_mut()
+        .expect("Boundaries only index existing responsibilities")[index.1];
+    &mut responsibility
+        .time_periods
+        .get_mut()
+        .expect("Boundaries only index existing time periods")[index.2]
+}
+
+fn sorted(set: HashSet<String>) -> Vec<String> {
+    let mut retval: Vec<String> = set.into_iter().collect();
+    retval.sort();
+    retval
+}
+
+//A time span of an adjustable rotation, with when in the week it starts and stops.
+struct AdjustableSpan<'a> {
+    index: SpanIndex,
+    rotation: &'a str,
+    key: [Vec<String>; 4],
+    timespan: &'a Timespan,
+    starts: BTreeSet<i64>,
+    stops: BTreeSet<i64>,
+}
+
+//When in the week, in minutes from Monday midnight, `time` happens for a responsibility on each of its days.
+fn week_minutes(time: &RelativeTime, responsibility: &RotationResponsibility) -> BTreeSet<i64> {
+    let week = 7 * NEXT_MIDNIGHT.to_minutes() as i64;
+    responsibility
+        .weekdays()
+        .into_iter()
+        .map(|day| {
+            (day.num_days_from_monday() as i64 * NEXT_MIDNIGHT.to_minutes() as i64
+                + time.resolve(day))
+            .rem_euclid(week)
+        })
+        .collect()
+}
+
+//The sites, exams, contexts, and days a responsibility covers.
+pub(crate) fn coverage_key(responsibility: &RotationResponsibility) -> [Vec<String>; 4] {
+    let all_days: Vec<String> = globals::ALL_DAYS
+        .iter()
+        .map(|day| day.to_string())
+        .collect();
+    let all_days: Vec<&str> = all_days.iter().map(|day| day.as_str()).collect();
+    [
+        sorted(responsibility.sites.to_vec(globals::FACILITIES)),
+        sorted(responsibility.exams.to_vec(globals::SUBSPECIALTIES)),
+        sorted(responsibility.contexts.to_vec(globals::CONTEXTS)),
+        sorted(responsibility.days.to_vec(&all_days)),
+    ]
+}
+
+/// Finds the handoffs between adjustable rotations in a manifest.
+/// Spans hand off when they cover the same sites, exams, and contexts and one ends at the same times in the week
+/// as the other starts, however each writes it. The times of day have to match for the spans to move together.
+/// A span end shared by more than one handoff can't move without opening a gap, so those handoffs are left alone.
+pub fn find_boundaries(manifest: &Manifest, settings: &OptimizerSettings) -> Vec<Boundary> {
+    let mut spans: Vec<AdjustableSpan> = Vec::new();
+    for (rotation_index, desc) in manifest.rotation_manifest.iter().enumerate() {
+        if !settings.is_adjustable(&desc.rotation) {
+            continue;
+        }
+        for (responsibility_index, responsibility) in desc
+            .responsibilities
+            .get()
+            .iter()
+            .flat_map(|x| x.iter())
+            .enumerate()
+        {
+            //Fractional responsibilities have no boundaries to move.
+            if responsibility.weekly_fraction.is_some() {
+                continue;
+            }
+            let key = coverage_key(responsibility);
+            for (span_index, timespan) in responsibility
+                .time_periods
+                .get()
+                .iter()
+                .flatten()
+                .enumerate()
+            {
+                spans.push(AdjustableSpan {
+                    index: (rotation_index, responsibility_index, span_index),
+                    rotation: desc.rotation.as_str(),
+                    key: key.clone(),
+                    timespan,
+                    starts: week_minutes(&timespan.start, responsibility),
+                    stops: week_minutes(&timespan.stop, responsibility),
+                });
+            }
+        }
+    }
+
+    let mut grouped: BTreeMap<(String, String, String), (RelativeTime, Vec<SpanPair>)> =
+        BTreeMap::new();
+    let mut uses: HashMap<(SpanIndex, bool), usize> = HashMap::new();
+    for ending in &spans {
+        for starting in &spans {
+            //The days are compared through when the spans happen, as a handoff to the next day shifts them.
+            if ending.rotation == starting.rotation
+                || ending.key[..3] != starting.key[..3]
+                || ending.stops != starting.starts
+                || ending.timespan.stop.get_time() != starting.timespan.start.get_time()
+            {
+                continue;
+            }
+            *uses.entry((ending.index, false)).or_default() += 1;
+            *uses.entry((starting.index, true)).or_default() += 1;
+            grouped
+                .entry((
+                    ending.rotation.to_string(),
+                    starting.rotation.to_string(),
+                    ending.timespan.stop.to_string(),
+                ))
+                .or_insert_with(|| (ending.timespan.stop.clone(), Vec::new()))
+                .1
+                .push((ending.index, starting.index));
+        }
+    }
+
+    grouped
+        .into_iter()
+        .filter(|(_, (_, pairs))| {
+            pairs.iter().all(|(ending, starting)| {
+                uses.get(&(*ending, false)) == Some(&1) && uses.get(&(*starting, true)) == Some(&1)
+            })
+        })
+        .map(
+            |((from_rotation, to_rotation, _), (time, pairs))| Boundary {
+                from_rotation,
+                to_rotation,
+                original: time.clone(),
+                time,
+                pairs,
+            },
+        )
+        .collect()
+}
+
+/// Scores a manifest's coverage of the work in `coverage_map`; lower is better.
+/// The variance objective only considers `rotations`, the ones whose boundaries can move.
+fn score(
+    coverage_map: &mut CoverageMap,
+    manifest: &Manifest,
+    settings: &OptimizerSettings,
+    rotations: &BTreeSet<String>,
+) -> Result<f64, Box<dyn Error>> {
+    coverage_map.clear_coverage();
+    coverage_map.add_coverage_from_manifest(manifest)?;
+    let averages = get_by_rotation_average(coverage_map);
+    let volume = |rotation: &str| -> f64 {
+        match averages.get(rotation) {
+            Some(datum) => match settings.measure() {
+                OptimizerMeasure::Rvu => datum.rvu,
+                OptimizerMeasure::Bvu => datum.bvu,
+            },
+            None => 0.0,
+        }
+    };
+
+    match settings.objective() {
+        OptimizerObjective::Variance => {
+            if rotations.is_empty() {
+                return Ok(0.0);
+            }
+            let values: Vec<f64> = rotations.iter().map(|rotation| volume(rotation)).collect();
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            Ok(values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / values.len() as f64)
+        }
+        OptimizerObjective::Baselines => {
+            let baselines = match &manifest.baselines {
+                Some(x) if !x.is_empty() => x,
+                _ => {
+                    return Err(
+                        "The baselines objective requires baselines in the manifest.".into(),
+                    );
+                }
+            };
+            let mut retval: f64 = 0.0;
+            for baseline in baselines {
+                let target = match settings.measure() {
+                    OptimizerMeasure::Rvu => baseline.rvu,
+                    OptimizerMeasure::Bvu => baseline.bvu,
+                };
+                if target > 0.0 {
+                    retval += ((volume(&baseline.rotation) - target) / target).powi(2);
+                }
+            }
+            Ok(retval)
+        }
+    }
+}
+
+/// The boundaries considered and how much moving them improved the objective.
+pub struct BoundaryOptimization {
+    pub boundaries: Vec<Boundary>,
+    pub initial_score: f64,
+    pub final_score: f64,
+    pub passes: usize,
+}
+
+impl BoundaryOptimization {
+    pub fn moved(&self) -> impl Iterator<Item = &Boundary> {
+        self.boundaries
+            .iter()
+            .filter(|boundary| boundary.time != boundary.original)
+    }
+
+    pub fn to_tsv(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        let file = File::create(filename)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "From Rotation \u{0009} To Rotation \u{0009} Original \u{0009} Proposed \u{0009} Responsibilities"
+        )?;
+        for boundary in &self.boundaries {
+            writeln!(
+                writer,
+                "{} \u{0009} {} \u{0009} {} \u{0009} {} \u{0009} {}",
+                boundary.from_rotation,
+                boundary.to_rotation,
+                boundary.original.to_string(),
+                boundary.time.to_string(),
+                boundary.pairs.len()
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Moves rotation handoffs in `manifest` along the settings' grid to improve the objective, one boundary at a time,
+/// until a pass over every boundary changes nothing. `coverage_map` supplies the work and is left untouched.
+pub fn optimize_boundaries(
+    coverage_map: &CoverageMap,
+    manifest: &mut Manifest,
+    settings: &OptimizerSettings,
+) -> Result<BoundaryOptimization, Box<dyn Error>> {
+    let mut boundaries = find_boundaries(manifest, settings);
+    let rotations: BTreeSet<String> = boundaries
+        .iter()
+        .flat_map(|boundary| {
+            [
+                boundary.from_rotation.to_string(),
+                boundary.to_rotation.to_string(),
+            ]
+        })
+        .collect();
+
+    let mut working_map = coverage_map.clone();
+    let initial_score = score(&mut working_map, manifest, settings, &rotations)?;
+    let mut current_score = initial_score;
+    let mut passes: usize = 0;
+
+    while passes < settings.max_passes() {
+        passes += 1;
+        let mut changed = false;
+        for boundary in &mut boundaries {
+            let current = *boundary.time.get_time();
+            let mut best: Option<(TimeSinceMidnight, f64)> = None;
+            for candidate in boundary.candidates(manifest, settings.grid_minutes()) {
+                if candidate == current {
+                    continue;
+                }
+                boundary.set_time(manifest, candidate);
+                let candidate_score = score(&mut working_map, manifest, settings, &rotations)?;
+                let best_score = best.as_ref().map(|x| x.1).unwrap_or(current_score);
+                if candidate_score < best_score - MIN_IMPROVEMENT {
+                    best = Some((candidate, candidate_score));
+                }
+            }
+            match best {
+                Some((time, best_score)) => {
+                    boundary.set_time(manifest, time);
+                    current_score = best_score;
+                    changed = true;
+                }
+                None => boundary.set_time(manifest, current),
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    Ok(BoundaryOptimization {
+        boundaries,
+        initial_score,
+        final_score: current_score,
+        passes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::{
+        analysis::coverage_audit::audit, malformed_coverage::CoverageError,
+        work_coverage_map::testing,
+    };
+
+    fn manifest(night_stop: &str, day_days: &str, day_start: &str) -> Manifest {
+        serde_yaml::from_str(&format!(
+            "title: Test
+rotation_manifest:
+- rotation: Night
+  location: Remote
+  responsibilities:
+  - sites: SH
+    exams: General CT
+    contexts: ED
+    days: Mon/Tue/Wed/Thu
+    time_periods:
+    - 17:00 CD-{}
+- rotation: Day
+  location: Remote
+  responsibilities:
+  - sites: SH
+    exams: General CT
+    contexts: ED
+    days: {}
+    time_periods:
+    - {}-17:00 CD
+",
+            night_stop, day_days, day_start
+        ))
+        .expect("Test manifest should parse")
+    }
+
+    #[test]
+    fn handoffs_to_the_next_day_are_found_and_moved() {
+        let mut manifest = manifest("08:00 ND", "Tue/Wed/Thu/Fri", "08:00 CD");
+        let mut boundaries = find_boundaries(&manifest, &OptimizerSettings::default());
+        assert_eq!(boundaries.len(), 1);
+        let boundary = &mut boundaries[0];
+        assert_eq!(boundary.from_rotation, "Night");
+        assert_eq!(boundary.to_rotation, "Day");
+        assert_eq!(boundary.original.to_string(), "08:00 ND");
+
+        boundary.set_time(&mut manifest, TimeSinceMidnight::new(7 * 60));
+        assert_eq!(boundary.time.to_string(), "07:00 ND");
+        assert_eq!(span(&manifest, &(0, 0, 0)).stop.to_string(), "07:00 ND");
+        assert_eq!(span(&manifest, &(1, 0, 0)).start.to_string(), "07:00 CD");
+    }
+
+    #[test]
+    fn handoffs_at_different_times_of_the_week_are_not_boundaries() {
+        //The night ends Tuesday through Friday mornings, but the day also starts Monday morning.
+        let manifest = manifest("08:00 ND", "Mon/Tue/Wed/Thu/Fri", "08:00 CD");
+        assert!(find_boundaries(&manifest, &OptimizerSettings::default()).is_empty());
+    }
+
+    #[test]
+    fn the_handoff_moves_to_where_volume_is_even() {
+        let mut manifest = testing::manifest(
+            "title: Test
+rotation_manifest:
+- rotation: Day
+  location: Remote
+  responsibilities:
+  - sites: SH
+    exams: General CT
+    contexts: ED
+    days: Mon
+    time_periods:
+    - 00:00 CD-12:00 CD
+- rotation: Late
+  location: Remote
+  responsibilities:
+  - sites: SH
+    exams: General CT
+    contexts: ED
+    days: Mon
+    time_periods:
+    - 12:00 CD-24:00 CD
+",
+        );
+        //Day reads all four exams at first. Only a handoff at 08:00 splits them evenly on the 30 minute grid.
+        let mut coverage_map = testing::coverage_map(&manifest);
+        for (hour, minute) in [(6, 45), (7, 45), (8, 15), (9, 15)] {
+            testing::add_exam(&mut coverage_map, testing::at(8, hour, minute), 1.0, 1);
+        }
+
+        let result =
+            optimize_boundaries(&coverage_map, &mut manifest, &OptimizerSettings::default())
+                .expect("Optimization should succeed");
+        assert_eq!(result.initial_score, 4.0);
+        assert_eq!(result.final_score, 0.0);
+        assert_eq!(result.moved().count(), 1);
+
+        //The two spans still meet, now at 08:00.
+        assert_eq!(span(&manifest, &(0, 0, 0)).stop.to_string(), "08:00 CD");
+        assert_eq!(span(&manifest, &(1, 0, 0)).start.to_string(), "08:00 CD");
+
+        let mut optimized_map = testing::coverage_map(&manifest);
+        for (hour, minute) in [(6, 45), (7, 45), (8, 15), (9, 15)] {
+            testing::add_exam(&mut optimized_map, testing::at(8, hour, minute), 1.0, 1);
+        }
+        for (_coords, errs) in audit(&mut optimized_map) {
+            match errs {
+                CoverageError::MalformedCoverage(errs) => {
+                    assert!(errs.gaps.is_empty());
+                    assert!(errs.overlaps.is_empty());
+                }
+                CoverageError::NoCoverage(_) => panic!("Monday is covered"),
+            }
+        }
+        let averages = get_by_rotation_average(&optimized_map);
+        assert_eq!(averages["Day"].rvu, 2.0);
+        assert_eq!(averages["Late"].rvu, 2.0);
+    }
+}
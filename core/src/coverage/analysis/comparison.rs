@@ -11,7 +11,7 @@ use crate::{
     serialization::output::JSONFileOut,
 };
 
-fn get_by_rotation_average(coverage_map: &CoverageMap) -> BTreeMap<String, ComparisonDatum> {
+pub fn get_by_rotation_average(coverage_map: &CoverageMap) -> BTreeMap<String, ComparisonDatum> {
     let mut retval: BTreeMap<String, ComparisonDatum> = BTreeMap::new();
     let mut dates: BTreeMap<String, BTreeSet<NaiveDate>> = BTreeMap::new();
 
@@ -56,16 +56,23 @@ fn get_by_rotation_average(coverage_map: &CoverageMap) -> BTreeMap<String, Compa
     retval
 }
 
-type ComparisonResult = BTreeMap<String, BTreeMap<String, ComparisonDatum>>;
+pub(crate) type ComparisonResult = BTreeMap<String, BTreeMap<String, ComparisonDatum>>;
 
 pub fn compare(active_map: &CoverageMap, proposed_map: &CoverageMap) -> ComparisonResult {
-    let active: BTreeMap<String, ComparisonDatum> = get_by_rotation_average(active_map);
-    let proposed: BTreeMap<String, ComparisonDatum> = get_by_rotation_average(proposed_map);
+    compare_labeled("active", active_map, "proposed", proposed_map)
+}
 
+/// Each rotation's average daily volume in two coverage maps, in the layout `compare` uses with the given labels in place of active and proposed.
+pub fn compare_labeled(
+    label: &str,
+    map: &CoverageMap,
+    other_label: &str,
+    other_map: &CoverageMap,
+) -> ComparisonResult {
     let mut retval: BTreeMap<String, BTreeMap<String, ComparisonDatum>> = BTreeMap::new();
 
-    retval.insert("active".to_string(), active);
-    retval.insert("proposed".to_string(), proposed);
+    retval.insert(label.to_string(), get_by_rotation_average(map));
+    retval.insert(other_label.to_string(), get_by_rotation_average(other_map));
 
     retval
 }
@@ -2,7 +2,7 @@ use std::{collections::HashMap, error::Error, io::Write};
 
 use crate::{
     coverage::{
-        coordinate::CoverageCoordinates, coverage_and_work_day::CoverageAndWorkDay, malformed_coverage::CoverageError, work_coverage_map::maps::CoverageMap
+        coordinate::CoverageCoordinates, coverage_and_work_day::CoverageAndWorkDay, malformed_coverage::{AuditRecord, CoverageError}, work_coverage_map::maps::CoverageMap
     },
     rotations::rotation_error::RotationManifestParseError,
 };
@@ -28,6 +28,22 @@ pub fn audit(coverage_map: &mut CoverageMap) -> HashMap<CoverageCoordinates, Cov
     retval
 }
 
+/// Collects the audit result into records sorted by coordinate so it can be written as JSON.
+pub fn audit_to_records(
+    audit_result: &HashMap<CoverageCoordinates, CoverageError>,
+) -> Vec<AuditRecord<'_>> {
+    let mut sorted_keys: Vec<&CoverageCoordinates> = audit_result.keys().collect();
+    sorted_keys.sort();
+
+    sorted_keys
+        .into_iter()
+        .map(|coords| AuditRecord {
+            coordinates: coords,
+            error: audit_result.get(coords).expect("Should be a key"),
+        })
+        .collect()
+}
+
 pub fn audit_to_stream<T: Write>(
     audit_result: &HashMap<CoverageCoordinates, CoverageError>,
     primary_error_writer: &mut T,
@@ -64,13 +80,9 @@ pub fn audit_to_stream<T: Write>(
             }
             CoverageError::MalformedCoverage(errs) => {
                 if !errs.gaps.is_empty() {
-                    for (rotation1, rotation2, desc, rvus) in &errs.gaps {
+                    for gap in &errs.gaps {
                         no_errs = false;
-                        writeln!(
-                            primary_error_writer,
-                            "{} Coverage gap: {}-{} {} ({} rvus)",
-                            coordstr, rotation1, rotation2, desc, rvus
-                        )?;
+                        writeln!(primary_error_writer, "{} Coverage gap: {}", coordstr, gap)?;
                     }
                 }
                 if !errs.overlaps.is_empty() {
@@ -94,6 +106,14 @@ pub fn audit_to_stream<T: Write>(
                     }
                     None => (),
                 }
+                if let Some(x) = errs.incorrect_shared_fraction {
+                    no_errs = false;
+                    writeln!(
+                        primary_error_writer,
+                        "{} Incorrect shared fraction: {}",
+                        coordstr, x
+                    )?;
+                }
                 if errs.no_work {
                     writeln!(work_gap_writer, "{} No work", coordstr)?;
                 }
@@ -0,0 +1,434 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use crate::{
+    coverage::{
+        coordinate::CoverageCoordinates, coverage_and_work_day::CoverageAndWorkDay,
+        work_coverage_map::maps::CoverageMap,
+    },
+    globals::ALL_DAYS,
+    rotations::time_modifiers::TimeSinceMidnight,
+};
+
+const MINUTES_PER_DAY: u64 = 24 * 60;
+const FRACTION_TOLERANCE: f64 = 0.001;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellStatus {
+    Gap,
+    Partial,
+    Covered,
+    Overlap,
+    Fractional,
+    IncorrectFraction,
+}
+
+impl CellStatus {
+    fn css_class(&self) -> &str {
+        match self {
+            CellStatus::Gap => "gap",
+            CellStatus::Partial => "partial",
+            CellStatus::Covered => "covered",
+            CellStatus::Overlap => "overlap",
+            CellStatus::Fractional => "fractional",
+            CellStatus::IncorrectFraction => "incorrect_fraction",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridCell {
+    pub status: CellStatus,
+    pub rotations: Vec<String>,
+}
+
+impl GridCell {
+    fn gap() -> GridCell {
+        GridCell {
+            status: CellStatus::Gap,
+            rotations: Vec::new(),
+        }
+    }
+
+    fn label(&self) -> String {
+        self.rotations.join(" / ")
+    }
+}
+
+//Rows are facility, subspecialty, and context. Each row has one cell per weekday and time slot, Monday first.
+type GridRowKey = (String, String, String);
+
+pub struct CoverageGrid {
+    step_minutes: u64,
+    rows: BTreeMap<GridRowKey, Vec<GridCell>>,
+}
+
+fn build_cell(
+    coverage_and_workday: Option<&CoverageAndWorkDay>,
+    slot_start: u64,
+    slot_end: u64,
+) -> GridCell {
+    let coverage = match coverage_and_workday.and_then(|x| x.get_coverage()) {
+        Some(x) => x,
+        None => {
+            return GridCell::gap();
+        }
+    };
+
+    let fractional_coverages = coverage.fractional_units();
+
+    let mut rotations: Vec<String> = Vec::new();
+    let mut intersections: Vec<(u64, u64)> = Vec::new();
+    let mut shared = false;
+    for tcu in coverage.temporal_units() {
+        let start = tcu.start.to_minutes().max(slot_start);
+        let end = tcu.end.to_minutes().min(slot_end);
+        if start < end {
+            intersections.push((start, end));
+            let label = if tcu.is_shared() {
+                shared = true;
+                format!("{} ({:.2})", tcu.get_rotation(), tcu.get_fraction())
+            } else {
+                tcu.get_rotation()
+            };
+            if !rotations.contains(&label) {
+                rotations.push(label);
+            }
+        }
+    }
+
+    //Sorted coverage start times, so the union can be measured in one pass.
+    intersections.sort();
+    let mut summed: u64 = 0;
+    let mut union: u64 = 0;
+    let mut farthest_end: u64 = slot_start;
+    for (start, end) in &intersections {
+        summed += end - start;
+        if *end > farthest_end {
+            union += end - (*start).max(farthest_end);
+            farthest_end = *end;
+        }
+    }
+
+    //Fractional coverage picks up whatever part of the slot the temporal coverage leaves.
+    let mut fraction_sum: f64 = 0.0;
+    if union < slot_end - slot_start {
+        for fcu in fractional_coverages {
+            fraction_sum += fcu.get_fraction();
+            rotations.push(format!("{} ({:.2})", fcu.get_rotation(), fcu.get_fraction()));
+        }
+    }
+
+    let status = if intersections.is_empty() && fractional_coverages.is_empty() {
+        CellStatus::Gap
+    } else if !fractional_coverages.is_empty()
+        && union < slot_end - slot_start
+        && (fraction_sum - 1.0).abs() > FRACTION_TOLERANCE
+    {
+        CellStatus::IncorrectFraction
+    } else if shared || intersections.is_empty() {
+        CellStatus::Fractional
+    } else if summed > union {
+        CellStatus::Overlap
+    } else if union < slot_end - slot_start && fractional_coverages.is_empty() {
+        CellStatus::Partial
+    } else {
+        CellStatus::Covered
+    };
+
+    GridCell { status, rotations }
+}
+
+impl CoverageGrid {
+    pub fn build(coverage_map: &CoverageMap, step_minutes: u64) -> CoverageGrid {
+        let mut keys: Vec<GridRowKey> = Vec::new();
+        coverage_map.foreach(|coords: &CoverageCoordinates, _cawd: &CoverageAndWorkDay| {
+            let key = (
+                coords.facility.to_string(),
+                coords.subspecialty.to_string(),
+                coords.context.to_string(),
+            );
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        });
+
+        let mut rows: BTreeMap<GridRowKey, Vec<GridCell>> = BTreeMap::new();
+        for key in keys {
+            let mut cells: Vec<GridCell> = Vec::new();
+            for weekday in ALL_DAYS {
+                let coords = CoverageCoordinates {
+                    facility: key.0.to_string(),
+                    subspecialty: key.1.to_string(),
+                    context: key.2.to_string(),
+                    weekday: **weekday,
+                };
+                let coverage_and_workday = coverage_map.get_coverageandworkday(&coords);
+
+                let mut slot_start: u64 = 0;
+                while slot_start < MINUTES_PER_DAY {
+                    let slot_end = (slot_start + step_minutes).min(MINUTES_PER_DAY);
+                    cells.push(build_cell(coverage_and_workday, slot_start, slot_end));
+                    slot_start = slot_end;
+                }
+            }
+            rows.insert(key, cells);
+        }
+
+        CoverageGrid { step_minutes, rows }
+    }
+
+    fn slot_labels(&self) -> Vec<String> {
+        let mut retval: Vec<String> = Vec::new();
+        let mut slot_start: u64 = 0;
+        while slot_start < MINUTES_PER_DAY {
+            retval.push(TimeSinceMidnight::from_minutes(slot_start).to_string());
+            slot_start += self.step_minutes;
+        }
+        retval
+    }
+
+    pub fn to_csv(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(b',')
+            .quote(b'"')
+            .has_headers(false) //write manually
+            .from_path(filename)?;
+
+        let mut headers: Vec<String> = vec![
+            "Facility".to_string(),
+            "Subspecialty".to_string(),
+            "Context".to_string(),
+        ];
+        for weekday in ALL_DAYS {
+            for slot in self.slot_labels() {
+                headers.push(format!("{} {}", weekday, slot));
+            }
+        }
+        writer.write_record(headers)?;
+
+        for ((facility, subspecialty, context), cells) in &self.rows {
+            let mut record: Vec<String> =
+                vec![facility.to_string(), subspecialty.to_string(), context.to_string()];
+            for cell in cells {
+                record.push(cell.label());
+            }
+            writer.write_record(record)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn to_html(&self, filename: &str, title: &str) -> Result<(), Box<dyn Error>> {
+        let file = File::create(filename)?;
+        let mut writer = BufWriter::new(file);
+
+        let slot_labels = self.slot_labels();
+
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, "<html>")?;
+        writeln!(writer, "<head>")?;
+        writeln!(writer, "<meta charset=\"utf-8\">")?;
+        writeln!(writer, "<title>{}</title>", escape_html(title))?;
+        writeln!(writer, "<style>{}</style>", STYLE)?;
+        writeln!(writer, "</head>")?;
+        writeln!(writer, "<body>")?;
+        writeln!(writer, "<h1>{}</h1>", escape_html(title))?;
+        writeln!(writer, "<p class=\"legend\">")?;
+        for status in [
+            CellStatus::Covered,
+            CellStatus::Fractional,
+            CellStatus::Partial,
+            CellStatus::Gap,
+            CellStatus::Overlap,
+            CellStatus::IncorrectFraction,
+        ] {
+            writeln!(
+                writer,
+                "<span class=\"{}\">{:?}</span>",
+                status.css_class(),
+                status
+            )?;
+        }
+        writeln!(writer, "</p>")?;
+
+        writeln!(writer, "<table>")?;
+        writeln!(writer, "<thead>")?;
+        write!(writer, "<tr><th rowspan=\"2\">Facility</th><th rowspan=\"2\">Subspecialty</th><th rowspan=\"2\">Context</th>")?;
+        for weekday in ALL_DAYS {
+            write!(
+                writer,
+                "<th class=\"day\" colspan=\"{}\">{}</th>",
+                slot_labels.len(),
+                weekday
+            )?;
+        }
+        writeln!(writer, "</tr>")?;
+        write!(writer, "<tr>")?;
+        for _ in ALL_DAYS {
+            for slot in &slot_labels {
+                write!(writer, "<th class=\"slot\">{}</th>", slot)?;
+            }
+        }
+        writeln!(writer, "</tr>")?;
+        writeln!(writer, "</thead>")?;
+
+        writeln!(writer, "<tbody>")?;
+        for ((facility, subspecialty, context), cells) in &self.rows {
+            write!(
+                writer,
+                "<tr><th>{}</th><th>{}</th><th>{}</th>",
+                escape_html(facility),
+                escape_html(subspecialty),
+                escape_html(context)
+            )?;
+
+            //Adjacent identical cells within a day are merged to keep the table compact.
+            for day_cells in cells.chunks(slot_labels.len()) {
+                let mut n: usize = 0;
+                while n < day_cells.len() {
+                    let mut span: usize = 1;
+                    while n + span < day_cells.len() && day_cells[n + span] == day_cells[n] {
+                        span += 1;
+                    }
+                    let label = day_cells[n].label();
+                    write!(
+                        writer,
+                        "<td class=\"{}\" colspan=\"{}\" title=\"{}\">{}</td>",
+                        day_cells[n].status.css_class(),
+                        span,
+                        escape_html(&label),
+                        escape_html(&label)
+                    )?;
+                    n += span;
+                }
+            }
+            writeln!(writer, "</tr>")?;
+        }
+        writeln!(writer, "</tbody>")?;
+        writeln!(writer, "</table>")?;
+        writeln!(writer, "</body>")?;
+        writeln!(writer, "</html>")?;
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+fn escape_html(str: &str) -> String {
+    str.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; font-size: 8pt; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #999; padding: 1px 3px; white-space: nowrap; }
+td { overflow: hidden; text-overflow: ellipsis; max-width: 12em; }
+th.day { border-left: 2px solid #000; }
+th.slot { font-weight: normal; writing-mode: vertical-rl; }
+.legend span { display: inline-block; padding: 2px 6px; margin-right: 4px; border: 1px solid #999; }
+.covered { background-color: #c8e6c9; }
+.fractional { background-color: #bbdefb; }
+.partial { background-color: #fff59d; }
+.gap { background-color: #ef9a9a; }
+.overlap { background-color: #ffcc80; }
+.incorrect_fraction { background-color: #ce93d8; }
+@media print { @page { size: landscape; margin: 0.5cm; } body { font-size: 6pt; } }
+";
+
+#[cfg(test)]
+mod tests {
+    use chrono::Weekday;
+
+    use super::*;
+    use crate::{coverage::work_coverage_map::testing, globals::SH};
+
+    //Monday coverage at SH General CT in the ED, one responsibility per (rotation, members) pair.
+    fn coverage(rotations: &[(&str, &str)]) -> CoverageMap {
+        let mut yaml = "title: Test\nrotation_manifest:\n".to_string();
+        for (rotation, members) in rotations {
+            yaml.push_str(&format!(
+                "- rotation: {}\n  location: Remote\n  responsibilities:\n  - sites: SH\n    exams: General CT\n    contexts: ED\n    days: Mon\n    {}\n",
+                rotation,
+                members.replace('\n', "\n    ")
+            ));
+        }
+        testing::coverage_map(&testing::manifest(&yaml))
+    }
+
+    fn cell(coverage_map: &CoverageMap, start_hour: u64, end_hour: u64) -> GridCell {
+        let coords = CoverageCoordinates {
+            facility: SH.to_string(),
+            subspecialty: "General CT".to_string(),
+            context: "ED".to_string(),
+            weekday: Weekday::Mon,
+        };
+        build_cell(
+            coverage_map.get_coverageandworkday(&coords),
+            start_hour * 60,
+            end_hour * 60,
+        )
+    }
+
+    const DAY: (&str, &str) = ("Day", "time_periods:\n- 08:00 CD-17:00 CD");
+    const SHARED: &str = "time_periods:\n- 08:00 CD-17:00 CD\nweekly_fraction: 0.5";
+
+    #[test]
+    fn no_coverage_is_a_gap() {
+        assert_eq!(build_cell(None, 9 * 60, 10 * 60), GridCell::gap());
+    }
+
+    #[test]
+    fn covered() {
+        let cell = cell(&coverage(&[DAY]), 9, 10);
+        assert_eq!(cell.status, CellStatus::Covered);
+        assert_eq!(cell.rotations, vec!["Day"]);
+    }
+
+    #[test]
+    fn gap_outside_coverage() {
+        assert_eq!(cell(&coverage(&[DAY]), 20, 21), GridCell::gap());
+    }
+
+    #[test]
+    fn partial() {
+        assert_eq!(cell(&coverage(&[DAY]), 16, 18).status, CellStatus::Partial);
+    }
+
+    #[test]
+    fn overlap() {
+        let coverage_map = coverage(&[DAY, ("Late", "time_periods:\n- 09:00 CD-20:00 CD")]);
+        let cell = cell(&coverage_map, 9, 10);
+        assert_eq!(cell.status, CellStatus::Overlap);
+        assert_eq!(cell.rotations, vec!["Day", "Late"]);
+    }
+
+    #[test]
+    fn shared() {
+        let coverage_map = coverage(&[("Split", SHARED), ("Other", SHARED)]);
+        let cell = cell(&coverage_map, 9, 10);
+        assert_eq!(cell.status, CellStatus::Fractional);
+        assert!(cell.rotations.contains(&"Split (0.50)".to_string()));
+    }
+
+    #[test]
+    fn fractional() {
+        let cell = cell(&coverage(&[("Pool", "weekly_fraction: 1.0")]), 9, 10);
+        assert_eq!(cell.status, CellStatus::Fractional);
+        assert_eq!(cell.rotations, vec!["Pool (1.00)"]);
+    }
+
+    #[test]
+    fn incorrect_fraction() {
+        let cell = cell(&coverage(&[("Pool", "weekly_fraction: 0.2")]), 9, 10);
+        assert_eq!(cell.status, CellStatus::IncorrectFraction);
+    }
+}
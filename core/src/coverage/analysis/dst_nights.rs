@@ -0,0 +1,165 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+
+use crate::{
+    analysis::analysis_datum::AnalysisDatum,
+    coverage::{units::CoverageUnit, work_coverage_map::maps::CoverageMap},
+    dates::{DSTTransition, TimeZone},
+    rotations::{description::WrappedSortable, manifest::Manifest, timespan::Timespan},
+    serialization::output::JSONFileOut,
+};
+
+//Relative times reach at most a week from the rotation date, so only rotations this close to a transition can cross it.
+const DAYS_AROUND_TRANSITION: i64 = 7;
+
+/// A rotation whose hours run through a daylight saving transition, so it's an hour shorter or longer than
+/// its span says. `typical_rvus` is the rotation's average on the same weekday over the rest of the data.
+#[derive(Serialize)]
+pub struct DSTNight {
+    pub rotation: String,
+    pub date: NaiveDate,
+    pub transition: DSTTransition,
+    pub span: String,
+    pub nominal_hours: f64,
+    pub actual_hours: f64,
+    pub rvus: f64,
+    pub typical_rvus: Option<f64>,
+}
+
+impl JSONFileOut for Vec<DSTNight> {}
+
+fn rvus_by_rotation_date(coverage_map: &CoverageMap) -> BTreeMap<String, BTreeMap<NaiveDate, f64>> {
+    let mut retval: BTreeMap<String, BTreeMap<NaiveDate, f64>> = BTreeMap::new();
+    coverage_map.foreach(|_coords, coverage_and_workday| {
+        coverage_and_workday.for_each_analysis_datum_by_rotation_date(
+            |date: NaiveDate, datum: AnalysisDatum, coverage: &CoverageUnit| {
+                *retval
+                    .entry(coverage.get_rotation())
+                    .or_default()
+                    .entry(date)
+                    .or_default() += datum.get_rvu();
+            },
+        )
+    });
+    retval
+}
+
+fn typical_rvus(by_date: Option<&BTreeMap<NaiveDate, f64>>, date: NaiveDate) -> Option<f64> {
+    let others: Vec<f64> = by_date?
+        .iter()
+        .filter(|(other, _)| **other != date && other.weekday() == date.weekday())
+        .map(|(_, rvus)| *rvus)
+        .collect();
+    if others.is_empty() {
+        None
+    } else {
+        Some(others.iter().sum::<f64>() / others.len() as f64)
+    }
+}
+
+/// Finds every rotation date within the data whose hours run through a daylight saving transition in `time_zone`.
+pub fn audit_dst_nights(
+    manifest: &Manifest,
+    coverage_map: &CoverageMap,
+    time_zone: &TimeZone,
+) -> Vec<DSTNight> {
+    let mut retval: Vec<DSTNight> = Vec::new();
+
+    let rvus = rvus_by_rotation_date(coverage_map);
+    let dates: BTreeSet<NaiveDate> = rvus.values().flat_map(|x| x.keys().copied()).collect();
+    let (first, last) = match (dates.first(), dates.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return retval,
+    };
+
+    let mut transitions: Vec<NaiveDate> = Vec::new();
+    for year in first.year()..=last.year() {
+        if let Some((start, end)) = time_zone.dst_dates(year) {
+            transitions.push(start);
+            transitions.push(end);
+        }
+    }
+
+    for desc in &manifest.rotation_manifest {
+        let mut spans: BTreeSet<(NaiveDate, DSTTransition, Timespan)> = BTreeSet::new();
+        for responsibility in desc.responsibilities.get().iter().copied().flatten() {
+            let weekdays = responsibility.weekdays();
+            for span in responsibility.time_periods.get().iter().flatten() {
+                for transition in &transitions {
+                    let kind = time_zone
+                        .transition_on(*transition)
+                        .expect("Transitions come from the zone's DST dates");
+                    for days in -DAYS_AROUND_TRANSITION..=DAYS_AROUND_TRANSITION {
+                        let date = *transition + Duration::days(days);
+                        if date < first
+                            || date > last
+                            || !weekdays.contains(&date.weekday())
+                            || span.elapsed_minutes_on(date, time_zone)
+                                == span.duration_on(date.weekday())
+                        {
+                            continue;
+                        }
+                        spans.insert((date, kind, span.clone()));
+                    }
+                }
+            }
+        }
+
+        for (date, transition, span) in spans {
+            let by_date = rvus.get(&desc.rotation);
+            retval.push(DSTNight {
+                rotation: desc.rotation.to_string(),
+                date,
+                transition,
+                span: span.to_string(),
+                nominal_hours: span.duration_on(date.weekday()) as f64 / 60.0,
+                actual_hours: span.elapsed_minutes_on(date, time_zone) as f64 / 60.0,
+                rvus: by_date
+                    .and_then(|x| x.get(&date))
+                    .copied()
+                    .unwrap_or_default(),
+                typical_rvus: typical_rvus(by_date, date),
+            });
+        }
+    }
+
+    retval.sort_by(|a, b| a.date.cmp(&b.date).then(a.rotation.cmp(&b.rotation)));
+    retval
+}
+
+pub fn dst_nights_to_tsv(nights: &[DSTNight], filename: &str) -> Result<(), Box<dyn Error>> {
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+
+    writeln!(
+        writer,
+        "Rotation \u{0009} Date \u{0009} Transition \u{0009} Hours \u{0009} Nominal Hours \u{0009} Actual Hours \u{0009} RVUs \u{0009} Typical RVUs"
+    )?;
+    for night in nights {
+        writeln!(
+            writer,
+            "{} \u{0009} {} \u{0009} {:?} \u{0009} {} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {}",
+            night.rotation,
+            night.date,
+            night.transition,
+            night.span,
+            night.nominal_hours,
+            night.actual_hours,
+            night.rvus,
+            night
+                .typical_rvus
+                .map(|x| format!("{:.2}", x))
+                .unwrap_or_default()
+        )?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
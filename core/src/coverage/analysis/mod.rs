@@ -1,6 +1,18 @@
+pub(crate) mod audit_severity;
+pub(crate) mod boundary_optimizer;
 pub(crate) mod by_day_of_week;
+pub(crate) mod coverage_grid;
 pub(crate) mod coverage_audit;
+pub(crate) mod dst_nights;
+pub(crate) mod reader_workload;
+pub(crate) mod responsibility_reassignment;
+pub(crate) mod roster_workload;
 pub(crate) mod rotation_day_details;
+pub(crate) mod scenario_comparison;
+pub(crate) mod staffing_projection;
 pub(crate) mod volumes_by_rotation_date;
 pub(crate) mod volumes_by_site_date;
+pub(crate) mod week_continuity;
 pub(crate) mod comparison;
+pub(crate) mod turnaround;
+pub(crate) mod workbook;
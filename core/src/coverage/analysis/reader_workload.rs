@@ -0,0 +1,595 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+
+use crate::{
+    analysis::analysis_datum::AnalysisDatum,
+    coverage::work_coverage_map::maps::CoverageMap,
+    source_data::tables::{readers::ExamReader, roster::RosterEntry},
+};
+
+pub const WORKLOAD_SUFFIX: &str = "_Workload.tsv";
+pub const ROTATIONS_SUFFIX: &str = "_Rotations.tsv";
+pub const OUTSIDE_ROTATION_SUFFIX: &str = "_Outside_Rotation.tsv";
+
+//A break this long between a reader's exams ends one shift and starts the next.
+const SHIFT_BREAK_HOURS: i64 = 6;
+//Shifts with less of their work in any one rotation than this aren't assigned to one.
+const MIN_ASSIGNMENT_MATCH: f64 = 0.5;
+
+fn study_count(datum: &AnalysisDatum) -> f64 {
+    datum.get_studies().values().sum()
+}
+
+pub fn reader_name(readers: &BTreeMap<u64, ExamReader>, signer_acct_id: u64) -> String {
+    match readers.get(&signer_acct_id) {
+        Some(reader) => format!("{}, {}", reader.rad_last_name, reader.rad_first_name),
+        None => format!("Unknown reader {}", signer_acct_id),
+    }
+}
+
+/// An exam attributed to a rotation, with the reader who actually read it.
+struct RotationRead {
+    rotation: String,
+    rotation_date: NaiveDate,
+    signer_acct_id: u64,
+    datetime: NaiveDateTime,
+    exam_desc: String,
+    rvu: f64,
+}
+
+/// The rotation a reader's work on one date most resembles. `matched` is the fraction of the reader's attributed RVUs that date falling within that rotation.
+#[derive(Serialize)]
+pub struct InferredAssignment {
+    #[serde(rename = "Date")]
+    pub date: NaiveDate,
+    #[serde(rename = "Rotation")]
+    pub rotation: String,
+    #[serde(rename = "Radiologist")]
+    pub radiologist: String,
+    #[serde(rename = "RVU")]
+    pub rvu: f64,
+    #[serde(rename = "Match")]
+    pub matched: f64,
+}
+
+/// The readers responsible for each rotation on each date, by name as `reader_name` writes it.
+pub struct ResponsibleReaders {
+    /// True when responsibility comes from an imported roster, false when it's inferred from who read the most.
+    pub rostered: bool,
+    by_rotation_date: BTreeMap<(String, NaiveDate), BTreeSet<String>>,
+}
+
+impl ResponsibleReaders {
+    pub fn label(&self) -> &'static str {
+        if self.rostered {
+            "Rostered"
+        } else {
+            "Inferred"
+        }
+    }
+
+    fn get(&self, rotation: &str, date: NaiveDate) -> Option<&BTreeSet<String>> {
+        self.by_rotation_date.get(&(rotation.to_string(), date))
+    }
+}
+
+/// What each radiologist read and which rotations that work belonged to.
+#[derive(Default)]
+pub struct ReaderAnalysis {
+    totals: BTreeMap<u64, AnalysisDatum>,
+    dates_read: BTreeMap<u64, BTreeSet<NaiveDate>>,
+    by_rotation: BTreeMap<(u64, String), AnalysisDatum>,
+    reads: Vec<RotationRead>,
+}
+
+impl ReaderAnalysis {
+    /// The readers rostered to each rotation on each date. Without a roster, the reader who read the most RVUs of
+    /// each rotation's work on each date is taken to be the one staffing it, which can't catch a misassigned majority.
+    pub fn responsible_readers(
+        &self,
+        roster: Option<&[RosterEntry]>,
+        readers: &BTreeMap<u64, ExamReader>,
+    ) -> ResponsibleReaders {
+        if let Some(roster) = roster {
+            let mut by_rotation_date: BTreeMap<(String, NaiveDate), BTreeSet<String>> =
+                BTreeMap::new();
+            for entry in roster {
+                by_rotation_date
+                    .entry((entry.rotation.to_string(), entry.date))
+                    .or_default()
+                    .insert(entry.radiologist.to_string());
+            }
+            return ResponsibleReaders {
+                rostered: true,
+                by_rotation_date,
+            };
+        }
+
+        let mut rvus: BTreeMap<(String, NaiveDate), BTreeMap<u64, f64>> = BTreeMap::new();
+        for read in &self.reads {
+            *rvus
+                .entry((read.rotation.to_string(), read.rotation_date))
+                .or_default()
+                .entry(read.signer_acct_id)
+                .or_default() += read.rvu;
+        }
+
+        let by_rotation_date = rvus
+            .into_iter()
+            .filter_map(|(key, reader_rvus)| {
+                reader_rvus
+                    .into_iter()
+                    .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+                    .map(|(signer_acct_id, _)| {
+                        (key, BTreeSet::from([reader_name(readers, signer_acct_id)]))
+                    })
+            })
+            .collect();
+        ResponsibleReaders {
+            rostered: false,
+            by_rotation_date,
+        }
+    }
+
+    /// Fraction of the RVUs attributed to rotations that were read by someone other than the responsible readers.
+    /// Rotation dates nobody was rostered to are left out, as the roster analysis reports those.
+    pub fn outside_rotation_fraction(
+        &self,
+        responsible: &ResponsibleReaders,
+        readers: &BTreeMap<u64, ExamReader>,
+    ) -> f64 {
+        let mut total: f64 = 0.0;
+        let mut outside: f64 = 0.0;
+        for read in &self.reads {
+            let responsible_readers = match responsible.get(&read.rotation, read.rotation_date) {
+                Some(x) => x,
+                None => continue,
+            };
+            total += read.rvu;
+            if !responsible_readers.contains(&reader_name(readers, read.signer_acct_id)) {
+                outside += read.rvu;
+            }
+        }
+        if total > 0.0 {
+            outside / total
+        } else {
+            0.0
+        }
+    }
+
+    /// Assigns each of a reader's shifts to the rotation whose coverage the most of its work fell in, if at least
+    /// `MIN_ASSIGNMENT_MATCH` of it did. A shift is a run of the reader's exams without a `SHIFT_BREAK_HOURS` break, so
+    /// overnight work after midnight stays with the shift it belongs to and leftovers cleared the next morning count
+    /// toward that morning. Each assignment is dated by the rotation date of that rotation's work in the shift, as an
+    /// imported roster dates it, so rotations anchored to the previous or next day land on their own date.
+    pub fn infer_roster(&self, readers: &BTreeMap<u64, ExamReader>) -> Vec<InferredAssignment> {
+        let mut by_reader: BTreeMap<u64, Vec<&RotationRead>> = BTreeMap::new();
+        for read in &self.reads {
+            by_reader.entry(read.signer_acct_id).or_default().push(read);
+        }
+
+        //RVUs in the assigned rotation and in the whole shift, summed over a reader's shifts with the same assignment.
+        let mut assigned: BTreeMap<(NaiveDate, String, u64), (f64, f64)> = BTreeMap::new();
+        for (signer_acct_id, mut reads) in by_reader {
+            reads.sort_by_key(|read| read.datetime);
+            let mut shifts: Vec<Vec<&RotationRead>> = Vec::new();
+            for read in reads {
+                match shifts.last_mut() {
+                    Some(shift)
+                        if shift.last().is_some_and(|latest| {
+                            read.datetime - latest.datetime <= Duration::hours(SHIFT_BREAK_HOURS)
+                        }) =>
+                    {
+                        shift.push(read)
+                    }
+                    _ => shifts.push(vec![read]),
+                }
+            }
+
+            for shift in shifts {
+                if let Some((date, rotation, rvu, total)) = dominant_rotation(&shift) {
+                    let entry = assigned
+                        .entry((date, rotation, signer_acct_id))
+                        .or_default();
+                    entry.0 += rvu;
+                    entry.1 += total;
+                }
+            }
+        }
+
+        let mut retval: Vec<InferredAssignment> = assigned
+            .into_iter()
+            .map(
+                |((date, rotation, signer_acct_id), (rvu, total))| InferredAssignment {
+                    date,
+                    rotation,
+                    radiologist: reader_name(readers, signer_acct_id),
+                    rvu,
+                    matched: rvu / total,
+                },
+            )
+            .collect();
+        retval.sort_by(|a, b| {
+            a.date
+                .cmp(&b.date)
+                .then(a.rotation.cmp(&b.rotation))
+                .then(a.radiologist.cmp(&b.radiologist))
+        });
+        retval
+    }
+
+    /// Writes the per reader workload, the reader by rotation breakdown, and the exams read outside the responsible rotation to three TSVs named by appending to `filename_base`.
+    pub fn to_tsv(
+        &self,
+        filename_base: &str,
+        readers: &BTreeMap<u64, ExamReader>,
+        responsible: &ResponsibleReaders,
+    ) -> Result<(), Box<dyn Error>> {
+        let file = File::create(filename_base.to_string() + WORKLOAD_SUFFIX)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "Reader \u{0009} Days Read \u{0009} Exams \u{0009} RVU \u{0009} BVU \u{0009} RVU per Day \u{0009} BVU per Day"
+        )?;
+        for (signer_acct_id, datum) in &self.totals {
+            let days = self
+                .dates_read
+                .get(signer_acct_id)
+                .map(|dates| dates.len())
+                .unwrap_or(0)
+                .max(1) as f64;
+            writeln!(
+                writer,
+                "{} \u{0009} {} \u{0009} {} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.2}",
+                reader_name(readers, *signer_acct_id),
+                days,
+                study_count(datum),
+                datum.get_rvu(),
+                datum.get_bvu(),
+                datum.get_rvu() / days,
+                datum.get_bvu() / days
+            )?;
+        }
+        writer.flush()?;
+
+        let file = File::create(filename_base.to_string() + ROTATIONS_SUFFIX)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "Reader \u{0009} Rotation \u{0009} Exams \u{0009} RVU \u{0009} BVU \u{0009} Fraction of Reader RVU"
+        )?;
+        for ((signer_acct_id, rotation), datum) in &self.by_rotation {
+            let reader_rvu = self
+                .totals
+                .get(signer_acct_id)
+                .map(|x| x.get_rvu())
+                .unwrap_or(0.0);
+            let fraction = if reader_rvu > 0.0 {
+                datum.get_rvu() / reader_rvu
+            } else {
+                0.0
+            };
+            writeln!(
+                writer,
+                "{} \u{0009} {} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.3}",
+                reader_name(readers, *signer_acct_id),
+                rotation,
+                study_count(datum),
+                datum.get_rvu(),
+                datum.get_bvu(),
+                fraction
+            )?;
+        }
+        writer.flush()?;
+
+        let file = File::create(filename_base.to_string() + OUTSIDE_ROTATION_SUFFIX)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "Rotation Date \u{0009} Exam Time \u{0009} Rotation \u{0009} {} Responsible Reader \u{0009} Read By \u{0009} Exam \u{0009} RVU",
+            responsible.label()
+        )?;
+        let mut reads: Vec<&RotationRead> = self.reads.iter().collect();
+        reads.sort_by(|a, b| {
+            a.rotation_date
+                .cmp(&b.rotation_date)
+                .then(a.rotation.cmp(&b.rotation))
+                .then(a.datetime.cmp(&b.datetime))
+        });
+        for read in reads {
+            let responsible_readers = match responsible.get(&read.rotation, read.rotation_date) {
+                Some(x) => x,
+                None => continue,
+            };
+            let read_by = reader_name(readers, read.signer_acct_id);
+            if !responsible_readers.contains(&read_by) {
+                writeln!(
+                    writer,
+                    "{} \u{0009} {} \u{0009} {} \u{0009} {} \u{0009} {} \u{0009} {} \u{0009} {:.2}",
+                    read.rotation_date,
+                    read.datetime,
+                    read.rotation,
+                    responsible_readers
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<String>>()
+                        .join("; "),
+                    read_by,
+                    read.exam_desc,
+                    read.rvu
+                )?;
+            }
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+//The rotation date and rotation most of a shift's RVUs fell in, with those RVUs and the shift's total, or None if
+//no rotation reaches `MIN_ASSIGNMENT_MATCH`. A shift can reach into two dates of its rotation, so the date with more
+//of that rotation's work is taken.
+fn dominant_rotation(shift: &[&RotationRead]) -> Option<(NaiveDate, String, f64, f64)> {
+    let mut rvus: BTreeMap<&str, BTreeMap<NaiveDate, f64>> = BTreeMap::new();
+    for read in shift {
+        *rvus
+            .entry(read.rotation.as_str())
+            .or_default()
+            .entry(read.rotation_date)
+            .or_default() += read.rvu;
+    }
+    let total: f64 = shift.iter().map(|read| read.rvu).sum();
+
+    let (rotation, rvu, by_date) = rvus
+        .into_iter()
+        .map(|(rotation, by_date)| (rotation, by_date.values().sum::<f64>(), by_date))
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(a.0)))?;
+    if total <= 0.0 || rvu / total < MIN_ASSIGNMENT_MATCH {
+        return None;
+    }
+    let (date, _) = by_date
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))?;
+    Some((date, rotation.to_string(), rvu, total))
+}
+
+/// Joins each exam to the rotations covering it and to the radiologist who read it.
+pub fn analyze_readers(coverage_map: &CoverageMap) -> ReaderAnalysis {
+    let mut retval = ReaderAnalysis::default();
+
+    coverage_map.foreach(|_coords, coverage_and_workday| {
+        //Totals come straight from the work so exams in undeclared overlaps aren't counted twice.
+        for work in coverage_and_workday.work_iterator() {
+            retval
+                .totals
+                .entry(work.get_signer_acct_id())
+                .or_default()
+                .add_workunit(work);
+            retval
+                .dates_read
+                .entry(work.get_signer_acct_id())
+                .or_default()
+                .insert(work.get_datetime().date());
+        }
+
+        coverage_and_workday.for_each_work_unit_by_rotation(|work, coverage, share| {
+            if share <= 0.0 {
+                return;
+            }
+            let rotation = coverage.get_rotation();
+            retval
+                .by_rotation
+                .entry((work.get_signer_acct_id(), rotation.to_string()))
+                .or_default()
+                .add_scaled_workunit(work, share);
+            retval.reads.push(RotationRead {
+                rotation,
+                rotation_date: coverage
+                    .get_time_adjustment()
+                    .get_date(work.get_datetime().date()),
+                signer_acct_id: work.get_signer_acct_id(),
+                datetime: work.get_datetime(),
+                exam_desc: work.get_exam_desc().to_string(),
+                rvu: work.get_absolute_rvu() * share,
+            });
+        });
+    });
+
+    retval
+}
+
+/// Writes the inferred roster in the same Date, Rotation, Radiologist layout as an imported roster.
+pub fn inferred_roster_to_csv(
+    roster: &[InferredAssignment],
+    filename: &str,
+) -> Result<(), Box<dyn Error>> {
+    let mut writer = csv::Writer::from_path(filename)?;
+    for assignment in roster {
+        writer.serialize(assignment)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Datelike;
+
+    use super::*;
+    use crate::coverage::work_coverage_map::testing;
+
+    fn read(rotation: &str, day: u32, signer_acct_id: u64, rvu: f64) -> RotationRead {
+        read_at(rotation, day, day, 12, signer_acct_id, rvu)
+    }
+
+    fn read_at(
+        rotation: &str,
+        rotation_day: u32,
+        day: u32,
+        hour: u32,
+        signer_acct_id: u64,
+        rvu: f64,
+    ) -> RotationRead {
+        RotationRead {
+            rotation: rotation.to_string(),
+            rotation_date: NaiveDate::from_ymd_opt(2024, 1, rotation_day).unwrap(),
+            signer_acct_id,
+            datetime: NaiveDate::from_ymd_opt(2024, 1, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap(),
+            exam_desc: "CT HEAD".to_string(),
+            rvu,
+        }
+    }
+
+    fn readers() -> BTreeMap<u64, ExamReader> {
+        [(1, "Able"), (2, "Baker")]
+            .into_iter()
+            .map(|(signer_acct_id, last_name)| {
+                (
+                    signer_acct_id,
+                    ExamReader {
+                        signer_acct_id,
+                        rad_last_name: last_name.to_string(),
+                        rad_first_name: "Pat".to_string(),
+                        excluded: false,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn analysis(reads: Vec<RotationRead>) -> ReaderAnalysis {
+        ReaderAnalysis {
+            reads,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn without_a_roster_the_majority_reader_is_inferred() {
+        let analysis = analysis(vec![read("Day", 8, 1, 3.0), read("Day", 8, 2, 1.0)]);
+        let readers = readers();
+        let responsible = analysis.responsible_readers(None, &readers);
+        assert!(!responsible.rostered);
+        assert_eq!(responsible.label(), "Inferred");
+        assert_eq!(
+            analysis.outside_rotation_fraction(&responsible, &readers),
+            0.25
+        );
+    }
+
+    #[test]
+    fn a_roster_flags_a_misassigned_majority() {
+        let analysis = analysis(vec![
+            read("Day", 8, 1, 3.0),
+            read("Day", 8, 2, 1.0),
+            read("Night", 8, 1, 2.0),
+        ]);
+        let readers = readers();
+        let roster = vec![RosterEntry {
+            date: NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(),
+            rotation: "Day".to_string(),
+            radiologist: "Baker, Pat".to_string(),
+        }];
+        let responsible = analysis.responsible_readers(Some(&roster), &readers);
+        assert!(responsible.rostered);
+        //Nobody was rostered to Night, so it's left to the unstaffed report.
+        assert_eq!(
+            analysis.outside_rotation_fraction(&responsible, &readers),
+            0.75
+        );
+    }
+
+    fn assignments(roster: &[InferredAssignment]) -> Vec<(u32, &str, &str)> {
+        roster
+            .iter()
+            .map(|x| (x.date.day(), x.rotation.as_str(), x.radiologist.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn inferred_days_are_the_readers_own_shifts() {
+        let analysis = analysis(vec![
+            //Able works the night of the 8th through the morning of the 9th.
+            read_at("Night", 8, 8, 18, 1, 2.0),
+            read_at("Night", 8, 8, 22, 1, 2.0),
+            read_at("Night", 8, 9, 2, 1, 4.0),
+            read_at("Night", 8, 9, 7, 1, 2.0),
+            //Baker clears the night's leftovers on the morning of the 9th before the day's work.
+            read_at("Night", 8, 9, 8, 2, 1.0),
+            read_at("Day", 9, 9, 10, 2, 5.0),
+            read_at("Day", 9, 9, 15, 2, 5.0),
+        ]);
+        let roster = analysis.infer_roster(&readers());
+        assert_eq!(
+            assignments(&roster),
+            vec![(8, "Night", "Able, Pat"), (9, "Day", "Baker, Pat")]
+        );
+    }
+
+    #[test]
+    fn shifts_are_dated_by_their_rotation_date() {
+        //Friday Late is Saturday's rotation but covers Friday afternoon, so the roster dates it Saturday the 6th.
+        let manifest = testing::manifest(
+            "title: Test
+rotation_manifest:
+- rotation: Day
+  location: Remote
+  responsibilities:
+  - sites: SH
+    exams: General CT
+    contexts: ED
+    days: Mon/Tue/Wed/Thu/Fri
+    time_periods:
+    - 08:00 CD-15:00 CD
+- rotation: Friday Late
+  location: Remote
+  responsibilities:
+  - sites: SH
+    exams: General CT
+    contexts: ED
+    days: Sat
+    time_periods:
+    - 15:00 PD-19:00 PD
+",
+        );
+        let mut coverage_map = testing::coverage_map(&manifest);
+        for (hour, signer_acct_id) in [(9, 2), (12, 2), (14, 1), (15, 1), (17, 1), (18, 1)] {
+            testing::add_exam(
+                &mut coverage_map,
+                testing::at(5, hour, 30),
+                1.0,
+                signer_acct_id,
+            );
+        }
+
+        let roster = analyze_readers(&coverage_map).infer_roster(&readers());
+        assert_eq!(
+            assignments(&roster),
+            vec![(5, "Day", "Baker, Pat"), (6, "Friday Late", "Able, Pat")]
+        );
+        assert_eq!(roster[1].matched, 0.75);
+    }
+
+    #[test]
+    fn reader_days_split_across_rotations_are_not_assigned() {
+        let analysis = analysis(vec![
+            read_at("Day", 9, 9, 9, 1, 2.0),
+            read_at("Late", 9, 9, 12, 1, 2.0),
+            read_at("Pool", 9, 9, 14, 1, 1.0),
+            read_at("Day", 9, 9, 9, 2, 4.0),
+            read_at("Late", 9, 9, 12, 2, 1.0),
+        ]);
+        let roster = analysis.infer_roster(&readers());
+        assert_eq!(assignments(&roster), vec![(9, "Day", "Baker, Pat")]);
+    }
+}
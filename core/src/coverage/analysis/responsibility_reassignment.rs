@@ -0,0 +1,425 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+};
+
+use serde::Serialize;
+
+use crate::{
+    analysis::volumes::VolumesMark,
+    coverage::analysis::boundary_optimizer::coverage_key,
+    globals,
+    rotations::{
+        description::WrappedSortable,
+        manifest::Manifest,
+        optimizer::{
+            ForbiddenCoverage, OptimizerMeasure, OptimizerObjective, ReassignmentSettings,
+        },
+        responsibility::RotationResponsibility,
+        stringtypes::StringTypes,
+    },
+};
+
+//Improvements smaller than this are rounding noise and don't justify moving a responsibility.
+const MIN_IMPROVEMENT: f64 = 1e-9;
+
+//A responsibility and the volume it brings to whichever rotation holds it.
+struct Block {
+    origin: usize,
+    index: usize,
+    key: [Vec<String>; 4],
+    volume: f64,
+    movable: bool,
+}
+
+/// A responsibility moved from one rotation to another, with its average volume per day.
+#[derive(Serialize)]
+pub struct MovedResponsibility {
+    #[serde(rename = "From Rotation")]
+    pub from_rotation: String,
+    #[serde(rename = "To Rotation")]
+    pub to_rotation: String,
+    #[serde(rename = "Sites")]
+    pub sites: String,
+    #[serde(rename = "Exams")]
+    pub exams: String,
+    #[serde(rename = "Contexts")]
+    pub contexts: String,
+    #[serde(rename = "Days")]
+    pub days: String,
+    #[serde(rename = "Time Periods")]
+    pub time_periods: String,
+    #[serde(rename = "RVU")]
+    pub rvu: f64,
+    #[serde(rename = "BVU")]
+    pub bvu: f64,
+}
+
+pub struct Reassignment {
+    pub moved: Vec<MovedResponsibility>,
+    pub initial_score: f64,
+    pub final_score: f64,
+}
+
+impl Reassignment {
+    pub fn to_csv(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        let mut writer = csv::Writer::from_path(filename)?;
+        for moved in &self.moved {
+            writer.serialize(moved)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+fn overlaps(constraint: &Option<StringTypes>, all: &[&str], members: &[String]) -> bool {
+    match constraint {
+        Some(constraint) => {
+            let constrained = constraint.to_vec(all);
+            members.iter().any(|member| constrained.contains(member))
+        }
+        None => true,
+    }
+}
+
+fn is_forbidden(forbidden: &ForbiddenCoverage, rotation: &str, key: &[Vec<String>; 4]) -> bool {
+    let all_days: Vec<String> = globals::ALL_DAYS
+        .iter()
+        .map(|day| day.to_string())
+        .collect();
+    let all_days: Vec<&str> = all_days.iter().map(|day| day.as_str()).collect();
+    forbidden.rotation == rotation
+        && overlaps(&forbidden.sites, globals::FACILITIES, &key[0])
+        && overlaps(&forbidden.exams, globals::SUBSPECIALTIES, &key[1])
+        && overlaps(&forbidden.contexts, globals::CONTEXTS, &key[2])
+        && overlaps(&forbidden.days, &all_days, &key[3])
+}
+
+fn responsibility(manifest: &Manifest, rotation: usize, index: usize) -> &RotationResponsibility {
+    &manifest.rotation_manifest[rotation]
+        .responsibilities
+        .get()
+        .expect("Blocks only index existing responsibilities")[index]
+}
+
+/// Average volume per day of each rotation. A responsibility's volume counts toward each of its days,
+/// and a rotation's average is over the weekdays it has any responsibility.
+fn daily_loads(blocks: &[Block], owners: &[usize], rotation_count: usize) -> Vec<f64> {
+    let mut by_day: BTreeMap<(usize, &str), f64> = BTreeMap::new();
+    for (block, owner) in blocks.iter().zip(owners) {
+        for day in &block.key[3] {
+            *by_day.entry((*owner, day.as_str())).or_default() += block.volume;
+        }
+    }
+
+    let mut totals: Vec<f64> = vec![0.0; rotation_count];
+    let mut days: Vec<usize> = vec![0; rotation_count];
+    for ((owner, _), volume) in by_day {
+        totals[owner] += volume;
+        days[owner] += 1;
+    }
+    totals
+        .into_iter()
+        .zip(days)
+        .map(|(total, days)| if days > 0 { total / days as f64 } else { 0.0 })
+        .collect()
+}
+
+//Lower is better.
+fn score(
+    loads: &[f64],
+    candidates: &BTreeSet<usize>,
+    targets: &BTreeMap<usize, f64>,
+    objective: OptimizerObjective,
+) -> f64 {
+    match objective {
+        OptimizerObjective::Variance => {
+            let values: Vec<f64> = candidates.iter().map(|x| loads[*x]).collect();
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / values.len() as f64
+        }
+        OptimizerObjective::Baselines => targets
+            .iter()
+            .map(|(rotation, target)| ((loads[*rotation] - target) / target).powi(2))
+            .sum(),
+    }
+}
+
+/// Moves whole responsibilities among the settings' rotations, one at a time and always the move that helps most,
+/// until no move improves the objective. `manifest` must already have volumes from `populate_responsibility_volumes`.
+/// A responsibility only moves to a rotation already working its exact time periods, and never to one its constraints forbid.
+/// Fractional responsibilities stay where they are but still count toward their rotation's volume.
+pub fn reassign_responsibilities(
+    manifest: &mut Manifest,
+    settings: &ReassignmentSettings,
+) -> Result<Reassignment, Box<dyn Error>> {
+    let names: Vec<String> = manifest
+        .rotation_manifest
+        .iter()
+        .map(|desc| desc.rotation.to_string())
+        .collect();
+    let mut candidates: BTreeSet<usize> = BTreeSet::new();
+    for rotation in &settings.rotations {
+        match names.iter().position(|name| name == rotation) {
+            Some(x) => {
+                candidates.insert(x);
+            }
+            None => {
+                return Err(format!("Rotation {} isn't in the manifest.", rotation).into());
+            }
+        }
+    }
+
+    let mut blocks: Vec<Block> = Vec::new();
+    for (origin, desc) in manifest.rotation_manifest.iter().enumerate() {
+        for (index, resp) in desc
+            .responsibilities
+            .get()
+            .iter()
+            .flat_map(|x| x.iter())
+            .enumerate()
+        {
+            let volume = match (&resp.volume, settings.measure()) {
+                (Some(volume), OptimizerMeasure::Rvu) => volume.rvu,
+                (Some(volume), OptimizerMeasure::Bvu) => volume.bvu,
+                (None, _) => 0.0,
+            };
+            blocks.push(Block {
+                origin,
+                index,
+                key: coverage_key(resp),
+                volume,
+                movable: candidates.contains(&origin)
+                    && resp.weekly_fraction.is_none()
+                    && resp.time_periods.get().is_some(),
+            });
+        }
+    }
+
+    let mut targets: BTreeMap<usize, f64> = BTreeMap::new();
+    for baseline in manifest.baselines.iter().flatten() {
+        let target = match settings.measure() {
+            OptimizerMeasure::Rvu => baseline.rvu,
+            OptimizerMeasure::Bvu => baseline.bvu,
+        };
+        if let Some(x) = names.iter().position(|name| *name == baseline.rotation) {
+            if candidates.contains(&x) && target > 0.0 {
+                targets.insert(x, target);
+            }
+        }
+    }
+    if settings.objective() == OptimizerObjective::Baselines && targets.is_empty() {
+        return Err(
+            "The baselines objective requires baselines for the rotations being reassigned.".into(),
+        );
+    }
+
+    //The rotations each block could be given.
+    let destinations: Vec<Vec<usize>> = blocks
+        .iter()
+        .map(|block| {
+            if !block.movable {
+                return Vec::new();
+            }
+            let time_periods = &responsibility(manifest, block.origin, block.index).time_periods;
+            candidates
+                .iter()
+                .copied()
+                .filter(|destination| {
+                    let works_hours = manifest.rotation_manifest[*destination]
+                        .responsibilities
+                        .get()
+                        .iter()
+                        .flat_map(|x| x.iter())
+                        .any(|resp| resp.time_periods == *time_periods);
+                    let forbidden =
+                        settings.forbidden.iter().flatten().any(|forbidden| {
+                            is_forbidden(forbidden, &names[*destination], &block.key)
+                        });
+                    works_hours && !forbidden
+                })
+                .collect()
+        })
+        .collect();
+
+    let rotation_count = names.len();
+    let mut owners: Vec<usize> = blocks.iter().map(|block| block.origin).collect();
+    let initial_score = score(
+        &daily_loads(&blocks, &owners, rotation_count),
+        &candidates,
+        &targets,
+        settings.objective(),
+    );
+    let mut current_score = initial_score;
+
+    for _ in 0..settings.max_moves() {
+        let mut best: Option<(usize, usize, f64)> = None;
+        for (block_index, block_destinations) in destinations.iter().enumerate() {
+            let owner = owners[block_index];
+            for destination in block_destinations {
+                if *destination == owner {
+                    continue;
+                }
+                owners[block_index] = *destination;
+                let candidate_score = score(
+                    &daily_loads(&blocks, &owners, rotation_count),
+                    &candidates,
+                    &targets,
+                    settings.objective(),
+                );
+                owners[block_index] = owner;
+                let best_score = best.map(|x| x.2).unwrap_or(current_score);
+                if candidate_score < best_score - MIN_IMPROVEMENT {
+                    best = Some((block_index, *destination, candidate_score));
+                }
+            }
+        }
+        match best {
+            Some((block_index, destination, best_score)) => {
+                owners[block_index] = destination;
+                current_score = best_score;
+            }
+            None => break,
+        }
+    }
+
+    let mut moved: Vec<MovedResponsibility> = Vec::new();
+    let mut moving: BTreeMap<usize, Vec<(usize, usize)>> = BTreeMap::new();
+    for (block, owner) in blocks.iter().zip(&owners) {
+        if block.origin == *owner {
+            continue;
+        }
+        let resp = responsibility(manifest, block.origin, block.index);
+        let volume = resp.volume.unwrap_or(VolumesMark { rvu: 0.0, bvu: 0.0 });
+        moved.push(MovedResponsibility {
+            from_rotation: names[block.origin].to_string(),
+            to_rotation: names[*owner].to_string(),
+            sites: block.key[0].join("/"),
+            exams: block.key[1].join("/"),
+            contexts: block.key[2].join("/"),
+            days: block.key[3].join("/"),
+            time_periods: resp
+                .time_periods
+                .get()
+                .iter()
+                .flatten()
+                .map(|timespan| timespan.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            rvu: volume.rvu,
+            bvu: volume.bvu,
+        });
+        moving
+            .entry(block.origin)
+            .or_default()
+            .push((block.index, *owner));
+    }
+
+    //Take every moving responsibility out before adding any, and from the back so earlier indices stay valid.
+    let mut arriving: BTreeMap<usize, Vec<RotationResponsibility>> = BTreeMap::new();
+    for (origin, mut indices) in moving {
+        indices.sort();
+        for (index, destination) in indices.into_iter().rev() {
+            let resp = manifest.rotation_manifest[origin]
+                .responsibilities
+                .get_mut()
+                .expect("Blocks only index existing responsibilities")
+                .remove(index);
+            arriving.entry(destination).or_default().push(resp);
+        }
+    }
+    for (destination, mut responsibilities) in arriving {
+        let destination_responsibilities = manifest.rotation_manifest[destination]
+            .responsibilities
+            .get_mut()
+            .expect("Destinations already have responsibilities with the same time periods");
+        destination_responsibilities.append(&mut responsibilities);
+        destination_responsibilities.sort();
+    }
+
+    Ok(Reassignment {
+        moved,
+        initial_score,
+        final_score: current_score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(sites: &str, exams: &str, rvu: f64) -> String {
+        format!(
+            "  - sites: {}
+    exams: {}
+    contexts: ED
+    days: Mon
+    time_periods:
+    - 08:00 CD-17:00 CD
+    volume:
+      rvu: {}
+      bvu: 0.0
+",
+            sites, exams, rvu
+        )
+    }
+
+    //Day holds all the work. Moving SC's CT to Late balances best, then nothing more helps.
+    fn manifest() -> Manifest {
+        serde_yaml::from_str(&format!(
+            "title: Test\nrotation_manifest:\n- rotation: Day\n  location: Remote\n  responsibilities:\n{}{}{}- rotation: Late\n  location: Remote\n  responsibilities:\n{}",
+            block("SC", "General CT", 5.0),
+            block("SH", "General XR", 2.0),
+            block("SH", "General CT", 1.0),
+            block("SH", "NM", 0.0),
+        ))
+        .expect("Test manifest should parse")
+    }
+
+    fn settings(forbidden: &str) -> ReassignmentSettings {
+        serde_yaml::from_str(&format!("rotations: [Day, Late]\n{}", forbidden))
+            .expect("Test settings should parse")
+    }
+
+    fn moved_sites(reassignment: &Reassignment) -> Vec<(&str, &str, &str)> {
+        reassignment
+            .moved
+            .iter()
+            .map(|x| (x.to_rotation.as_str(), x.sites.as_str(), x.exams.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn the_best_move_is_taken_without_constraints() {
+        let mut manifest = manifest();
+        let reassignment =
+            reassign_responsibilities(&mut manifest, &settings("")).expect("Should reassign");
+        assert_eq!(
+            moved_sites(&reassignment),
+            vec![("Late", "SC", "General CT")]
+        );
+    }
+
+    #[test]
+    fn forbidden_coverage_is_never_reassigned() {
+        let mut manifest = manifest();
+        let reassignment = reassign_responsibilities(
+            &mut manifest,
+            &settings("forbidden:\n- rotation: Late\n  sites: SC\n"),
+        )
+        .expect("Should reassign");
+        assert!(!reassignment.moved.is_empty());
+        assert!(reassignment
+            .moved
+            .iter()
+            .all(|x| !(x.to_rotation == "Late" && x.sites.contains("SC"))));
+
+        let late = &manifest.rotation_manifest[1];
+        assert!(late
+            .responsibilities
+            .get()
+            .iter()
+            .flat_map(|x| x.iter())
+            .all(|resp| !coverage_key(resp)[0].contains(&"SC".to_string())));
+    }
+}
@@ -0,0 +1,482 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+
+use crate::{
+    analysis::volumes::{CategorizedVolumes, VolumesMark},
+    source_data::tables::roster::RosterEntry,
+};
+
+/// A rotation with volume on a date nobody was rostered to it.
+pub struct UnstaffedRotation {
+    pub date: NaiveDate,
+    pub rotation: String,
+    pub volume: VolumesMark,
+}
+
+#[derive(Serialize)]
+struct CumulativeRow<'a> {
+    #[serde(rename = "Date")]
+    date: NaiveDate,
+    #[serde(rename = "Radiologist")]
+    radiologist: &'a str,
+    #[serde(rename = "RVU")]
+    rvu: f64,
+    #[serde(rename = "BVU")]
+    bvu: f64,
+    #[serde(rename = "Cumulative RVU")]
+    cumulative_rvu: f64,
+    #[serde(rename = "Cumulative BVU")]
+    cumulative_bvu: f64,
+}
+
+/// A radiologist's workload over the period compared with the rest of the group.
+/// Deviations are of the per day averages from the group mean of those averages.
+pub struct EquityRow {
+    pub radiologist: String,
+    pub days: usize,
+    pub total: VolumesMark,
+    pub per_day: VolumesMark,
+    pub rank: usize,
+    pub deviation: VolumesMark,
+    pub deviation_fraction: VolumesMark,
+}
+
+fn fraction_of(value: f64, mean: f64) -> f64 {
+    if mean != 0.0 {
+        value / mean
+    } else {
+        0.0
+    }
+}
+
+/// Rotation-date volumes attributed to the radiologists rostered to them.
+#[derive(Default)]
+pub struct RosterAttribution {
+    by_person_date: BTreeMap<String, BTreeMap<NaiveDate, VolumesMark>>,
+    unstaffed: Vec<UnstaffedRotation>,
+    unknown_rotations: BTreeSet<String>,
+}
+
+impl RosterAttribution {
+    pub fn get_unstaffed(&self) -> &[UnstaffedRotation] {
+        &self.unstaffed
+    }
+
+    /// Roster rotations that never appear in the volumes, usually a misspelling or a rotation missing from the manifest.
+    pub fn get_unknown_rotations(&self) -> &BTreeSet<String> {
+        &self.unknown_rotations
+    }
+
+    //Each person's days worked and total volume.
+    fn totals(&self) -> BTreeMap<&str, (usize, VolumesMark)> {
+        self.by_person_date
+            .iter()
+            .map(|(radiologist, dates)| {
+                let total = dates
+                    .values()
+                    .fold(VolumesMark { rvu: 0.0, bvu: 0.0 }, |acc, mark| acc + *mark);
+                (radiologist.as_str(), (dates.len(), total))
+            })
+            .collect()
+    }
+
+    /// Each person's total volume and average volume per rostered day.
+    pub fn workload_to_tsv(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        let file = File::create(filename)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "Radiologist \u{0009} Days \u{0009} RVU \u{0009} BVU \u{0009} RVU per Day \u{0009} BVU per Day"
+        )?;
+        for (radiologist, (days, total)) in self.totals() {
+            let denominator = days.max(1) as f64;
+            writeln!(
+                writer,
+                "{} \u{0009} {} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.2}",
+                radiologist,
+                days,
+                total.rvu,
+                total.bvu,
+                total.rvu / denominator,
+                total.bvu / denominator
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Ranks radiologists by RVUs per day worked, most loaded first, with each one's deviation from the group mean.
+    pub fn equity_report(&self) -> Vec<EquityRow> {
+        let mut retval: Vec<EquityRow> = self
+            .totals()
+            .into_iter()
+            .filter(|(_radiologist, (days, _total))| *days > 0)
+            .map(|(radiologist, (days, total))| EquityRow {
+                radiologist: radiologist.to_string(),
+                days,
+                total,
+                per_day: VolumesMark {
+                    rvu: total.rvu / days as f64,
+                    bvu: total.bvu / days as f64,
+                },
+                rank: 0,
+                deviation: VolumesMark { rvu: 0.0, bvu: 0.0 },
+                deviation_fraction: VolumesMark { rvu: 0.0, bvu: 0.0 },
+            })
+            .collect();
+        if retval.is_empty() {
+            return retval;
+        }
+
+        let count = retval.len() as f64;
+        let mean = VolumesMark {
+            rvu: retval.iter().map(|row| row.per_day.rvu).sum::<f64>() / count,
+            bvu: retval.iter().map(|row| row.per_day.bvu).sum::<f64>() / count,
+        };
+
+        retval.sort_by(|a, b| {
+            b.per_day
+                .rvu
+                .total_cmp(&a.per_day.rvu)
+                .then(a.radiologist.cmp(&b.radiologist))
+        });
+        for (n, row) in retval.iter_mut().enumerate() {
+            row.rank = n + 1;
+            row.deviation = VolumesMark {
+                rvu: row.per_day.rvu - mean.rvu,
+                bvu: row.per_day.bvu - mean.bvu,
+            };
+            row.deviation_fraction = VolumesMark {
+                rvu: fraction_of(row.deviation.rvu, mean.rvu),
+                bvu: fraction_of(row.deviation.bvu, mean.bvu),
+            };
+        }
+        retval
+    }
+
+    /// Each person's daily volume and running total across the period, for plotting workload over time.
+    pub fn cumulative_to_csv(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        let mut writer = csv::Writer::from_path(filename)?;
+        for (radiologist, dates) in &self.by_person_date {
+            let mut cumulative = VolumesMark { rvu: 0.0, bvu: 0.0 };
+            for (date, mark) in dates {
+                cumulative += *mark;
+                writer.serialize(CumulativeRow {
+                    date: *date,
+                    radiologist,
+                    rvu: mark.rvu,
+                    bvu: mark.bvu,
+                    cumulative_rvu: cumulative.rvu,
+                    cumulative_bvu: cumulative.bvu,
+                })?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn unstaffed_to_tsv(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        let file = File::create(filename)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "Date \u{0009} Day of Week \u{0009} Rotation \u{0009} RVU \u{0009} BVU"
+        )?;
+        for unstaffed in &self.unstaffed {
+            writeln!(
+                writer,
+                "{} \u{0009} {} \u{0009} {} \u{0009} {:.2} \u{0009} {:.2}",
+                unstaffed.date,
+                unstaffed.date.weekday(),
+                unstaffed.rotation,
+                unstaffed.volume.rvu,
+                unstaffed.volume.bvu
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Attributes each rotation-date volume between `start` and `end` to the radiologists rostered to it.
+/// A rotation staffed by several people on one date is split evenly among them.
+pub fn attribute_volumes_to_roster(
+    volumes: &CategorizedVolumes,
+    roster: &[RosterEntry],
+    start: &NaiveDate,
+    end: &NaiveDate,
+) -> RosterAttribution {
+    let mut retval = RosterAttribution::default();
+
+    let mut staff: BTreeMap<(NaiveDate, &str), BTreeSet<&str>> = BTreeMap::new();
+    for entry in roster {
+        if start <= &entry.date && &entry.date <= end {
+            staff
+                .entry((entry.date, entry.rotation.as_str()))
+                .or_default()
+                .insert(entry.radiologist.as_str());
+        }
+    }
+
+    let mut known_rotations: BTreeSet<&str> = BTreeSet::new();
+    for (date, rotation_map) in volumes.get_date_map() {
+        for (rotation, mark) in rotation_map {
+            known_rotations.insert(rotation.as_str());
+            if date < start || end < date {
+                continue;
+            }
+            match staff.get(&(*date, rotation.as_str())) {
+                Some(people) => {
+                    let share = 1.0 / people.len() as f64;
+                    for person in people {
+                        let entry = retval
+                            .by_person_date
+                            .entry(person.to_string())
+                            .or_default()
+                            .entry(*date)
+                            .or_insert(VolumesMark { rvu: 0.0, bvu: 0.0 });
+                        *entry += VolumesMark {
+                            rvu: mark.rvu * share,
+                            bvu: mark.bvu * share,
+                        };
+                    }
+                }
+                None => {
+                    if mark.rvu > 0.0 || mark.bvu > 0.0 {
+                        retval.unstaffed.push(UnstaffedRotation {
+                            date: *date,
+                            rotation: rotation.to_string(),
+                            volume: *mark,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    //People rostered to a rotation with no volume that day still worked it.
+    for ((date, _rotation), people) in &staff {
+        for person in people {
+            retval
+                .by_person_date
+                .entry(person.to_string())
+                .or_default()
+                .entry(*date)
+                .or_insert(VolumesMark { rvu: 0.0, bvu: 0.0 });
+        }
+    }
+
+    retval.unknown_rotations = staff
+        .keys()
+        .map(|(_date, rotation)| *rotation)
+        .filter(|rotation| !known_rotations.contains(rotation))
+        .map(|rotation| rotation.to_string())
+        .collect();
+
+    retval
+}
+
+/// Coefficient of variation of RVUs per day across the group, a single number for how evenly work is spread.
+pub fn equity_variation(report: &[EquityRow]) -> f64 {
+    if report.is_empty() {
+        return 0.0;
+    }
+    let count = report.len() as f64;
+    let mean = report.iter().map(|row| row.per_day.rvu).sum::<f64>() / count;
+    let variance = report
+        .iter()
+        .map(|row| (row.per_day.rvu - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    fraction_of(variance.sqrt(), mean)
+}
+
+pub fn equity_report_to_tsv(report: &[EquityRow], filename: &str) -> Result<(), Box<dyn Error>> {
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+    writeln!(
+        writer,
+        "Rank \u{0009} Radiologist \u{0009} Days \u{0009} RVU \u{0009} BVU \u{0009} RVU per Day \u{0009} BVU per Day \u{0009} RVU per Day Deviation \u{0009} RVU per Day Deviation % \u{0009} BVU per Day Deviation \u{0009} BVU per Day Deviation %"
+    )?;
+    for row in report {
+        writeln!(
+            writer,
+            "{} \u{0009} {} \u{0009} {} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.1} \u{0009} {:.2} \u{0009} {:.1}",
+            row.rank,
+            row.radiologist,
+            row.days,
+            row.total.rvu,
+            row.total.bvu,
+            row.per_day.rvu,
+            row.per_day.bvu,
+            row.deviation.rvu,
+            row.deviation_fraction.rvu * 100.0,
+            row.deviation.bvu,
+            row.deviation_fraction.bvu * 100.0
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn mark(rvu: f64) -> VolumesMark {
+        VolumesMark {
+            rvu,
+            bvu: 2.0 * rvu,
+        }
+    }
+
+    fn entry(day: u32, rotation: &str, radiologist: &str) -> RosterEntry {
+        RosterEntry {
+            date: date(day),
+            rotation: rotation.to_string(),
+            radiologist: radiologist.to_string(),
+        }
+    }
+
+    //Each person's RVUs by day of the month.
+    fn rvus(attribution: &RosterAttribution) -> BTreeMap<(&str, u32), f64> {
+        attribution
+            .by_person_date
+            .iter()
+            .flat_map(|(radiologist, dates)| {
+                dates
+                    .iter()
+                    .map(move |(date, mark)| ((radiologist.as_str(), date.day()), mark.rvu))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn volumes_are_split_among_the_rostered_and_unstaffed_dates_reported() {
+        let mut volumes = CategorizedVolumes::new();
+        volumes.add(date(8), "Day", mark(6.0));
+        volumes.add(date(8), "Night", mark(2.0));
+        volumes.add(date(9), "Day", mark(4.0));
+        volumes.add(date(9), "Night", mark(3.0));
+        volumes.add(date(10), "Day", mark(5.0));
+
+        let roster = vec![
+            entry(8, "Day", "Able"),
+            entry(8, "Day", "Baker"),
+            entry(8, "Night", "Carter"),
+            entry(9, "Day", "Able"),
+            entry(9, "Dya", "Baker"),
+            //Outside the period, so neither credited nor checked against the volumes.
+            entry(10, "Day", "Carter"),
+            entry(7, "Evening", "Carter"),
+        ];
+        let attribution = attribute_volumes_to_roster(&volumes, &roster, &date(8), &date(9));
+
+        assert_eq!(
+            rvus(&attribution),
+            BTreeMap::from([
+                (("Able", 8), 3.0),
+                (("Able", 9), 4.0),
+                (("Baker", 8), 3.0),
+                //Rostered to a rotation with no volume, which still counts as a day worked.
+                (("Baker", 9), 0.0),
+                (("Carter", 8), 2.0),
+            ])
+        );
+        assert_eq!(attribution.by_person_date["Able"][&date(8)].bvu, 6.0);
+
+        let unstaffed: Vec<(u32, &str, f64)> = attribution
+            .get_unstaffed()
+            .iter()
+            .map(|x| (x.date.day(), x.rotation.as_str(), x.volume.rvu))
+            .collect();
+        assert_eq!(unstaffed, vec![(9, "Night", 3.0)]);
+
+        assert_eq!(
+            attribution.get_unknown_rotations(),
+            &BTreeSet::from(["Dya".to_string()])
+        );
+    }
+
+    //People with their RVUs on each day they worked, in January.
+    fn attribution(people: &[(&str, &[(u32, f64)])]) -> RosterAttribution {
+        RosterAttribution {
+            by_person_date: people
+                .iter()
+                .map(|(radiologist, days)| {
+                    (
+                        radiologist.to_string(),
+                        days.iter()
+                            .map(|(day, rvu)| (date(*day), mark(*rvu)))
+                            .collect(),
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn equity_ranks_rvus_per_day_against_the_group_mean() {
+        //12, 6 and 6 RVUs per day, a mean of 8.
+        let report = attribution(&[
+            ("Carter", &[(8, 5.0), (9, 7.0)]),
+            ("Able", &[(8, 10.0), (9, 14.0)]),
+            ("Baker", &[(10, 6.0)]),
+        ])
+        .equity_report();
+
+        let rows: Vec<(usize, &str, usize, f64, f64, f64)> = report
+            .iter()
+            .map(|row| {
+                (
+                    row.rank,
+                    row.radiologist.as_str(),
+                    row.days,
+                    row.per_day.rvu,
+                    row.deviation.rvu,
+                    row.deviation_fraction.rvu,
+                )
+            })
+            .collect();
+        //Baker and Carter tie and are ranked by name.
+        assert_eq!(
+            rows,
+            vec![
+                (1, "Able", 2, 12.0, 4.0, 0.5),
+                (2, "Baker", 1, 6.0, -2.0, -0.25),
+                (3, "Carter", 2, 6.0, -2.0, -0.25),
+            ]
+        );
+        assert_eq!(report[0].total.bvu, 48.0);
+        assert_eq!(report[0].deviation.bvu, 8.0);
+
+        //The standard deviation of 12, 6 and 6 is the square root of 8.
+        assert!((equity_variation(&report) - 8.0_f64.sqrt() / 8.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn a_single_person_has_no_deviation() {
+        let report = attribution(&[("Able", &[(8, 10.0), (9, 4.0)])]).equity_report();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].rank, 1);
+        assert_eq!(report[0].per_day.rvu, 7.0);
+        assert_eq!(report[0].deviation.rvu, 0.0);
+        assert_eq!(report[0].deviation_fraction.rvu, 0.0);
+        assert_eq!(equity_variation(&report), 0.0);
+
+        assert!(attribution(&[]).equity_report().is_empty());
+        assert_eq!(equity_variation(&[]), 0.0);
+    }
+}
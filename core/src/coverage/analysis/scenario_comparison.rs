@@ -0,0 +1,107 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use crate::{
+    analysis::analysis_datum::ComparisonDatum,
+    coverage::{
+        analysis::comparison::{compare_labeled, ComparisonResult},
+        work_coverage_map::maps::CoverageMap,
+    },
+    rotations::baseline::RotationBaseline,
+};
+
+pub const HISTORICAL: &str = "historical";
+pub const SCENARIO: &str = "scenario";
+
+/// Each rotation's average daily volume historically and under a growth scenario, for one manifest.
+/// `comparison` is laid out as `compare` lays out active and proposed, keyed by `HISTORICAL` and `SCENARIO`.
+pub struct ScenarioComparison {
+    pub label: String,
+    pub comparison: ComparisonResult,
+    pub baselines: Vec<RotationBaseline>,
+}
+
+impl ScenarioComparison {
+    pub fn build(
+        label: &str,
+        historical_map: &CoverageMap,
+        scenario_map: &CoverageMap,
+        baselines: Option<Vec<RotationBaseline>>,
+    ) -> ScenarioComparison {
+        ScenarioComparison {
+            label: label.to_string(),
+            comparison: compare_labeled(HISTORICAL, historical_map, SCENARIO, scenario_map),
+            baselines: baselines.unwrap_or_default(),
+        }
+    }
+
+    fn volumes(&self, key: &str) -> Option<&BTreeMap<String, ComparisonDatum>> {
+        self.comparison.get(key)
+    }
+}
+
+//Percent change, blank when there's nothing to compare against.
+fn percent_change(from: Option<f64>, to: Option<f64>) -> String {
+    match (from, to) {
+        (Some(from), Some(to)) if from != 0.0 => format!("{:.1}", (to - from) / from * 100.0),
+        _ => String::new(),
+    }
+}
+
+fn volume(value: Option<f64>) -> String {
+    match value {
+        Some(x) => format!("{:.2}", x),
+        None => String::new(),
+    }
+}
+
+/// Writes the historical and scenario volumes of every rotation, with the scenario's deviation from baselines where the manifest has them.
+pub fn scenario_comparisons_to_tsv(
+    comparisons: &[ScenarioComparison],
+    filename: &str,
+) -> Result<(), Box<dyn Error>> {
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+    writeln!(
+        writer,
+        "Manifest \u{0009} Rotation \u{0009} Historical RVU \u{0009} Scenario RVU \u{0009} RVU Change % \u{0009} Baseline RVU \u{0009} Scenario RVU Deviation % \u{0009} Historical BVU \u{0009} Scenario BVU \u{0009} BVU Change % \u{0009} Baseline BVU \u{0009} Scenario BVU Deviation %"
+    )?;
+    for comparison in comparisons {
+        let rotations: BTreeSet<&String> = [HISTORICAL, SCENARIO]
+            .iter()
+            .filter_map(|key| comparison.volumes(key))
+            .flat_map(|volumes| volumes.keys())
+            .collect();
+        for rotation in rotations {
+            let historical = comparison.volumes(HISTORICAL).and_then(|x| x.get(rotation));
+            let scenario = comparison.volumes(SCENARIO).and_then(|x| x.get(rotation));
+            let baseline = comparison
+                .baselines
+                .iter()
+                .find(|baseline| &baseline.rotation == rotation);
+            let mut columns: Vec<String> = vec![comparison.label.to_string(), rotation.to_string()];
+            for measure in [|x: &ComparisonDatum| x.rvu, |x: &ComparisonDatum| x.bvu] {
+                let historical_value = historical.map(measure);
+                let scenario_value = scenario.map(measure);
+                let baseline_value = baseline.map(|baseline| {
+                    measure(&ComparisonDatum {
+                        rvu: baseline.rvu,
+                        bvu: baseline.bvu,
+                    })
+                });
+                columns.push(volume(historical_value));
+                columns.push(volume(scenario_value));
+                columns.push(percent_change(historical_value, scenario_value));
+                columns.push(volume(baseline_value));
+                columns.push(percent_change(baseline_value, scenario_value));
+            }
+            writeln!(writer, "{}", columns.join(" \u{0009} "))?;
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,326 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use chrono::NaiveDate;
+
+use crate::{
+    analysis::volumes::{CategorizedVolumes, VolumesMark},
+    rotations::staffing::{StaffingModel, WEEKS_PER_YEAR},
+};
+
+pub const ROTATIONS_SUFFIX: &str = "_Rotations.tsv";
+pub const PEOPLE_SUFFIX: &str = "_People.tsv";
+
+/// A rotation's historical volume per shift projected over a year of shifts.
+pub struct RotationProjection {
+    pub rotation: String,
+    pub shifts_per_week: f64,
+    pub frequency_from_model: bool,
+    pub per_shift: VolumesMark,
+    pub annual: VolumesMark,
+}
+
+impl RotationProjection {
+    pub fn shifts_per_year(&self) -> f64 {
+        self.shifts_per_week * WEEKS_PER_YEAR
+    }
+}
+
+/// A person's share of the projected annual volume, in proportion to the shifts they're available for.
+pub struct PersonProjection {
+    pub name: String,
+    pub fte: f64,
+    pub shifts_available: f64,
+    pub annual: VolumesMark,
+}
+
+pub struct StaffingProjection {
+    pub rotations: Vec<RotationProjection>,
+    pub people: Vec<PersonProjection>,
+    pub annual: VolumesMark,
+    pub shifts_needed: f64,
+    pub shifts_available: f64,
+    pub fte_needed: f64,
+    pub fte_available: f64,
+}
+
+impl StaffingProjection {
+    pub fn per_fte(&self) -> VolumesMark {
+        if self.fte_available > 0.0 {
+            VolumesMark {
+                rvu: self.annual.rvu / self.fte_available,
+                bvu: self.annual.bvu / self.fte_available,
+            }
+        } else {
+            VolumesMark { rvu: 0.0, bvu: 0.0 }
+        }
+    }
+
+    /// Writes the rotation and person projections to two TSVs named by appending to `filename_base`.
+    pub fn to_tsv(&self, filename_base: &str) -> Result<(), Box<dyn Error>> {
+        let file = File::create(filename_base.to_string() + ROTATIONS_SUFFIX)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "Rotation \u{0009} Shifts per Week \u{0009} Frequency Source \u{0009} Shifts per Year \u{0009} RVU per Shift \u{0009} BVU per Shift \u{0009} Annual RVU \u{0009} Annual BVU"
+        )?;
+        for rotation in &self.rotations {
+            writeln!(
+                writer,
+                "{} \u{0009} {:.2} \u{0009} {} \u{0009} {:.1} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {:.0} \u{0009} {:.0}",
+                rotation.rotation,
+                rotation.shifts_per_week,
+                if rotation.frequency_from_model {
+                    "Model"
+                } else {
+                    "History"
+                },
+                rotation.shifts_per_year(),
+                rotation.per_shift.rvu,
+                rotation.per_shift.bvu,
+                rotation.annual.rvu,
+                rotation.annual.bvu
+            )?;
+        }
+        writeln!(
+            writer,
+            "Total \u{0009} \u{0009} \u{0009} {:.1} \u{0009} \u{0009} \u{0009} {:.0} \u{0009} {:.0}",
+            self.shifts_needed, self.annual.rvu, self.annual.bvu
+        )?;
+        writer.flush()?;
+
+        let file = File::create(filename_base.to_string() + PEOPLE_SUFFIX)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "Name \u{0009} FTE \u{0009} Shifts Available \u{0009} Annual RVU \u{0009} Annual BVU"
+        )?;
+        for person in &self.people {
+            writeln!(
+                writer,
+                "{} \u{0009} {:.2} \u{0009} {:.1} \u{0009} {:.0} \u{0009} {:.0}",
+                person.name,
+                person.fte,
+                person.shifts_available,
+                person.annual.rvu,
+                person.annual.bvu
+            )?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Projects a year of the manifest from its historical volumes between `start` and `end`.
+/// Each rotation is assumed to need one person per shift. Rotations absent from the model's frequencies are
+/// staffed as often as they had work historically.
+pub fn project_staffing(
+    volumes: &CategorizedVolumes,
+    model: &StaffingModel,
+    start: &NaiveDate,
+    end: &NaiveDate,
+) -> StaffingProjection {
+    let history_weeks = ((*end - *start).num_days() + 1).max(1) as f64 / 7.0;
+
+    let mut totals: BTreeMap<&str, VolumesMark> = BTreeMap::new();
+    let mut dates: BTreeMap<&str, BTreeSet<NaiveDate>> = BTreeMap::new();
+    for (date, rotation_map) in volumes.get_date_map() {
+        if date < start || end < date {
+            continue;
+        }
+        for (rotation, mark) in rotation_map {
+            *totals
+                .entry(rotation.as_str())
+                .or_insert(VolumesMark { rvu: 0.0, bvu: 0.0 }) += *mark;
+            dates.entry(rotation.as_str()).or_default().insert(*date);
+        }
+    }
+
+    let mut rotations: Vec<RotationProjection> = Vec::new();
+    for (rotation, total) in totals {
+        let shift_count = dates.get(rotation).map(|x| x.len()).unwrap_or(0).max(1) as f64;
+        let per_shift = VolumesMark {
+            rvu: total.rvu / shift_count,
+            bvu: total.bvu / shift_count,
+        };
+        let (shifts_per_week, frequency_from_model) = match model.get_rotation_frequency(rotation) {
+            Some(x) => (x, true),
+            None => (shift_count / history_weeks, false),
+        };
+        let shifts_per_year = shifts_per_week * WEEKS_PER_YEAR;
+        rotations.push(RotationProjection {
+            rotation: rotation.to_string(),
+            shifts_per_week,
+            frequency_from_model,
+            per_shift,
+            annual: VolumesMark {
+                rvu: per_shift.rvu * shifts_per_year,
+                bvu: per_shift.bvu * shifts_per_year,
+            },
+        });
+    }
+
+    let annual = rotations
+        .iter()
+        .fold(VolumesMark { rvu: 0.0, bvu: 0.0 }, |acc, x| acc + x.annual);
+    let shifts_needed: f64 = rotations.iter().map(|x| x.shifts_per_year()).sum();
+    let shifts_available: f64 = model
+        .people
+        .iter()
+        .map(|person| model.shifts_per_year(person))
+        .sum();
+
+    let people = model
+        .people
+        .iter()
+        .map(|person| {
+            let shifts = model.shifts_per_year(person);
+            let share = if shifts_available > 0.0 {
+                shifts / shifts_available
+            } else {
+                0.0
+            };
+            PersonProjection {
+                name: person.name.to_string(),
+                fte: person.fte,
+                shifts_available: shifts,
+                annual: VolumesMark {
+                    rvu: annual.rvu * share,
+                    bvu: annual.bvu * share,
+                },
+            }
+        })
+        .collect();
+
+    let shifts_per_fte = model.shifts_per_fte();
+    StaffingProjection {
+        rotations,
+        people,
+        annual,
+        shifts_needed,
+        shifts_available,
+        fte_needed: if shifts_per_fte > 0.0 {
+            shifts_needed / shifts_per_fte
+        } else {
+            0.0
+        },
+        fte_available: model.people.iter().map(|person| person.fte).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    //Two weeks of history: Day worked four weekdays, Weekend three weekend days, and Day after the period.
+    fn projection() -> StaffingProjection {
+        let mut volumes = CategorizedVolumes::new();
+        for day in [1, 2, 3, 4] {
+            volumes.add(
+                date(day),
+                "Day",
+                VolumesMark {
+                    rvu: 10.0,
+                    bvu: 20.0,
+                },
+            );
+        }
+        for day in [6, 7, 13] {
+            volumes.add(
+                date(day),
+                "Weekend",
+                VolumesMark {
+                    rvu: 6.0,
+                    bvu: 12.0,
+                },
+            );
+        }
+        volumes.add(
+            date(15),
+            "Day",
+            VolumesMark {
+                rvu: 100.0,
+                bvu: 200.0,
+            },
+        );
+
+        let model: StaffingModel = serde_yaml::from_str(
+            "people:
+- name: Able
+  fte: 1.0
+  vacation_weeks: 4
+- name: Baker
+  fte: 0.5
+  vacation_weeks: 2
+working_days_per_year: 250
+days_per_week: 5
+rotation_frequency:
+  Day: 5
+",
+        )
+        .expect("Test model should parse");
+
+        project_staffing(&volumes, &model, &date(1), &date(14))
+    }
+
+    #[test]
+    fn rotations_are_annualized_from_their_volume_per_shift() {
+        let projection = projection();
+        assert_eq!(projection.rotations.len(), 2);
+
+        let day = &projection.rotations[0];
+        assert_eq!(day.rotation, "Day");
+        assert!(day.frequency_from_model);
+        assert_close(day.per_shift.rvu, 10.0);
+        assert_close(day.shifts_per_year(), 260.0);
+        assert_close(day.annual.rvu, 2600.0);
+        assert_close(day.annual.bvu, 5200.0);
+
+        //Not in the model, so staffed as often as it had work: three shifts in two weeks.
+        let weekend = &projection.rotations[1];
+        assert_eq!(weekend.rotation, "Weekend");
+        assert!(!weekend.frequency_from_model);
+        assert_close(weekend.shifts_per_week, 1.5);
+        assert_close(weekend.per_shift.rvu, 6.0);
+        assert_close(weekend.annual.rvu, 468.0);
+
+        assert_close(projection.annual.rvu, 3068.0);
+        assert_close(projection.shifts_needed, 338.0);
+    }
+
+    #[test]
+    fn fte_needed_uses_the_group_average_vacation() {
+        let projection = projection();
+
+        //Able is available for 250 - 4 * 5 shifts, Baker for half of 250 - 2 * 5.
+        assert_close(projection.shifts_available, 350.0);
+        assert_close(projection.fte_available, 1.5);
+        assert_close(projection.people[0].shifts_available, 230.0);
+        assert_close(projection.people[1].shifts_available, 120.0);
+        assert_close(projection.people[0].annual.rvu, 3068.0 * 230.0 / 350.0);
+        assert_close(projection.people[1].annual.rvu, 3068.0 * 120.0 / 350.0);
+
+        //A full time person with the FTE weighted average of 10 / 3 vacation weeks works 250 - 50 / 3 shifts.
+        assert_close(projection.fte_needed, 338.0 / (250.0 - 50.0 / 3.0));
+        assert_close(projection.per_fte().rvu, 3068.0 / 1.5);
+        assert_close(projection.per_fte().bvu, 6136.0 / 1.5);
+    }
+}
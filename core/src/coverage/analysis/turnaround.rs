@@ -0,0 +1,316 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use chrono::{NaiveDateTime, Timelike};
+
+use crate::{
+    analysis::analysis_datum::ExamTimestamps,
+    coverage::{coordinate::CoverageCoordinates, work_coverage_map::maps::CoverageMap},
+};
+
+//Name, first hour, and end hour of each time of day bucket. Exams are bucketed by the hour they were started.
+const TIME_OF_DAY_BUCKETS: &[(&str, u32, u32)] = &[
+    ("Overnight", 0, 8),
+    ("Day", 8, 17),
+    ("Evening", 17, 24),
+];
+const ALL_DAY: &str = "All";
+
+pub const BY_ROTATION_SUFFIX: &str = "_By_Rotation.tsv";
+pub const BY_COORDINATE_SUFFIX: &str = "_By_Coordinate.tsv";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TurnaroundMetric {
+    StartedToPreliminary,
+    StartedToFinalized,
+    CompletedToFinalized,
+}
+
+const ALL_METRICS: &[TurnaroundMetric] = &[
+    TurnaroundMetric::StartedToPreliminary,
+    TurnaroundMetric::StartedToFinalized,
+    TurnaroundMetric::CompletedToFinalized,
+];
+
+impl TurnaroundMetric {
+    /// Minutes between the metric's two timestamps, or None if the exam lacks either one.
+    fn minutes(&self, timestamps: &ExamTimestamps) -> Option<f64> {
+        let (from, to): (Option<NaiveDateTime>, Option<NaiveDateTime>) = match self {
+            TurnaroundMetric::StartedToPreliminary => {
+                (Some(timestamps.started), timestamps.preliminary)
+            }
+            TurnaroundMetric::StartedToFinalized => (Some(timestamps.started), timestamps.finalized),
+            TurnaroundMetric::CompletedToFinalized => (timestamps.completed, timestamps.finalized),
+        };
+        let minutes = (to? - from?).num_seconds() as f64 / 60.0;
+        //Out of order timestamps are data entry errors, not fast reads.
+        if minutes < 0.0 {
+            None
+        } else {
+            Some(minutes)
+        }
+    }
+}
+
+fn time_of_day_bucket(datetime: &NaiveDateTime) -> &'static str {
+    let hour = datetime.hour();
+    for (name, start, end) in TIME_OF_DAY_BUCKETS {
+        if *start <= hour && hour < *end {
+            return name;
+        }
+    }
+    panic!("Time of day buckets should cover every hour.");
+}
+
+/// Summary statistics of a set of turnaround times, in minutes.
+pub struct TurnaroundDistribution {
+    pub exams: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub p90: f64,
+    pub max: f64,
+}
+
+//Nearest rank percentile of sorted values.
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = ((percent / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl TurnaroundDistribution {
+    fn build(values: &[f64]) -> Option<TurnaroundDistribution> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        Some(TurnaroundDistribution {
+            exams: sorted.len(),
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            median: percentile(&sorted, 50.0),
+            p90: percentile(&sorted, 90.0),
+            max: *sorted.last().expect("Should not be empty"),
+        })
+    }
+}
+
+/// Turnaround times grouped by the rotation responsible for each exam.
+#[derive(Default)]
+pub struct TurnaroundAnalysis {
+    by_rotation: BTreeMap<(String, &'static str, TurnaroundMetric), Vec<f64>>,
+    by_coordinate: BTreeMap<(CoverageCoordinates, String, TurnaroundMetric), Vec<f64>>,
+}
+
+impl TurnaroundAnalysis {
+    pub fn get_by_rotation(
+        &self,
+    ) -> BTreeMap<(String, &'static str, TurnaroundMetric), TurnaroundDistribution> {
+        self.by_rotation
+            .iter()
+            .filter_map(|(key, values)| {
+                TurnaroundDistribution::build(values).map(|dist| (key.to_owned(), dist))
+            })
+            .collect()
+    }
+
+    pub fn get_by_coordinate(
+        &self,
+    ) -> BTreeMap<(CoverageCoordinates, String, TurnaroundMetric), TurnaroundDistribution> {
+        self.by_coordinate
+            .iter()
+            .filter_map(|(key, values)| {
+                TurnaroundDistribution::build(values).map(|dist| (key.to_owned(), dist))
+            })
+            .collect()
+    }
+
+    /// Writes the rotation and coordinate distributions to two TSVs named by appending to `filename_base`.
+    pub fn to_tsv(&self, filename_base: &str) -> Result<(), Box<dyn Error>> {
+        let file = File::create(filename_base.to_string() + BY_ROTATION_SUFFIX)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "Rotation \u{0009} Time of Day \u{0009} Metric \u{0009} Exams \u{0009} Mean \u{0009} Median \u{0009} 90th Percentile \u{0009} Max"
+        )?;
+        for ((rotation, bucket, metric), dist) in self.get_by_rotation() {
+            writeln!(
+                writer,
+                "{} \u{0009} {} \u{0009} {:?} \u{0009} {} \u{0009} {:.1} \u{0009} {:.1} \u{0009} {:.1} \u{0009} {:.1}",
+                rotation, bucket, metric, dist.exams, dist.mean, dist.median, dist.p90, dist.max
+            )?;
+        }
+        writer.flush()?;
+
+        let file = File::create(filename_base.to_string() + BY_COORDINATE_SUFFIX)?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "Site \u{0009} Exam \u{0009} Context \u{0009} Day of Week \u{0009} Rotation \u{0009} Metric \u{0009} Exams \u{0009} Mean \u{0009} Median \u{0009} 90th Percentile \u{0009} Max"
+        )?;
+        for ((coords, rotation, metric), dist) in self.get_by_coordinate() {
+            writeln!(
+                writer,
+                "{} \u{0009} {} \u{0009} {} \u{0009} {} \u{0009} {} \u{0009} {:?} \u{0009} {} \u{0009} {:.1} \u{0009} {:.1} \u{0009} {:.1} \u{0009} {:.1}",
+                coords.facility,
+                coords.subspecialty,
+                coords.context,
+                coords.weekday,
+                rotation,
+                metric,
+                dist.exams,
+                dist.mean,
+                dist.median,
+                dist.p90,
+                dist.max
+            )?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Collects the turnaround times of every exam under the rotation responsible for it.
+/// Exams shared between rotations count toward each rotation with a nonzero share.
+pub fn analyze_turnaround(coverage_map: &CoverageMap) -> TurnaroundAnalysis {
+    let mut retval = TurnaroundAnalysis::default();
+
+    coverage_map.foreach(|coords, coverage_and_workday| {
+        coverage_and_workday.for_each_work_unit_by_rotation(|work, coverage, share| {
+            if share <= 0.0 {
+                return;
+            }
+            let rotation = coverage.get_rotation();
+            let timestamps = work.get_timestamps();
+            let bucket = time_of_day_bucket(&timestamps.started);
+            for metric in ALL_METRICS {
+                if let Some(minutes) = metric.minutes(timestamps) {
+                    for time_of_day in [ALL_DAY, bucket] {
+                        retval
+                            .by_rotation
+                            .entry((rotation.to_string(), time_of_day, *metric))
+                            .or_default()
+                            .push(minutes);
+                    }
+                    retval
+                        .by_coordinate
+                        .entry((coords.to_owned(), rotation.to_string(), *metric))
+                        .or_default()
+                        .push(minutes);
+                }
+            }
+        });
+    });
+
+    retval
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coverage::work_coverage_map::testing;
+
+    #[test]
+    fn percentiles_are_nearest_rank() {
+        let sorted = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(percentile(&sorted, 50.0), 3.0);
+        assert_eq!(percentile(&sorted, 90.0), 5.0);
+
+        let sorted = [10.0, 20.0, 30.0, 40.0];
+        assert_eq!(percentile(&sorted, 50.0), 20.0);
+        assert_eq!(percentile(&sorted, 90.0), 40.0);
+
+        let sorted: Vec<f64> = (1..=10).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 50.0), 5.0);
+        assert_eq!(percentile(&sorted, 90.0), 9.0);
+    }
+
+    #[test]
+    fn a_single_exam_is_every_statistic() {
+        let dist = TurnaroundDistribution::build(&[7.0]).expect("One value is a distribution");
+        assert_eq!(dist.exams, 1);
+        assert_eq!(
+            [dist.mean, dist.median, dist.p90, dist.max],
+            [7.0, 7.0, 7.0, 7.0]
+        );
+        assert!(TurnaroundDistribution::build(&[]).is_none());
+    }
+
+    //An exam started on Monday the 8th at `started`, with its other timestamps in minutes after that.
+    fn exam(
+        started: u32,
+        completed: Option<i64>,
+        preliminary: Option<i64>,
+        finalized: Option<i64>,
+    ) -> crate::analysis::analysis_datum::WorkUnit {
+        let started = testing::at(8, started, 0);
+        let after = |minutes: Option<i64>| minutes.map(|x| started + chrono::Duration::minutes(x));
+        testing::exam(started, 1.0, 1).with_timestamps(ExamTimestamps {
+            started,
+            completed: after(completed),
+            preliminary: after(preliminary),
+            finalized: after(finalized),
+        })
+    }
+
+    #[test]
+    fn missing_and_out_of_order_timestamps_are_left_out() {
+        let manifest = testing::manifest(
+            "title: Test
+rotation_manifest:
+- rotation: Day
+  location: Remote
+  responsibilities:
+  - sites: SH
+    exams: General CT
+    contexts: ED
+    days: Mon/Tue/Wed/Thu/Fri
+    time_periods:
+    - 08:00 CD-17:00 CD
+",
+        );
+        let mut coverage_map = testing::coverage_map(&manifest);
+        testing::add_work(&mut coverage_map, exam(9, Some(10), Some(30), Some(60)));
+        //Not completed or finalized yet.
+        testing::add_work(&mut coverage_map, exam(10, None, Some(20), None));
+        //Finalized before it was recorded as completed.
+        testing::add_work(&mut coverage_map, exam(11, Some(30), Some(20), Some(25)));
+
+        let by_rotation = analyze_turnaround(&coverage_map).get_by_rotation();
+        let stats = |metric: TurnaroundMetric| {
+            let dist = &by_rotation[&("Day".to_string(), ALL_DAY, metric)];
+            (dist.exams, dist.mean, dist.median, dist.p90, dist.max)
+        };
+        assert_eq!(
+            stats(TurnaroundMetric::StartedToPreliminary),
+            (3, 70.0 / 3.0, 20.0, 30.0, 30.0)
+        );
+        assert_eq!(
+            stats(TurnaroundMetric::StartedToFinalized),
+            (2, 42.5, 25.0, 60.0, 60.0)
+        );
+        assert_eq!(
+            stats(TurnaroundMetric::CompletedToFinalized),
+            (1, 50.0, 50.0, 50.0, 50.0)
+        );
+        //Every exam was started during the day.
+        assert_eq!(
+            by_rotation[&(
+                "Day".to_string(),
+                "Day",
+                TurnaroundMetric::StartedToPreliminary
+            )]
+                .exams,
+            3
+        );
+        assert!(!by_rotation.contains_key(&(
+            "Day".to_string(),
+            "Evening",
+            TurnaroundMetric::StartedToPreliminary
+        )));
+    }
+}
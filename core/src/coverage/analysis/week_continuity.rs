@@ -0,0 +1,111 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use serde::Serialize;
+
+use crate::{
+    coverage::{
+        coordinate::CoverageCoordinates,
+        week_coverage::{build_week_coverage, MinuteOfWeek, WeekCoordinates},
+        work_coverage_map::maps::CoverageMap,
+    },
+    serialization::output::JSONFileOut,
+};
+
+/// A stretch of the week without coverage, with the volume that fell within it across the whole date range.
+/// `unaudited_days` are the weekdays it touches with neither coverage nor work, which the per-day audit never sees.
+#[derive(Serialize)]
+pub struct ContinuityGap {
+    #[serde(flatten)]
+    pub coordinates: WeekCoordinates,
+    pub start: MinuteOfWeek,
+    pub end: MinuteOfWeek,
+    pub hours: f64,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub rvus: f64,
+    pub bvus: f64,
+    pub unaudited_days: Vec<String>,
+}
+
+impl JSONFileOut for Vec<ContinuityGap> {}
+
+/// Checks that each site, exam, and context is covered continuously around the whole week, Sunday night into Monday included.
+pub fn audit_week_continuity(coverage_map: &CoverageMap) -> Vec<ContinuityGap> {
+    let mut retval: Vec<ContinuityGap> = Vec::new();
+
+    for (coordinates, week_coverage) in build_week_coverage(coverage_map) {
+        for gap in week_coverage.gaps() {
+            let mut rvus: f64 = 0.0;
+            let mut bvus: f64 = 0.0;
+            let mut unaudited_days: Vec<String> = Vec::new();
+            for (weekday, start, end) in gap.split_by_day() {
+                let coords = CoverageCoordinates {
+                    facility: coordinates.facility.to_string(),
+                    subspecialty: coordinates.subspecialty.to_string(),
+                    context: coordinates.context.to_string(),
+                    weekday,
+                };
+                match coverage_map.get_coverageandworkday(&coords) {
+                    Some(coverage_and_workday) => {
+                        for work_unit in coverage_and_workday.get_work_in_timespan(start, end) {
+                            rvus += work_unit.get_absolute_rvu();
+                            bvus += work_unit.get_absolute_bvu();
+                        }
+                    }
+                    None => unaudited_days.push(weekday.to_string()),
+                }
+            }
+
+            retval.push(ContinuityGap {
+                coordinates: coordinates.clone(),
+                start: MinuteOfWeek::from_minutes(gap.start),
+                end: MinuteOfWeek::from_minutes(gap.end),
+                hours: gap.minutes() as f64 / 60.0,
+                before: gap.before,
+                after: gap.after,
+                rvus,
+                bvus,
+                unaudited_days,
+            });
+        }
+    }
+
+    retval
+}
+
+pub fn week_continuity_to_tsv(
+    gaps: &[ContinuityGap],
+    filename: &str,
+) -> Result<(), Box<dyn Error>> {
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+
+    writeln!(
+        writer,
+        "Site \u{0009} Exam \u{0009} Context \u{0009} Start \u{0009} End \u{0009} Hours \u{0009} Before \u{0009} After \u{0009} RVUs \u{0009} BVUs \u{0009} Unaudited Days"
+    )?;
+    for gap in gaps {
+        writeln!(
+            writer,
+            "{} \u{0009} {} \u{0009} {} \u{0009} {} \u{0009} {} \u{0009} {:.2} \u{0009} {} \u{0009} {} \u{0009} {:.2} \u{0009} {:.2} \u{0009} {}",
+            gap.coordinates.facility,
+            gap.coordinates.subspecialty,
+            gap.coordinates.context,
+            gap.start,
+            gap.end,
+            gap.hours,
+            gap.before.as_deref().unwrap_or(""),
+            gap.after.as_deref().unwrap_or(""),
+            gap.rvus,
+            gap.bvus,
+            gap.unaudited_days.join("/")
+        )?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,366 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+};
+
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+
+use crate::{
+    analysis::analysis_datum::{AnalysisDatum, ComparisonDatum},
+    coverage::{coordinate::CoverageCoordinates, malformed_coverage::CoverageError},
+    globals::ALL_DAYS,
+    rotations::baseline::RotationBaseline,
+};
+
+const VOLUME_FORMAT: &str = "0.00";
+const PERCENT_FORMAT: &str = "0.0%";
+
+const COORDINATE_HEADERS: &[&str] = &["Site", "Exam", "Context", "Day of Week"];
+
+/// A single Excel workbook bundling the audit and volume analyses, one sheet per table.
+pub struct AnalysisWorkbook {
+    workbook: Workbook,
+    header_format: Format,
+    volume_format: Format,
+    percent_format: Format,
+}
+
+fn write_headers(
+    worksheet: &mut Worksheet,
+    headers: &[&str],
+    format: &Format,
+) -> Result<(), Box<dyn Error>> {
+    for (col, header) in headers.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, format)?;
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+fn write_coordinates(
+    worksheet: &mut Worksheet,
+    row: u32,
+    coords: &CoverageCoordinates,
+) -> Result<(), Box<dyn Error>> {
+    worksheet.write_string(row, 0, &coords.facility)?;
+    worksheet.write_string(row, 1, &coords.subspecialty)?;
+    worksheet.write_string(row, 2, &coords.context)?;
+    worksheet.write_string(row, 3, coords.weekday.to_string())?;
+    Ok(())
+}
+
+impl AnalysisWorkbook {
+    pub fn new() -> AnalysisWorkbook {
+        AnalysisWorkbook {
+            workbook: Workbook::new(),
+            header_format: Format::new().set_bold(),
+            volume_format: Format::new().set_num_format(VOLUME_FORMAT),
+            percent_format: Format::new().set_num_format(PERCENT_FORMAT),
+        }
+    }
+
+    fn add_sheet(&mut self, name: &str) -> Result<&mut Worksheet, Box<dyn Error>> {
+        let worksheet = self.workbook.add_worksheet();
+        worksheet.set_name(name)?;
+        Ok(worksheet)
+    }
+
+    /// Adds the audit errors and the coordinates without work as two sheets, mirroring the TSV audit outputs.
+    pub fn add_audit(
+        &mut self,
+        label: &str,
+        audit_result: &HashMap<CoverageCoordinates, CoverageError>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut sorted_keys: Vec<&CoverageCoordinates> = audit_result.keys().collect();
+        sorted_keys.sort();
+
+        let header_format = self.header_format.clone();
+        let volume_format = self.volume_format.clone();
+
+        let worksheet = self.add_sheet(&format!("{} Audit", label))?;
+        let mut headers: Vec<&str> = COORDINATE_HEADERS.to_vec();
+        headers.extend(["Issue", "Detail", "RVUs"]);
+        write_headers(worksheet, &headers, &header_format)?;
+
+        let mut row: u32 = 1;
+        for coords in &sorted_keys {
+            let mut issues: Vec<(&str, String, Option<f64>)> = Vec::new();
+            match audit_result.get(*coords).expect("Should be a key") {
+                CoverageError::NoCoverage(rvus) => {
+                    issues.push(("No coverage", "".to_string(), Some(*rvus)));
+                }
+                CoverageError::MalformedCoverage(errs) => {
+                    for gap in &errs.gaps {
+                        issues.push((
+                            "Coverage gap",
+                            format!("{}-{} {}", gap.start, gap.end, gap.description),
+                            Some(gap.rvus),
+                        ));
+                    }
+                    for overlap in &errs.overlaps {
+                        issues.push(("Coverage overlap", overlap.to_string(), None));
+                    }
+                    if let Some(x) = errs.incorrect_fraction {
+                        issues.push(("Incorrect fraction", x.to_string(), None));
+                    }
+                    if let Some(x) = errs.incorrect_shared_fraction {
+                        issues.push(("Incorrect shared fraction", x.to_string(), None));
+                    }
+                }
+            }
+
+            for (issue, detail, rvus) in issues {
+                write_coordinates(worksheet, row, coords)?;
+                worksheet.write_string(row, 4, issue)?;
+                worksheet.write_string(row, 5, detail)?;
+                if let Some(rvus) = rvus {
+                    worksheet.write_number_with_format(row, 6, rvus, &volume_format)?;
+                }
+                row += 1;
+            }
+        }
+        worksheet.autofit();
+
+        let worksheet = self.add_sheet(&format!("{} Audit (no work)", label))?;
+        write_headers(worksheet, COORDINATE_HEADERS, &header_format)?;
+        let mut row: u32 = 1;
+        for coords in &sorted_keys {
+            if let CoverageError::MalformedCoverage(errs) =
+                audit_result.get(*coords).expect("Should be a key")
+            {
+                if errs.no_work {
+                    write_coordinates(worksheet, row, coords)?;
+                    row += 1;
+                }
+            }
+        }
+        worksheet.autofit();
+
+        Ok(())
+    }
+
+    /// Adds the average RVUs and BVUs for each rotation by day of week as two sheets.
+    pub fn add_day_of_week_analysis(
+        &mut self,
+        label: &str,
+        analysis: &HashMap<String, HashMap<chrono::Weekday, AnalysisDatum>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut rotations: Vec<&String> = analysis.keys().collect();
+        rotations.sort();
+
+        let header_format = self.header_format.clone();
+        let volume_format = self.volume_format.clone();
+
+        for use_rvu in [true, false] {
+            let unit = if use_rvu { "RVU" } else { "BVU" };
+            let worksheet = self.add_sheet(&format!("{} {} by Weekday", label, unit))?;
+
+            let weekday_strings: Vec<String> =
+                ALL_DAYS.iter().map(|weekday| weekday.to_string()).collect();
+            let mut headers: Vec<&str> = vec!["Rotation"];
+            headers.extend(weekday_strings.iter().map(|x| x.as_str()));
+            write_headers(worksheet, &headers, &header_format)?;
+
+            for (n, rotation) in rotations.iter().enumerate() {
+                let row = n as u32 + 1;
+                worksheet.write_string(row, 0, *rotation)?;
+                let daymap = analysis.get(*rotation).expect("Should be a key");
+                for (col, weekday) in ALL_DAYS.iter().enumerate() {
+                    if let Some(datum) = daymap.get(weekday) {
+                        let value = if use_rvu {
+                            datum.get_rvu()
+                        } else {
+                            datum.get_bvu()
+                        };
+                        worksheet.write_number_with_format(
+                            row,
+                            col as u16 + 1,
+                            value,
+                            &volume_format,
+                        )?;
+                    }
+                }
+            }
+            worksheet.autofit();
+        }
+
+        Ok(())
+    }
+
+    /// Adds a side by side comparison of the average daily volume of each rotation in each manifest.
+    pub fn add_comparison(
+        &mut self,
+        comparison: &BTreeMap<String, BTreeMap<String, ComparisonDatum>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let header_format = self.header_format.clone();
+        let volume_format = self.volume_format.clone();
+
+        let worksheet = self.add_sheet("Comparison")?;
+
+        let mut headers: Vec<String> = vec!["Rotation".to_string()];
+        for manifest in comparison.keys() {
+            headers.push(format!("{} RVU", manifest));
+            headers.push(format!("{} BVU", manifest));
+        }
+        write_headers(
+            worksheet,
+            &headers.iter().map(|x| x.as_str()).collect::<Vec<&str>>(),
+            &header_format,
+        )?;
+
+        let mut rotations: Vec<&String> = comparison
+            .values()
+            .flat_map(|rotation_map| rotation_map.keys())
+            .collect();
+        rotations.sort();
+        rotations.dedup();
+
+        for (n, rotation) in rotations.iter().enumerate() {
+            let row = n as u32 + 1;
+            worksheet.write_string(row, 0, *rotation)?;
+            for (m, rotation_map) in comparison.values().enumerate() {
+                if let Some(datum) = rotation_map.get(*rotation) {
+                    let col = 1 + 2 * m as u16;
+                    worksheet.write_number_with_format(row, col, datum.rvu, &volume_format)?;
+                    worksheet.write_number_with_format(row, col + 1, datum.bvu, &volume_format)?;
+                }
+            }
+        }
+        worksheet.autofit();
+
+        Ok(())
+    }
+
+    /// Adds the deviation of each rotation's average daily volume from its baseline.
+    pub fn add_baseline_deviations(
+        &mut self,
+        label: &str,
+        baselines: &[RotationBaseline],
+        averages: &BTreeMap<String, ComparisonDatum>,
+    ) -> Result<(), Box<dyn Error>> {
+        let header_format = self.header_format.clone();
+        let volume_format = self.volume_format.clone();
+        let percent_format = self.percent_format.clone();
+
+        let worksheet = self.add_sheet(&format!("{} Baselines", label))?;
+        write_headers(
+            worksheet,
+            &[
+                "Rotation",
+                "Baseline RVU",
+                "RVU",
+                "RVU Deviation",
+                "RVU Deviation %",
+                "Baseline BVU",
+                "BVU",
+                "BVU Deviation",
+                "BVU Deviation %",
+            ],
+            &header_format,
+        )?;
+
+        for (n, baseline) in baselines.iter().enumerate() {
+            let row = n as u32 + 1;
+            worksheet.write_string(row, 0, &baseline.rotation)?;
+
+            let average = averages.get(&baseline.rotation);
+            for (col, baseline_value, value) in [
+                (1, baseline.rvu, average.map(|x| x.rvu)),
+                (5, baseline.bvu, average.map(|x| x.bvu)),
+            ] {
+                worksheet.write_number_with_format(row, col, baseline_value, &volume_format)?;
+                if let Some(value) = value {
+                    let deviation = value - baseline_value;
+                    worksheet.write_number_with_format(row, col + 1, value, &volume_format)?;
+                    worksheet.write_number_with_format(row, col + 2, deviation, &volume_format)?;
+                    if baseline_value != 0.0 {
+                        worksheet.write_number_with_format(
+                            row,
+                            col + 3,
+                            deviation / baseline_value,
+                            &percent_format,
+                        )?;
+                    }
+                }
+            }
+        }
+        worksheet.autofit();
+
+        Ok(())
+    }
+
+    pub fn save(&mut self, filename: &str) -> Result<(), Box<dyn Error>> {
+        self.workbook.save(filename)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        coverage::{
+            analysis::{
+                by_day_of_week::analyze_by_day_of_week,
+                comparison::{compare, get_by_rotation_average},
+                coverage_audit::audit,
+            },
+            work_coverage_map::{maps::CoverageMap, testing},
+        },
+        ACTIVE_OUTPUTS, PROPOSED_OUTPUTS,
+    };
+
+    //Day covers Monday and Tuesday but only reads on Monday, with one exam after it ends, so the audit has a gap and no work.
+    fn coverage_map(rotation: &str) -> CoverageMap {
+        let mut coverage_map = testing::coverage_map(&testing::manifest(&format!(
+            "title: Test\nrotation_manifest:\n- rotation: {}\n  location: Remote\n  responsibilities:\n  - sites: SH\n    exams: General CT\n    contexts: ED\n    days: Mon/Tue\n    time_periods:\n    - 08:00 CD-17:00 CD\n",
+            rotation
+        )));
+        testing::add_exam(&mut coverage_map, testing::at(1, 10, 0), 2.0, 1);
+        testing::add_exam(&mut coverage_map, testing::at(1, 20, 0), 1.0, 1);
+        coverage_map
+    }
+
+    //Sheets are named from the output labels, and Excel rejects names longer than 31 characters.
+    #[test]
+    fn saves_every_sheet() {
+        let mut active = coverage_map("Day");
+        let mut proposed = coverage_map("Late");
+
+        let mut workbook = AnalysisWorkbook::new();
+        for (label, coverage_map) in [
+            (ACTIVE_OUTPUTS.label, &mut active),
+            (PROPOSED_OUTPUTS.label, &mut proposed),
+        ] {
+            let audit_result = audit(coverage_map);
+            assert!(!audit_result.is_empty());
+            workbook
+                .add_audit(label, &audit_result)
+                .expect("Audit sheets should be added");
+            workbook
+                .add_day_of_week_analysis(label, &analyze_by_day_of_week(coverage_map))
+                .expect("Analysis sheets should be added");
+            let baselines = [RotationBaseline {
+                rotation: "Day".to_string(),
+                rvu: 2.0,
+                bvu: 2.0,
+            }];
+            workbook
+                .add_baseline_deviations(label, &baselines, &get_by_rotation_average(coverage_map))
+                .expect("Baseline sheet should be added");
+        }
+        workbook
+            .add_comparison(&compare(&active, &proposed))
+            .expect("Comparison sheet should be added");
+
+        let path = std::env::temp_dir().join(format!(
+            "rotation_tool_workbook_{}.xlsx",
+            std::process::id()
+        ));
+        let filename = path.to_str().expect("Scratch paths are UTF-8");
+        workbook.save(filename).expect("Workbook should save");
+        let metadata = std::fs::metadata(filename).expect("Workbook should exist");
+        assert!(metadata.len() > 0);
+        let _ = std::fs::remove_file(filename);
+    }
+}
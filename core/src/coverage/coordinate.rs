@@ -1,4 +1,6 @@
-#[derive(Eq, Hash, PartialEq, Clone)]
+use serde::Serialize;
+
+#[derive(Eq, Hash, PartialEq, Clone, Serialize)]
 pub struct CoverageCoordinates {
     pub facility: String,
     pub subspecialty: String,
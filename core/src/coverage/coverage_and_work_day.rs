@@ -1,21 +1,30 @@
-use std::{collections::HashMap, f32::consts::E};
+use std::{collections::{BTreeSet, HashMap}, f32::consts::E};
 
 use chrono::{Datelike, Days, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use serde::Serialize;
 
 use crate::{
     analysis::analysis_datum::{AnalysisDatum, WorkUnit},
-    rotations::time_modifiers::{TimeSinceMidnight, NEXT_MIDNIGHT, THIS_MIDNIGHT},
+    rotations::{
+        overlap_policy::OverlapPolicy,
+        time_modifiers::{TimeSinceMidnight, NEXT_MIDNIGHT, THIS_MIDNIGHT},
+    },
 };
 
 use super::{
-    coordinate::CoverageCoordinates, malformed_coverage::{CoverageError, MalformedCoverage}, units::{temporal_coverage::{weekday_plus, TemporalCoverageUnit}, Coverage, CoverageUnit}
+    coordinate::CoverageCoordinates, malformed_coverage::{CoverageError, CoverageGap, CoverageOverlap, MalformedCoverage}, units::{temporal_coverage::{weekday_plus, TemporalCoverageUnit}, Coverage, CoverageUnit}
 };
 
 #[derive(Debug, Default, Serialize, Clone)]
 pub struct CoverageAndWorkDay {
     coverages: Option<Coverage>,
     work: Vec<WorkUnit>,
+    #[serde(skip)]
+    overlap_policies: Vec<OverlapPolicy>,
+}
+
+fn work_time(work: &WorkUnit) -> TimeSinceMidnight {
+    TimeSinceMidnight::from_minutes((work.get_datetime().num_seconds_from_midnight() / 60).into())
 }
 
 pub enum TimeAdjustment
@@ -82,26 +91,131 @@ impl CoverageAndWorkDay {
                     CoverageUnit::Temporal(_) => Coverage::Temporal(Vec::new()),
                     CoverageUnit::WeekFraction(_) => Coverage::Fractional(Vec::new()),
                 };
-                let retval = instantiated_coverages.add(coverage);
+                instantiated_coverages.add(coverage);
                 self.coverages = Some(instantiated_coverages);
-                retval
             }
         }
+        Ok(())
     }
 
+    /// Removes the coverage and the overlap policies kept for it, so coverage from another manifest starts clean.
     pub fn clear_coverage(&mut self) {
-        self.coverages=None
+        self.coverages=None;
+        self.overlap_policies.clear();
+    }
+
+    pub fn get_coverage(&self) -> Option<&Coverage> {
+        self.coverages.as_ref()
+    }
+
+    /// Keeps the policies that could apply to this day's coverage, i.e. those naming at least two of its rotations.
+    pub fn set_overlap_policies(&mut self, policies: &[OverlapPolicy]) {
+        let rotations: BTreeSet<String> = self
+            .temporal_units()
+            .iter()
+            .map(|tcu| tcu.get_rotation())
+            .collect();
+
+        self.overlap_policies = policies
+            .iter()
+            .filter(|policy| policy.rotations.intersection(&rotations).count() > 1)
+            .cloned()
+            .collect();
+    }
+
+    fn overlap_policy_for(&self, covering: &BTreeSet<String>) -> Option<&OverlapPolicy> {
+        self.overlap_policies
+            .iter()
+            .find(|policy| policy.governs(covering))
+    }
+
+    fn temporal_units(&self) -> &[TemporalCoverageUnit] {
+        match &self.coverages {
+            Some(coverages) => coverages.temporal_units(),
+            None => &[],
+        }
+    }
+
+    fn temporally_covered(&self, time: TimeSinceMidnight) -> bool {
+        self.temporal_units()
+            .iter()
+            .any(|tcu| tcu.start <= time && time < tcu.end)
+    }
+
+    //Rotations with unshared temporal coverage at the given time.
+    fn covering_rotations(&self, time: TimeSinceMidnight) -> BTreeSet<String> {
+        self.temporal_units()
+            .iter()
+            .filter(|tcu| !tcu.is_shared() && tcu.start <= time && time < tcu.end)
+            .map(|tcu| tcu.get_rotation())
+            .collect()
+    }
+
+    //The share of a work unit attributed to the temporal coverage. Shared timespans are split by their fractions.
+    //Otherwise, work in an undeclared overlap is counted in full by each rotation.
+    fn work_share(&self, work: &WorkUnit, tcu: &TemporalCoverageUnit) -> f64 {
+        if tcu.is_shared() {
+            return tcu.get_fraction();
+        }
+        if self.overlap_policies.is_empty() {
+            return 1.0;
+        }
+        let covering = self.covering_rotations(work_time(work));
+        match self.overlap_policy_for(&covering) {
+            Some(policy) => policy.share(&tcu.get_rotation(), &covering),
+            None => 1.0,
+        }
+    }
+
+    //Sum of the fractions of the first portion of the day covered by a shared timespan whose fractions don't sum to one.
+    fn incorrect_shared_fraction(&self) -> Option<f64> {
+        let temporal_units = self.temporal_units();
+        if !temporal_units.iter().any(|tcu| tcu.is_shared()) {
+            return None;
+        }
+
+        let mut breakpoints: Vec<TimeSinceMidnight> = temporal_units
+            .iter()
+            .flat_map(|tcu| [tcu.start, tcu.end])
+            .collect();
+        breakpoints.sort();
+        breakpoints.dedup();
+
+        for window in breakpoints.windows(2) {
+            let covering: Vec<&TemporalCoverageUnit> = temporal_units
+                .iter()
+                .filter(|tcu| tcu.start <= window[0] && window[1] <= tcu.end)
+                .collect();
+            if covering.iter().any(|tcu| tcu.is_shared()) {
+                let sum: f64 = covering.iter().map(|tcu| tcu.get_fraction()).sum();
+                if (sum - 1.0).abs() > 0.001 {
+                    return Some(sum);
+                }
+            }
+        }
+        None
+    }
+
+    //Sum of the fractional layer's fractions, if there is one.
+    fn fractional_sum(&self) -> Option<f64> {
+        let fractional_units = self.coverages.as_ref()?.fractional_units();
+        if fractional_units.is_empty() {
+            None
+        } else {
+            Some(fractional_units.iter().map(|fcu| fcu.get_fraction()).sum())
+        }
     }
 
     fn sort_coverage(&mut self) {
         match &mut self.coverages {
-            Some(coverages) => match coverages {
-                Coverage::Temporal(temporal_coverages) => {
-                    temporal_coverages.sort();
-                }
-                Coverage::Fractional(_) => (),
-            },
-            None => (),
+            Some(Coverage::Temporal(temporal_coverages))
+            | Some(Coverage::Layered {
+                temporal: temporal_coverages,
+                ..
+            }) => {
+                temporal_coverages.sort();
+            }
+            _ => (),
         }
     }
 
@@ -112,9 +226,7 @@ impl CoverageAndWorkDay {
     ) -> Vec<&WorkUnit> {
         let mut retval: Vec<&WorkUnit> = Vec::new();
         for work in &self.work {
-            let tsm = TimeSinceMidnight::from_minutes(
-                (work.get_datetime().num_seconds_from_midnight() / 60).into(),
-            );
+            let tsm = work_time(work);
             if start <= tsm && tsm < end {
                 retval.push(work);
             }
@@ -146,16 +258,27 @@ impl CoverageAndWorkDay {
         retval
     }
 
+    //The work attributed to the coverage unit and the share of each work unit it's responsible for.
+    fn shared_work(&self,coverage:&CoverageUnit)->Vec<(&WorkUnit,f64)>
+    {
+        match coverage
+        {
+            CoverageUnit::Temporal(tcu) => self.get_work_in_timespan(tcu.start, tcu.end)
+                .into_iter()
+                .map(|work|(work,self.work_share(work,tcu)))
+                .collect(),
+            //Fractional coverage layered under temporal coverage only shares the work the temporal coverage leaves.
+            CoverageUnit::WeekFraction(fcu) => self.work.iter()
+                .filter(|work|{!self.temporally_covered(work_time(work))})
+                .map(|work|(work,fcu.get_fraction()))
+                .collect()
+        }
+    }
+
     fn collect_work_by_rotation_date(&self,coverage:&CoverageUnit)->HashMap<NaiveDate,AnalysisDatum>
     {
         let mut retval: HashMap<NaiveDate,AnalysisDatum> = HashMap::new();
 
-        let mut collected_work:Vec<&WorkUnit> = match coverage
-        {
-            CoverageUnit::Temporal(tcu) => self.get_work_in_timespan(tcu.start, tcu.end),
-            CoverageUnit::WeekFraction(_fcu) => self.work.iter().collect()
-        };
-
         /*
         let wu;
         if collected_work.is_empty()
@@ -180,36 +303,23 @@ impl CoverageAndWorkDay {
         }
         */
 
-        for work in collected_work {
+        for (work,share) in self.shared_work(coverage) {
             let rotation_date=coverage.get_time_adjustment().get_date(work.get_datetime().date());
             match retval.entry(rotation_date)
             {
                 std::collections::hash_map::Entry::Occupied(mut occ) => 
                 {
-                    occ.get_mut().add_workunit(work)
+                    occ.get_mut().add_scaled_workunit(work,share)
                 },
                 std::collections::hash_map::Entry::Vacant(vac) => 
                 {
                     let mut new_unit:AnalysisDatum=AnalysisDatum::default();
-                    new_unit.add_workunit(work);
+                    new_unit.add_scaled_workunit(work,share);
                     vac.insert(new_unit);
                 },
             };
         }
 
-        //If it's fractional coverage, scale the result
-        match coverage
-        {
-            CoverageUnit::Temporal(_tcu) => {
-            },
-            CoverageUnit::WeekFraction(fcu) => {
-                for ad in retval.values_mut()
-                {
-                    ad.scale(fcu.get_fraction());
-                }
-            },
-        };
-
         retval
     }
 
@@ -239,12 +349,31 @@ impl CoverageAndWorkDay {
         );
     }
 
+    /// Calls `fun` for each work unit a coverage unit is responsible for, with the share of the work unit attributed to it.
+    pub fn for_each_work_unit_by_rotation<T>(&self,mut fun:T)
+    where T:FnMut(&WorkUnit,&CoverageUnit,f64)
+    {
+        self.for_each_coverage_unit(
+            |coverage:&CoverageUnit|
+            {
+                for (work,share) in self.shared_work(coverage)
+                {
+                    fun(work,coverage,share);
+                }
+            }
+        );
+    }
 
     pub fn work_iterator(&self)->core::slice::Iter<'_,WorkUnit>
     {
         self.work.iter()
     }
 
+    pub fn work_iterator_mut(&mut self)->core::slice::IterMut<'_,WorkUnit>
+    {
+        self.work.iter_mut()
+    }
+
     pub fn audit_coverage(&mut self) -> CoverageError {
         self.sort_coverage();
 
@@ -258,85 +387,105 @@ impl CoverageAndWorkDay {
 
                 retval.no_work = self.work.is_empty();
 
-                match coverages {
-                    Coverage::Temporal(temporal_coverages) => {
-                        match temporal_coverages.split_first() {
-                            Some((mut farthest_unit, rest)) => {
-                                //Check from midnight
-                                if farthest_unit.starts_after_this_midnight() {
-                                    let rvus = &self.aggregate_work_in_timespan(
-                                        THIS_MIDNIGHT,
-                                        farthest_unit.start,
-                                    );
-                                    retval.gaps.push((
-                                        THIS_MIDNIGHT,
-                                        farthest_unit.start,
-                                        farthest_unit.to_string() + " starts after midnight",
-                                        rvus.get_rvu(),
-                                    ))
-                                }
+                //Fractional coverage layered under temporal coverage covers whatever the temporal coverage doesn't,
+                //but only if its fractions account for all of that work. Otherwise the gaps are still gaps.
+                let gaps_covered = match self.fractional_sum() {
+                    Some(sum) => (sum - 1.0).abs() <= 0.001,
+                    None => false,
+                };
 
-                                for cu in rest {
-                                    if farthest_unit.end_overlaps_other(cu)
-                                    //Check overlap
-                                    {
-                                        retval.overlaps.push(
-                                            TemporalCoverageUnit::get_overlap_desc(
-                                                farthest_unit,
-                                                cu,
-                                            ),
-                                        );
-                                    } else if farthest_unit.gap_between_end_and_other(cu)
-                                    //Check gap
-                                    {
-                                        let rvus = &self.aggregate_work_in_timespan(
-                                            farthest_unit.end,
-                                            cu.start,
-                                        );
-                                        retval.gaps.push((
-                                            farthest_unit.end,
-                                            cu.start,
-                                            TemporalCoverageUnit::get_overlap_desc(
-                                                farthest_unit,
-                                                cu,
-                                            ),
-                                            rvus.get_rvu(),
-                                        ));
-                                    }
-
-                                    //Adjust prior_end
-                                    if cu.ends_after_other(farthest_unit) {
-                                        farthest_unit = cu;
-                                    }
-                                }
-                                //Check through midnight
-                                if farthest_unit.ends_before_next_midnight() {
-                                    let rvus = &self.aggregate_work_in_timespan(
-                                        farthest_unit.end,
-                                        NEXT_MIDNIGHT,
-                                    );
-                                    retval.gaps.push((
-                                        farthest_unit.end,
-                                        NEXT_MIDNIGHT,
-                                        farthest_unit.to_string() + " ends before midnight",
-                                        rvus.get_rvu(),
-                                    ));
+                match coverages.temporal_units().split_first() {
+                    Some((mut farthest_unit, rest)) => {
+                        //Check from midnight
+                        if !gaps_covered && farthest_unit.starts_after_this_midnight() {
+                            let rvus = &self.aggregate_work_in_timespan(
+                                THIS_MIDNIGHT,
+                                farthest_unit.start,
+                            );
+                            retval.gaps.push(CoverageGap {
+                                start: THIS_MIDNIGHT,
+                                end: farthest_unit.start,
+                                before: None,
+                                after: Some(farthest_unit.get_rotation()),
+                                description: farthest_unit.to_string()
+                                    + " starts after midnight",
+                                rvus: rvus.get_rvu(),
+                            })
+                        }
+
+                        for cu in rest {
+                            if farthest_unit.end_overlaps_other(cu)
+                            //Check overlap
+                            {
+                                //Overlaps declared in the manifest are intentional. Rotations sharing a timespan are checked
+                                //by their fractions instead, but an unshared unit overlapping a shared one is still an overlap.
+                                let pair = BTreeSet::from([
+                                    farthest_unit.get_rotation(),
+                                    cu.get_rotation(),
+                                ]);
+                                if self.overlap_policy_for(&pair).is_none()
+                                    && !(farthest_unit.is_shared() && cu.is_shared())
+                                {
+                                    retval.overlaps.push(CoverageOverlap {
+                                        start: cu.start,
+                                        end: farthest_unit.end.min(cu.end),
+                                        first: farthest_unit.get_rotation(),
+                                        second: cu.get_rotation(),
+                                        description: TemporalCoverageUnit::get_overlap_desc(
+                                            farthest_unit,
+                                            cu,
+                                        ),
+                                    });
                                 }
+                            } else if !gaps_covered && farthest_unit.gap_between_end_and_other(cu)
+                            //Check gap
+                            {
+                                let rvus = &self.aggregate_work_in_timespan(
+                                    farthest_unit.end,
+                                    cu.start,
+                                );
+                                retval.gaps.push(CoverageGap {
+                                    start: farthest_unit.end,
+                                    end: cu.start,
+                                    before: Some(farthest_unit.get_rotation()),
+                                    after: Some(cu.get_rotation()),
+                                    description: TemporalCoverageUnit::get_overlap_desc(
+                                        farthest_unit,
+                                        cu,
+                                    ),
+                                    rvus: rvus.get_rvu(),
+                                });
                             }
-                            None => (),
-                        };
-                    }
-                    Coverage::Fractional(fractional_coverages) => {
-                        let mut sum: f64 = 0.0;
-                        for coverage in fractional_coverages {
-                            sum += coverage.get_fraction();
-                        }
 
-                        if (sum - 1.0).abs() > 0.001 {
-                            retval.incorrect_fraction = Some(sum);
+                            //Adjust prior_end
+                            if cu.ends_after_other(farthest_unit) {
+                                farthest_unit = cu;
+                            }
+                        }
+                        //Check through midnight
+                        if !gaps_covered && farthest_unit.ends_before_next_midnight() {
+                            let rvus = &self.aggregate_work_in_timespan(
+                                farthest_unit.end,
+                                NEXT_MIDNIGHT,
+                            );
+                            retval.gaps.push(CoverageGap {
+                                start: farthest_unit.end,
+                                end: NEXT_MIDNIGHT,
+                                before: Some(farthest_unit.get_rotation()),
+                                after: None,
+                                description: farthest_unit.to_string()
+                                    + " ends before midnight",
+                                rvus: rvus.get_rvu(),
+                            });
                         }
                     }
-                }
+                    None => (),
+                };
+
+                retval.incorrect_shared_fraction = self.incorrect_shared_fraction();
+                retval.incorrect_fraction = self
+                    .fractional_sum()
+                    .filter(|sum| (sum - 1.0).abs() > 0.001);
 
                 CoverageError::MalformedCoverage(retval)
             }
@@ -345,3 +494,185 @@ impl CoverageAndWorkDay {
 
 
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn hours(hours: u64) -> TimeSinceMidnight {
+        TimeSinceMidnight::new(hours * 60)
+    }
+
+    fn temporal(start: u64, end: u64, rotation: &str) -> CoverageUnit {
+        CoverageUnit::Temporal(TemporalCoverageUnit::create(
+            hours(start),
+            hours(end),
+            rotation.to_string(),
+            0,
+        ))
+    }
+
+    fn policy(yaml: &str) -> OverlapPolicy {
+        serde_yaml::from_str(yaml).expect("Valid overlap policy")
+    }
+
+    fn day(units: Vec<CoverageUnit>, policies: &[OverlapPolicy], work_hours: &[u64]) -> CoverageAndWorkDay {
+        let mut retval = CoverageAndWorkDay::default();
+        for unit in units {
+            retval.add_coverage(unit).expect("Coverage adds");
+        }
+        retval.set_overlap_policies(policies);
+        for hour in work_hours {
+            let datetime = NaiveDate::from_ymd_opt(2024, 1, 8)
+                .and_then(|date| date.and_hms_opt(*hour as u32, 30, 0))
+                .expect("Valid work time");
+            retval.add_work(WorkUnit::create(datetime, 1.0, 1.0, "Exam".to_string()));
+        }
+        retval
+    }
+
+    //Each rotation's share of the work, summed over the day.
+    fn shares(day: &CoverageAndWorkDay) -> BTreeMap<String, f64> {
+        let mut retval: BTreeMap<String, f64> = BTreeMap::new();
+        day.for_each_work_unit_by_rotation(|_work, coverage, share| {
+            *retval.entry(coverage.get_rotation()).or_default() += share;
+        });
+        retval
+    }
+
+    fn malformed(day: &mut CoverageAndWorkDay) -> MalformedCoverage {
+        match day.audit_coverage() {
+            CoverageError::MalformedCoverage(x) => x,
+            CoverageError::NoCoverage(_) => panic!("The day has coverage"),
+        }
+    }
+
+    fn overlapping_day(policies: &[OverlapPolicy]) -> CoverageAndWorkDay {
+        day(
+            vec![temporal(0, 17, "Day"), temporal(12, 24, "Late")],
+            policies,
+            &[13],
+        )
+    }
+
+    #[test]
+    fn undeclared_overlaps_are_reported_and_counted_twice() {
+        let mut day = overlapping_day(&[]);
+        let audit = malformed(&mut day);
+        assert_eq!(audit.overlaps.len(), 1);
+        assert_eq!(audit.overlaps[0].start, hours(12));
+        assert_eq!(audit.overlaps[0].end, hours(17));
+        assert_eq!(shares(&day), BTreeMap::from([("Day".to_string(), 1.0), ("Late".to_string(), 1.0)]));
+    }
+
+    #[test]
+    fn declared_overlaps_split_the_work() {
+        let mut equal = overlapping_day(&[policy("rotations: [Day, Late]\nsplit: equal\n")]);
+        assert!(malformed(&mut equal).overlaps.is_empty());
+        assert_eq!(shares(&equal), BTreeMap::from([("Day".to_string(), 0.5), ("Late".to_string(), 0.5)]));
+
+        let primary = overlapping_day(&[policy("rotations: [Day, Late]\nsplit: primary\nprimary: Late\n")]);
+        assert_eq!(shares(&primary), BTreeMap::from([("Day".to_string(), 0.0), ("Late".to_string(), 1.0)]));
+
+        let weighted = overlapping_day(&[policy("rotations: [Day, Late]\nsplit: weighted\nweights:\n  Day: 3\n  Late: 1\n")]);
+        assert_eq!(shares(&weighted), BTreeMap::from([("Day".to_string(), 0.75), ("Late".to_string(), 0.25)]));
+    }
+
+    //Coverage for Monday from a manifest responsibility, as the coverage tree adds it.
+    fn responsibility(rotation: &str, yaml: &str) -> Vec<CoverageUnit> {
+        let responsibility: crate::rotations::responsibility::RotationResponsibility =
+            serde_yaml::from_str(&format!("sites: SH\nexams: General CT\ncontexts: ED\ndays: Mon\n{}", yaml))
+                .expect("Valid responsibility");
+        crate::coverage::work_coverage_map::maps::CoverageMap::responsibility_to_coverages(rotation, &responsibility)
+            .expect("Coverage from responsibility")
+            .into_iter()
+            .filter(|(coords, _)| coords.weekday == chrono::Weekday::Mon)
+            .map(|(_, unit)| unit)
+            .collect()
+    }
+
+    #[test]
+    fn time_periods_with_a_fraction_share_the_timespan() {
+        let units = responsibility("Split", "time_periods:\n- 08:00 CD-17:00 CD\nweekly_fraction: 0.5\n");
+        assert_eq!(units.len(), 1);
+        match &units[0] {
+            CoverageUnit::Temporal(tcu) => assert_eq!(tcu.get_fraction(), 0.5),
+            CoverageUnit::WeekFraction(_) => panic!("Time periods make temporal coverage"),
+        }
+
+        let mut units = units;
+        units.extend(responsibility("Other", "time_periods:\n- 08:00 CD-17:00 CD\nweekly_fraction: 0.5\n"));
+        units.extend(responsibility("Pool", "weekly_fraction: 1.0\n"));
+        let mut day = day(units, &[], &[9]);
+        let audit = malformed(&mut day);
+        assert!(audit.overlaps.is_empty());
+        assert!(audit.gaps.is_empty());
+        assert_eq!(audit.incorrect_shared_fraction, None);
+        assert_eq!(shares(&day), BTreeMap::from([
+            ("Other".to_string(), 0.5),
+            ("Split".to_string(), 0.5),
+        ]));
+    }
+
+    #[test]
+    fn a_complete_fractional_layer_covers_the_gaps() {
+        let mut units = vec![temporal(8, 17, "Day")];
+        units.extend(responsibility("Pool", "weekly_fraction: 1.0\n"));
+        let mut day = day(units, &[], &[9, 20]);
+        let audit = malformed(&mut day);
+        assert!(audit.gaps.is_empty());
+        assert_eq!(audit.incorrect_fraction, None);
+        assert_eq!(shares(&day), BTreeMap::from([("Day".to_string(), 1.0), ("Pool".to_string(), 1.0)]));
+    }
+
+    #[test]
+    fn an_incomplete_fractional_layer_leaves_the_gaps() {
+        let mut units = vec![temporal(8, 17, "Day")];
+        units.extend(responsibility("Pool", "weekly_fraction: 0.2\n"));
+        let mut day = day(units, &[], &[20]);
+        let audit = malformed(&mut day);
+        assert_eq!(
+            audit.gaps.iter().map(|gap| (gap.start, gap.end)).collect::<Vec<_>>(),
+            [(hours(0), hours(8)), (hours(17), hours(24))]
+        );
+        assert_eq!(audit.gaps[1].rvus, 1.0);
+        assert_eq!(audit.incorrect_fraction, Some(0.2));
+    }
+
+    #[test]
+    fn unshared_coverage_overlapping_a_shared_timespan_is_reported() {
+        let mut units = vec![temporal(0, 17, "Day"), temporal(17, 24, "Night")];
+        units.extend(responsibility("Split", "time_periods:\n- 12:00 CD-20:00 CD\nweekly_fraction: 0.5\n"));
+        units.extend(responsibility("Other", "time_periods:\n- 12:00 CD-20:00 CD\nweekly_fraction: 0.5\n"));
+        units.extend(responsibility("Pool", "weekly_fraction: 0.5\n"));
+        let mut day = day(units, &[], &[]);
+        let audit = malformed(&mut day);
+        assert!(audit.overlaps.iter().any(|overlap| overlap.first == "Day"));
+        assert!(audit.overlaps.iter().any(|overlap| overlap.second == "Night"));
+        assert!(!audit.overlaps.iter().any(|overlap| {
+            BTreeSet::from([overlap.first.as_str(), overlap.second.as_str()]) == BTreeSet::from(["Split", "Other"])
+        }));
+        //Both fraction errors are kept.
+        assert_eq!(audit.incorrect_shared_fraction, Some(2.0));
+        assert_eq!(audit.incorrect_fraction, Some(0.5));
+    }
+
+    #[test]
+    fn clearing_coverage_drops_its_policies() {
+        let mut day = overlapping_day(&[policy("rotations: [Day, Late]\nsplit: equal\n")]);
+        day.clear_coverage();
+        day.add_coverage(temporal(0, 17, "Day")).expect("Coverage adds");
+        day.add_coverage(temporal(12, 24, "Late")).expect("Coverage adds");
+        assert_eq!(malformed(&mut day).overlaps.len(), 1);
+        assert_eq!(shares(&day), BTreeMap::from([("Day".to_string(), 1.0), ("Late".to_string(), 1.0)]));
+    }
+
+    #[test]
+    fn policies_only_cover_their_rotations() {
+        let mut day = overlapping_day(&[policy("rotations: [Day, Night]\nsplit: equal\n")]);
+        assert_eq!(malformed(&mut day).overlaps.len(), 1);
+        assert_eq!(shares(&day)["Day"], 1.0);
+    }
+}
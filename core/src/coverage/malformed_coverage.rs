@@ -1,14 +1,72 @@
-use crate::rotations::time_modifiers::TimeSinceMidnight;
+use std::fmt;
 
-#[derive(Default, Debug)]
+use serde::Serialize;
+
+use crate::{
+    coverage::coordinate::CoverageCoordinates, rotations::time_modifiers::TimeSinceMidnight,
+    serialization::output::JSONFileOut,
+};
+
+/// An uncovered interval. `before` and `after` are the rotations on either side of the gap, if any.
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageGap {
+    pub start: TimeSinceMidnight,
+    pub end: TimeSinceMidnight,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub description: String,
+    pub rvus: f64,
+}
+
+impl fmt::Display for CoverageGap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{} {} ({} rvus)",
+            self.start, self.end, self.description, self.rvus
+        )
+    }
+}
+
+/// An interval covered by more than one temporal coverage unit.
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageOverlap {
+    pub start: TimeSinceMidnight,
+    pub end: TimeSinceMidnight,
+    pub first: String,
+    pub second: String,
+    pub description: String,
+}
+
+impl fmt::Display for CoverageOverlap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.description)
+    }
+}
+
+#[derive(Default, Debug, Serialize)]
 pub struct MalformedCoverage {
-    pub gaps: Vec<(TimeSinceMidnight, TimeSinceMidnight, String, f64)>,
-    pub overlaps: Vec<String>,
+    pub gaps: Vec<CoverageGap>,
+    pub overlaps: Vec<CoverageOverlap>,
+    //Sum of the fractional layer, when it doesn't cover all of the day's remaining work.
     pub incorrect_fraction: Option<f64>,
+    //Sum of the fractions covering a shared timespan, when they don't add up to the whole timespan.
+    pub incorrect_shared_fraction: Option<f64>,
     pub no_work: bool,
 }
 
+#[derive(Debug, Serialize)]
 pub enum CoverageError {
     NoCoverage(f64),
     MalformedCoverage(MalformedCoverage),
 }
+
+/// A single coordinate's audit result in a serializable form.
+#[derive(Serialize)]
+pub struct AuditRecord<'a> {
+    #[serde(flatten)]
+    pub coordinates: &'a CoverageCoordinates,
+    pub error: &'a CoverageError,
+}
+
+impl JSONFileOut for Vec<AuditRecord<'_>> {}
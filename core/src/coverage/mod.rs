@@ -4,4 +4,5 @@ pub(crate) mod coverage_and_work_day;
 pub(crate) mod distribution;
 pub(crate) mod malformed_coverage;
 pub(crate) mod units;
+pub(crate) mod week_coverage;
 pub(crate) mod work_coverage_map;
@@ -1,8 +1,6 @@
 
 use serde::Serialize;
 
-use crate::error::source_error::SourceError;
-
 use self::{fractional_coverage::FractionalCoverageUnit, temporal_coverage::TemporalCoverageUnit};
 
 use super::coverage_and_work_day::TimeAdjustment;
@@ -40,52 +38,73 @@ impl CoverageUnit
 pub enum Coverage {
     Temporal(Vec<TemporalCoverageUnit>),
     Fractional(Vec<FractionalCoverageUnit>),
+    //Temporal coverage first, with the fractional units sharing the work the temporal units don't cover.
+    Layered {
+        temporal: Vec<TemporalCoverageUnit>,
+        fractional: Vec<FractionalCoverageUnit>,
+    },
 }
 
 impl Coverage {
-    fn coverage_error(coverage: &CoverageUnit, coverages: &Coverage) -> String {
-        let message = format!("Mixing fractional and temporal coverage types is not allowed. This was attempted for {:?} with the following coverages already listed {:?}",coverage,coverages);
-        message
+    pub fn add(&mut self, coverage: CoverageUnit) {
+        match (self, coverage) {
+            (Coverage::Temporal(coverages), CoverageUnit::Temporal(new_coverage))
+            | (Coverage::Layered { temporal: coverages, .. }, CoverageUnit::Temporal(new_coverage)) => {
+                coverages.push(new_coverage);
+                coverages.sort();
+            }
+            (Coverage::Fractional(coverages), CoverageUnit::WeekFraction(new_coverage))
+            | (
+                Coverage::Layered { fractional: coverages, .. },
+                CoverageUnit::WeekFraction(new_coverage),
+            ) => {
+                coverages.push(new_coverage);
+                coverages.sort();
+            }
+            (this @ Coverage::Temporal(_), CoverageUnit::WeekFraction(new_coverage)) => {
+                let temporal = match std::mem::replace(this, Coverage::Fractional(Vec::new())) {
+                    Coverage::Temporal(x) => x,
+                    _ => unreachable!(),
+                };
+                *this = Coverage::Layered {
+                    temporal,
+                    fractional: vec![new_coverage],
+                };
+            }
+            (this @ Coverage::Fractional(_), CoverageUnit::Temporal(new_coverage)) => {
+                let fractional = match std::mem::replace(this, Coverage::Temporal(Vec::new())) {
+                    Coverage::Fractional(x) => x,
+                    _ => unreachable!(),
+                };
+                *this = Coverage::Layered {
+                    temporal: vec![new_coverage],
+                    fractional,
+                };
+            }
+        }
+    }
+    pub fn temporal_units(&self) -> &[TemporalCoverageUnit] {
+        match self {
+            Coverage::Temporal(tcus) | Coverage::Layered { temporal: tcus, .. } => tcus,
+            Coverage::Fractional(_) => &[],
+        }
     }
-    pub fn add(&mut self, coverage: CoverageUnit) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn fractional_units(&self) -> &[FractionalCoverageUnit] {
         match self {
-            Coverage::Temporal(coverages) => match coverage {
-                CoverageUnit::Temporal(new_coverage) => {
-                    coverages.push(new_coverage);
-                    coverages.sort();
-                }
-                CoverageUnit::WeekFraction(new_coverage) => {
-                    return SourceError::generate_boxed(Self::coverage_error(
-                        &(CoverageUnit::WeekFraction(new_coverage)),
-                        self,
-                    ));
-                }
-            },
-            Coverage::Fractional(coverages) => match coverage {
-                CoverageUnit::Temporal(new_coverage) => {
-                    return SourceError::generate_boxed(Self::coverage_error(
-                        &(CoverageUnit::Temporal(new_coverage)),
-                        self,
-                    ));
-                }
-                CoverageUnit::WeekFraction(new_coverage) => {
-                    coverages.push(new_coverage);
-                    coverages.sort();
-                }
-            },
+            Coverage::Fractional(fcus) | Coverage::Layered { fractional: fcus, .. } => fcus,
+            Coverage::Temporal(_) => &[],
         }
-        Ok(())
     }
     pub fn to_enum_version(&self)->Vec<CoverageUnit>
     {
-        match self
-        {
-            Coverage::Temporal(tcus) => {
-                tcus.iter().map(|tcu|{CoverageUnit::Temporal(tcu.clone())}).collect()
-            },
-            Coverage::Fractional(fcus) => {
-                fcus.iter().map(|fcu|{CoverageUnit::WeekFraction(fcu.clone())}).collect()
-            },
-        }
+        self.temporal_units()
+            .iter()
+            .map(|tcu| CoverageUnit::Temporal(tcu.clone()))
+            .chain(
+                self.fractional_units()
+                    .iter()
+                    .map(|fcu| CoverageUnit::WeekFraction(fcu.clone())),
+            )
+            .collect()
     }
 }
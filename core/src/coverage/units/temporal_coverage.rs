@@ -26,7 +26,8 @@ pub struct TemporalCoverageUnit {
     pub end: TimeSinceMidnight,
     rotation: String,
     //rotation_day: SerializeableWeekday
-    work_to_rotation_day_offset:i64 //Day offset between work and rotation (relative to work, so + means work is before rotation, should generally be positive)
+    work_to_rotation_day_offset:i64, //Day offset between work and rotation (relative to work, so + means work is before rotation, should generally be positive)
+    fraction:f64 //Share of the work in this timespan, for rotations splitting a timespan
 }
 
 impl Eq for TemporalCoverageUnit {}
@@ -48,9 +49,23 @@ impl TemporalCoverageUnit {
             rotation,
             work_to_rotation_day_offset
             ,
+            fraction: 1.0,
         }
     }
 
+    pub fn with_fraction(mut self, fraction: f64) -> TemporalCoverageUnit {
+        self.fraction = fraction;
+        self
+    }
+
+    pub fn get_fraction(&self) -> f64 {
+        self.fraction
+    }
+
+    pub fn is_shared(&self) -> bool {
+        self.fraction < 1.0
+    }
+
     pub fn end_overlaps_other(&self, other: &TemporalCoverageUnit) -> bool {
         //self.weekday_offset>other.weekday_offset ||
         //(self.weekday_offset==other.weekday_offset && self.end>other.start)
@@ -0,0 +1,293 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::Serialize;
+
+use crate::{
+    coverage::{units::temporal_coverage::weekday_plus, work_coverage_map::maps::CoverageMap},
+    rotations::time_modifiers::TimeSinceMidnight,
+};
+
+const MINUTES_PER_DAY: u64 = 24 * 60;
+pub const MINUTES_PER_WEEK: u64 = 7 * MINUTES_PER_DAY;
+
+/// A point on the week, counted in minutes from Monday 00:00 and wrapping after Sunday 24:00.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct MinuteOfWeek {
+    minutes: u64,
+}
+
+impl MinuteOfWeek {
+    pub fn new(weekday: chrono::Weekday, time: TimeSinceMidnight) -> MinuteOfWeek {
+        MinuteOfWeek::from_minutes(
+            weekday.num_days_from_monday() as u64 * MINUTES_PER_DAY + time.to_minutes(),
+        )
+    }
+
+    pub fn from_minutes(minutes: u64) -> MinuteOfWeek {
+        MinuteOfWeek {
+            minutes: minutes % MINUTES_PER_WEEK,
+        }
+    }
+
+    pub fn to_minutes(self) -> u64 {
+        self.minutes
+    }
+
+    pub fn weekday(&self) -> chrono::Weekday {
+        weekday_plus(
+            chrono::Weekday::Mon,
+            (self.minutes / MINUTES_PER_DAY) as i64,
+        )
+    }
+
+    pub fn time(&self) -> TimeSinceMidnight {
+        TimeSinceMidnight::new(self.minutes % MINUTES_PER_DAY)
+    }
+}
+
+impl fmt::Display for MinuteOfWeek {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.weekday(), self.time())
+    }
+}
+
+impl Serialize for MinuteOfWeek {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// A coverage coordinate without its weekday, since the weekday is now part of the time.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize)]
+pub struct WeekCoordinates {
+    pub facility: String,
+    pub subspecialty: String,
+    pub context: String,
+}
+
+/// Covered minutes `start..end`, where `end` is past `MINUTES_PER_WEEK` for an interval running through Sunday midnight.
+#[derive(Debug, PartialEq, Clone)]
+pub struct WeekInterval {
+    pub start: u64,
+    pub end: u64,
+    pub rotation: String,
+}
+
+/// An uncovered stretch of the week, with the rotations ending before it and starting after it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct WeekGap {
+    pub start: u64,
+    pub end: u64,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl WeekGap {
+    pub fn minutes(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// The gap cut at each midnight, as weekdays and times within them.
+    pub fn split_by_day(&self) -> Vec<(chrono::Weekday, TimeSinceMidnight, TimeSinceMidnight)> {
+        let mut retval = Vec::new();
+        let mut start = self.start;
+        while start < self.end {
+            let midnight = (start / MINUTES_PER_DAY + 1) * MINUTES_PER_DAY;
+            let stop = midnight.min(self.end);
+            let weekday = MinuteOfWeek::from_minutes(start).weekday();
+            retval.push((
+                weekday,
+                TimeSinceMidnight::new(start % MINUTES_PER_DAY),
+                TimeSinceMidnight::new(stop - (midnight - MINUTES_PER_DAY)),
+            ));
+            start = stop;
+        }
+        retval
+    }
+}
+
+/// Every interval covering one site, exam, and context over the week, so coverage running through midnight,
+/// including Sunday into Monday, is one continuous stretch rather than pieces on separate days.
+#[derive(Debug, Default, Clone)]
+pub struct WeekCoverage {
+    intervals: Vec<WeekInterval>,
+}
+
+impl WeekCoverage {
+    pub fn add(&mut self, start: u64, end: u64, rotation: String) {
+        if start >= end {
+            return;
+        }
+        let length = (end - start).min(MINUTES_PER_WEEK);
+        let start = start % MINUTES_PER_WEEK;
+        let end = start + length;
+        //Split intervals running through Sunday midnight so every piece lies within one week.
+        if end > MINUTES_PER_WEEK {
+            self.intervals.push(WeekInterval {
+                start: 0,
+                end: end - MINUTES_PER_WEEK,
+                rotation: rotation.to_string(),
+            });
+            self.intervals.push(WeekInterval {
+                start,
+                end: MINUTES_PER_WEEK,
+                rotation,
+            });
+        } else {
+            self.intervals.push(WeekInterval {
+                start,
+                end,
+                rotation,
+            });
+        }
+    }
+
+    /// The uncovered stretches in order around the week. A gap through Sunday midnight ends past `MINUTES_PER_WEEK`.
+    pub fn gaps(&self) -> Vec<WeekGap> {
+        let mut sorted: Vec<&WeekInterval> = self.intervals.iter().collect();
+        sorted.sort_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+        let (first, rest) = match sorted.split_first() {
+            Some(x) => x,
+            None => {
+                return vec![WeekGap {
+                    start: 0,
+                    end: MINUTES_PER_WEEK,
+                    before: None,
+                    after: None,
+                }];
+            }
+        };
+
+        let mut retval = Vec::new();
+        let mut farthest = *first;
+        for interval in rest {
+            if interval.start > farthest.end {
+                retval.push(WeekGap {
+                    start: farthest.end,
+                    end: interval.start,
+                    before: Some(farthest.rotation.to_string()),
+                    after: Some(interval.rotation.to_string()),
+                });
+            }
+            if interval.end > farthest.end {
+                farthest = interval;
+            }
+        }
+
+        //Around Sunday midnight, back to the earliest coverage.
+        if farthest.end < first.start + MINUTES_PER_WEEK {
+            retval.push(WeekGap {
+                start: farthest.end,
+                end: first.start + MINUTES_PER_WEEK,
+                before: Some(farthest.rotation.to_string()),
+                after: Some(first.rotation.to_string()),
+            });
+        }
+
+        retval
+    }
+}
+
+/// Collects each coordinate's coverage from every weekday onto the week. Fractional coverage covers the rest of its day,
+/// so it counts as the whole day.
+pub fn build_week_coverage(coverage_map: &CoverageMap) -> BTreeMap<WeekCoordinates, WeekCoverage> {
+    let mut retval: BTreeMap<WeekCoordinates, WeekCoverage> = BTreeMap::new();
+    coverage_map.foreach(|coords, coverage_and_workday| {
+        let week_coverage = retval
+            .entry(WeekCoordinates {
+                facility: coords.facility.to_string(),
+                subspecialty: coords.subspecialty.to_string(),
+                context: coords.context.to_string(),
+            })
+            .or_default();
+        let midnight = MinuteOfWeek::new(coords.weekday, TimeSinceMidnight::new(0)).to_minutes();
+        if let Some(coverage) = coverage_and_workday.get_coverage() {
+            for tcu in coverage.temporal_units() {
+                week_coverage.add(
+                    midnight + tcu.start.to_minutes(),
+                    midnight + tcu.end.to_minutes(),
+                    tcu.get_rotation(),
+                );
+            }
+            for fcu in coverage.fractional_units() {
+                week_coverage.add(midnight, midnight + MINUTES_PER_DAY, fcu.get_rotation());
+            }
+        }
+    });
+    retval
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(weekday: chrono::Weekday, hours: u64) -> u64 {
+        MinuteOfWeek::new(weekday, TimeSinceMidnight::new(0)).to_minutes() + hours * 60
+    }
+
+    #[test]
+    fn gaps_wrap_through_sunday_midnight() {
+        let mut coverage = WeekCoverage::default();
+        for weekday in [
+            chrono::Weekday::Mon,
+            chrono::Weekday::Tue,
+            chrono::Weekday::Wed,
+            chrono::Weekday::Thu,
+            chrono::Weekday::Fri,
+        ] {
+            coverage.add(at(weekday, 8), at(weekday, 17), "Day".to_string());
+            coverage.add(at(weekday, 17), at(weekday, 24), "Night".to_string());
+        }
+
+        let gaps = coverage.gaps();
+        assert_eq!(gaps.len(), 5);
+        let weekend = gaps.last().expect("Weekend gap");
+        assert_eq!(weekend.start, at(chrono::Weekday::Sat, 0));
+        assert_eq!(weekend.end, at(chrono::Weekday::Mon, 8) + MINUTES_PER_WEEK);
+        assert_eq!(
+            weekend
+                .split_by_day()
+                .iter()
+                .map(|(weekday, _, _)| *weekday)
+                .collect::<Vec<chrono::Weekday>>(),
+            [
+                chrono::Weekday::Sat,
+                chrono::Weekday::Sun,
+                chrono::Weekday::Mon
+            ]
+        );
+        assert_eq!(
+            gaps[0].split_by_day(),
+            [(
+                chrono::Weekday::Tue,
+                TimeSinceMidnight::new(0),
+                TimeSinceMidnight::new(8 * 60)
+            )]
+        );
+    }
+
+    #[test]
+    fn sunday_night_into_monday_is_continuous() {
+        let mut coverage = WeekCoverage::default();
+        coverage.add(
+            at(chrono::Weekday::Sun, 17),
+            at(chrono::Weekday::Sun, 32),
+            "Night".to_string(),
+        );
+        coverage.add(
+            at(chrono::Weekday::Mon, 8),
+            at(chrono::Weekday::Sun, 17),
+            "Day".to_string(),
+        );
+        assert!(coverage.gaps().is_empty());
+
+        assert_eq!(
+            WeekCoverage::default().gaps()[0].minutes(),
+            MINUTES_PER_WEEK
+        );
+    }
+}
@@ -61,11 +61,6 @@ impl CoverageMap {
                             }
                         };
 
-                        if responsibility.time_periods.get().is_some()
-                            && responsibility.weekly_fraction.is_some()
-                        {
-                            return RotationManifestParseError::generate_boxed(0,"'time_periods' and 'fraction' have both been provided. One and only one must be provided.".to_string());
-                        }
                         if responsibility.time_periods.get().is_none()
                             && responsibility.weekly_fraction.is_none()
                         {
@@ -94,13 +89,18 @@ impl CoverageMap {
                                         coords.weekday =
                                             weekday_plus(weekday, work_day_offset);
 
-                                        let coverage = TemporalCoverageUnit::create(
+                                        let mut coverage = TemporalCoverageUnit::create(
                                             start,
                                             end,
                                             rotation_name.to_string(),
                                             -work_day_offset, //this is the offset to get from the day of the rotation to the day of the work, so invert this value
                                         );
 
+                                        //With both provided, the fraction is this rotation's share of the work in its time periods.
+                                        if let Some(fraction) = responsibility.weekly_fraction {
+                                            coverage = coverage.with_fraction(fraction);
+                                        }
+
                                         retval.push(
                                             (coords.clone(),CoverageUnit::Temporal(coverage))
                                         );
@@ -111,7 +111,7 @@ impl CoverageMap {
                         }
 
                         match &responsibility.weekly_fraction {
-                            Some(fraction) => {
+                            Some(fraction) if responsibility.time_periods.get().is_none() => {
                                 coords.weekday = weekday;
                                 let coverage = FractionalCoverageUnit::create(
                                     rotation_name.to_string(),
@@ -122,7 +122,7 @@ impl CoverageMap {
                                     (coords.clone(),CoverageUnit::WeekFraction(coverage))
                                 );
                             }
-                            None => (),
+                            _ => (),
                         }
                     }
                     //}
@@ -134,7 +134,7 @@ impl CoverageMap {
 
     pub fn add_coverage_from_manifest(
         &mut self,
-        manifest: Manifest,
+        manifest: &Manifest,
     ) -> Result<(), Box<dyn std::error::Error>> {
         for rotation_description in &manifest.rotation_manifest {
             match rotation_description.responsibilities.get() {
@@ -150,6 +150,12 @@ impl CoverageMap {
             };
         }
 
+        if let Some(policies) = &manifest.overlap_policies {
+            self.foreach_mut(|_coords, coverage_and_workday| {
+                coverage_and_workday.set_overlap_policies(policies)
+            });
+        }
+
         Ok(())
     }
 }
@@ -1,4 +1,6 @@
 pub(crate) mod coverage_adding;
 pub(crate) mod generics;
 pub(crate) mod maps;
+#[cfg(test)]
+pub(crate) mod testing;
 pub(crate) mod work_adding;
@@ -0,0 +1,56 @@
+//! Small coverage maps for unit tests: coverage from an inline manifest and exams at SH General CT in the ED.
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+
+use crate::{
+    analysis::analysis_datum::WorkUnit, coverage::coordinate::CoverageCoordinates, globals::SH,
+    rotations::manifest::Manifest,
+};
+
+use super::{generics::WorkCoverageMap, maps::CoverageMap};
+
+/// A manifest from inline YAML, without the checks reading a manifest file makes.
+pub(crate) fn manifest(yaml: &str) -> Manifest {
+    serde_yaml::from_str(yaml).expect("Test manifest should parse")
+}
+
+pub(crate) fn coverage_map(manifest: &Manifest) -> CoverageMap {
+    let mut retval = CoverageMap::default();
+    retval
+        .add_coverage_from_manifest(manifest)
+        .expect("Test coverage should add");
+    retval
+}
+
+/// A time in January 2024, when the 1st was a Monday.
+pub(crate) fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 1, day)
+        .and_then(|date| date.and_hms_opt(hour, minute, 0))
+        .expect("Valid test time")
+}
+
+/// An exam read by `signer_acct_id` at `datetime`, without its other timestamps.
+pub(crate) fn exam(datetime: NaiveDateTime, rvu: f64, signer_acct_id: u64) -> WorkUnit {
+    WorkUnit::create(datetime, rvu, rvu, "CT HEAD".to_string()).with_signer(signer_acct_id)
+}
+
+/// Adds `work` to SH General CT in the ED on the weekday it was read.
+pub(crate) fn add_work(coverage_map: &mut CoverageMap, work: WorkUnit) {
+    let coords = CoverageCoordinates {
+        facility: SH.to_string(),
+        subspecialty: "General CT".to_string(),
+        context: "ED".to_string(),
+        weekday: work.get_datetime().weekday(),
+    };
+    coverage_map.add_work(&coords, work);
+}
+
+/// Adds an exam read by `signer_acct_id` at `datetime` to SH General CT in the ED.
+pub(crate) fn add_exam(
+    coverage_map: &mut CoverageMap,
+    datetime: NaiveDateTime,
+    rvu: f64,
+    signer_acct_id: u64,
+) {
+    add_work(coverage_map, exam(datetime, rvu, signer_acct_id));
+}
@@ -1,7 +1,7 @@
 
 use chrono::{Datelike, NaiveDateTime};
 
-use crate::analysis::analysis_datum::WorkUnit;
+use crate::analysis::analysis_datum::{ExamTimestamps, WorkUnit};
 
 use crate::coverage::coordinate::CoverageCoordinates;
 
@@ -13,7 +13,7 @@ use crate::source_data::processing::categorization::
     build_salem_rvumap
 ;
 use crate::source_data::processing::processed_source::ProcessedSource;
-use crate::source_data::tables::exam_data::Exam;
+use crate::source_data::tables::exam_data::{AttributionTime, Exam};
 use crate::{
     constraints::ConstraintSet,
     globals::FACILITIES,
@@ -50,10 +50,13 @@ fn get_sh_facility_from_metadata(exam:&Exam)->Option<String>{
 }
 
 impl CoverageMap {
+    /// Attributes each exam to the coverage at its wall-clock time. Exams read in the hour repeated in autumn land
+    /// on the same wall-clock coverage as the first occurrence; days aren't adjusted for daylight saving.
     pub fn add_work_from_source(
         &mut self,
         source: &ProcessedSource,
         date_constraints: &ConstraintSet<'_, NaiveDateTime>,
+        attribution_time: AttributionTime,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let _retval = CoverageMap::default();
 
@@ -97,6 +100,7 @@ impl CoverageMap {
         let mut excluded_by_reader:Vec<&Exam>=Vec::new();
         let mut excluded_as_nonradiology:Vec<&Exam>=Vec::new();
         let mut excluded_both:Vec<&Exam>=Vec::new();
+        let mut missing_attribution_time:usize=0;
 
         //Process Data
         for exam in source.main_data.iter()
@@ -114,7 +118,17 @@ impl CoverageMap {
                 },
             };
 
-            if date_constraints.include(&exam.list_datetime) {
+            //Exams without the chosen timestamp fall back to the list time.
+            let attribution_datetime = match exam.get_attribution_datetime(attribution_time)
+            {
+                Some(x) => x,
+                None => {
+                    missing_attribution_time+=1;
+                    exam.list_datetime
+                }
+            };
+
+            if date_constraints.include(&attribution_datetime) {
                 //let denominator = *salem_weekday_count
                 //    .get(&NaiveDate::from(exam.list_datetime).weekday())
                 //    .expect("All weekdays should be populated")
@@ -180,7 +194,7 @@ impl CoverageMap {
                         subspecialty,
                         context,
                         //modality: modality.to_string(),
-                        weekday: exam.list_datetime.weekday(),
+                        weekday: attribution_datetime.weekday(),
                     }
                 };
 
@@ -233,12 +247,20 @@ impl CoverageMap {
                         };
 
                         WorkUnit::create(
-                            exam.list_datetime,
+                            attribution_datetime,
                             *rvu,
                             *bvu,
                             //denominator,
                             exam.procedure_description.to_string()
                         )
+                        .with_timestamps(ExamTimestamps {
+                            started: exam.list_datetime,
+                            completed: exam.completed_datetime,
+                            preliminary: exam.preliminary_datetime,
+                            finalized: exam.finalized_datetime,
+                        })
+                        .with_signer(exam.signer_acct_id)
+                        .with_exam_code(exam.exam_code.to_string())
                     };
 
                     self.add_work(&coords, work);
@@ -296,6 +318,10 @@ impl CoverageMap {
         println!("   {:.3}% of RVUs filtered by reader and non-radiology categorization.",(rvus_mutually_excluded/rvu_total*100.0));
         println!("   {:.3}% of RVUs filtered by reader only.",rvus_excluded_by_reader/rvu_total*100.0);
         println!("   {:.3}% of RVUs filtered by non-radiology categorization only.",rvus_filtered/rvu_total*100.0);
+        if missing_attribution_time>0
+        {
+            println!("   {} exams had no {:?} time and were attributed by list time.",missing_attribution_time,attribution_time);
+        }
         println!();
 
         Ok(())
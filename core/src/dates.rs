@@ -1,4 +1,5 @@
-use chrono::{Datelike, NaiveDate};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
 
 pub fn check_week_day(date: NaiveDate) -> bool {
     match date.weekday() {
@@ -99,3 +100,187 @@ pub fn check_holiday(date: NaiveDate) -> bool {
     }
     false
 }
+
+/// The first year the current US daylight saving rules were in effect. Earlier years followed other rules, so
+/// data from before it is rejected rather than converted or audited under the wrong dates.
+pub const FIRST_DST_RULES_YEAR: i32 = 2007;
+
+/// A US time zone under the daylight saving rules in effect since 2007: clocks go forward at 02:00 on the second
+/// Sunday of March and back at 02:00 on the first Sunday of November. Named as in the tz database, but only these
+/// seven zones are supported; any other name is rejected when the settings are read.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum TimeZone {
+    #[serde(rename = "America/New_York")]
+    Eastern,
+    #[serde(rename = "America/Chicago")]
+    Central,
+    #[serde(rename = "America/Denver")]
+    Mountain,
+    #[serde(rename = "America/Phoenix")]
+    Arizona,
+    #[serde(rename = "America/Los_Angeles")]
+    Pacific,
+    #[serde(rename = "America/Anchorage")]
+    Alaska,
+    #[serde(rename = "Pacific/Honolulu")]
+    Hawaii,
+}
+
+/// Which way the clocks change on a transition date.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize)]
+pub enum DSTTransition {
+    SpringForward,
+    FallBack,
+}
+
+const MINUTES_PER_HOUR: i64 = 60;
+
+fn transition_time() -> NaiveTime {
+    NaiveTime::from_hms_opt(2, 0, 0).expect("02:00 is valid")
+}
+
+impl TimeZone {
+    fn standard_offset_minutes(&self) -> i64 {
+        MINUTES_PER_HOUR
+            * match self {
+                TimeZone::Eastern => -5,
+                TimeZone::Central => -6,
+                TimeZone::Mountain | TimeZone::Arizona => -7,
+                TimeZone::Pacific => -8,
+                TimeZone::Alaska => -9,
+                TimeZone::Hawaii => -10,
+            }
+    }
+
+    fn observes_dst(&self) -> bool {
+        !matches!(self, TimeZone::Arizona | TimeZone::Hawaii)
+    }
+
+    /// The dates daylight saving time starts and ends in `year`, if the zone observes it. Only meaningful from
+    /// `FIRST_DST_RULES_YEAR` on.
+    pub fn dst_dates(&self, year: i32) -> Option<(NaiveDate, NaiveDate)> {
+        if !self.observes_dst() {
+            return None;
+        }
+        Some((
+            NaiveDate::from_weekday_of_month_opt(year, 3, chrono::Weekday::Sun, 2)?,
+            NaiveDate::from_weekday_of_month_opt(year, 11, chrono::Weekday::Sun, 1)?,
+        ))
+    }
+
+    pub fn transition_on(&self, date: NaiveDate) -> Option<DSTTransition> {
+        let (start, end) = self.dst_dates(date.year())?;
+        if date == start {
+            Some(DSTTransition::SpringForward)
+        } else if date == end {
+            Some(DSTTransition::FallBack)
+        } else {
+            None
+        }
+    }
+
+    /// Minutes from UTC at a local wall-clock time. The hour skipped in spring counts as daylight time,
+    /// and the hour repeated in autumn as its first occurrence, which is also daylight time.
+    pub fn utc_offset_minutes(&self, local: NaiveDateTime) -> i64 {
+        let standard = self.standard_offset_minutes();
+        match self.dst_dates(local.year()) {
+            Some((start, end))
+                if local >= start.and_time(transition_time())
+                    && local < end.and_time(transition_time()) =>
+            {
+                standard + MINUTES_PER_HOUR
+            }
+            _ => standard,
+        }
+    }
+
+    /// The local wall-clock time at a UTC time.
+    pub fn from_utc(&self, utc: NaiveDateTime) -> NaiveDateTime {
+        let standard = self.standard_offset_minutes();
+        let offset = match self.dst_dates(utc.year()) {
+            //Clocks change at 02:00 standard time in spring and 02:00 daylight time in autumn.
+            Some((start, end))
+                if utc >= start.and_time(transition_time()) - Duration::minutes(standard)
+                    && utc
+                        < end.and_time(transition_time())
+                            - Duration::minutes(standard + MINUTES_PER_HOUR) =>
+            {
+                standard + MINUTES_PER_HOUR
+            }
+            _ => standard,
+        };
+        utc + Duration::minutes(offset)
+    }
+
+    /// Minutes actually elapsed between two local wall-clock times, which differs from the wall-clock
+    /// difference by an hour when they span a transition.
+    pub fn elapsed_minutes(&self, from: NaiveDateTime, to: NaiveDateTime) -> i64 {
+        (to - from).num_minutes() - self.utc_offset_minutes(to) + self.utc_offset_minutes(from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_opt(hour, 0, 0))
+            .expect("Valid test time")
+    }
+
+    #[test]
+    fn us_transitions() {
+        assert_eq!(
+            TimeZone::Eastern.dst_dates(2024),
+            Some((
+                NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 11, 3).unwrap()
+            ))
+        );
+        assert_eq!(TimeZone::Arizona.dst_dates(2024), None);
+        assert_eq!(
+            TimeZone::Central.transition_on(NaiveDate::from_ymd_opt(2025, 3, 9).unwrap()),
+            Some(DSTTransition::SpringForward)
+        );
+        assert_eq!(
+            TimeZone::Central.transition_on(NaiveDate::from_ymd_opt(2025, 11, 2).unwrap()),
+            Some(DSTTransition::FallBack)
+        );
+    }
+
+    #[test]
+    fn overnights_across_transitions() {
+        let eastern = TimeZone::Eastern;
+        assert_eq!(
+            eastern.elapsed_minutes(at(2024, 3, 9, 17), at(2024, 3, 10, 8)),
+            14 * 60
+        );
+        assert_eq!(
+            eastern.elapsed_minutes(at(2024, 11, 2, 17), at(2024, 11, 3, 8)),
+            16 * 60
+        );
+        assert_eq!(
+            eastern.elapsed_minutes(at(2024, 7, 1, 17), at(2024, 7, 2, 8)),
+            15 * 60
+        );
+        assert_eq!(
+            TimeZone::Hawaii.elapsed_minutes(at(2024, 3, 9, 17), at(2024, 3, 10, 8)),
+            15 * 60
+        );
+    }
+
+    #[test]
+    fn utc_to_local() {
+        let eastern = TimeZone::Eastern;
+        assert_eq!(eastern.from_utc(at(2024, 1, 15, 12)), at(2024, 1, 15, 7));
+        assert_eq!(eastern.from_utc(at(2024, 7, 15, 12)), at(2024, 7, 15, 8));
+        //06:00 UTC is 01:00 EST, and an hour later the clocks have gone forward to 03:00 EDT.
+        assert_eq!(eastern.from_utc(at(2024, 3, 10, 6)), at(2024, 3, 10, 1));
+        assert_eq!(eastern.from_utc(at(2024, 3, 10, 7)), at(2024, 3, 10, 3));
+        //The hour from 01:00 repeats, first as EDT from 05:00 UTC and then as EST from 06:00 UTC.
+        assert_eq!(eastern.from_utc(at(2024, 11, 3, 5)), at(2024, 11, 3, 1));
+        assert_eq!(eastern.from_utc(at(2024, 11, 3, 6)), at(2024, 11, 3, 1));
+        assert_eq!(eastern.from_utc(at(2024, 11, 3, 7)), at(2024, 11, 3, 2));
+    }
+}
@@ -4,6 +4,8 @@ pub mod file_names {
     //pub(crate) const MAIN_DATA_FILE: &str = "./data/2024 to today v02_2.csv";
     pub(crate) const MAIN_DATA_FILE: &str = "./data/2024 to today v01.csv";
     pub(crate) const TEST_GROUP_FILE: &str = "./data/test_group.csv";
+    pub(crate) const SYNTHETIC_DATA_FILE: &str = "./data/synthetic.csv";
+    pub(crate) const GENERATOR_SETTINGS: &str = "./data/generator.yaml";
 
     //categorizations
     pub(crate) const BVU_DATA_FILE: &str = "./categories/BVU.csv";
@@ -11,6 +13,9 @@ pub mod file_names {
     pub(crate) const CATEGORIES_EXAM_FILE: &str = "./categories/Categories_Exam.csv";
     pub(crate) const EXAM_ALIAS_FILE: &str = "./categories/Exam_Aliases.csv";
     pub(crate) const READERS_FILE: &str = "./categories/Readers.csv";
+    pub(crate) const TIME_ZONE_SETTINGS: &str = "./categories/time_zone.yaml";
+    pub(crate) const ROSTER_FILE: &str = "./data/roster.csv";
+    pub(crate) const PROPOSED_ROSTER_FILE: &str = "./data/proposed_roster.csv";
     
     //errors
     pub(crate) const BVU_UPDATE_FILE: &str = "./err/Unaccounted BVU Codes.csv";
@@ -26,17 +31,54 @@ pub mod file_names {
     //audits
     pub(crate) const ACTIVE_COVERAGE_AUDIT_OUT: &str = "./err/Active_Coverage_Audit.tsv";
     pub(crate) const ACTIVE_COVERAGE_AUDIT_NOWORK_OUT: &str = "./err/Active_Coverage_Audit (no work).tsv";
+    pub(crate) const ACTIVE_COVERAGE_AUDIT_JSON_OUT: &str = "./err/Active_Coverage_Audit.json";
+    pub(crate) const ACTIVE_COVERAGE_AUDIT_RANKED_OUT: &str = "./err/Active_Coverage_Audit_Top_Issues";
+    pub(crate) const ACTIVE_WEEK_CONTINUITY_OUT: &str = "./err/Active_Week_Continuity_Audit";
+    pub(crate) const ACTIVE_DST_NIGHTS_OUT: &str = "./err/Active_DST_Nights";
 
     pub(crate) const PROPOSED_COVERAGE_AUDIT_OUT: &str = "./err/Proposed_Coverage_Audit.tsv";
     pub(crate) const PROPOSED_COVERAGE_AUDIT_NOWORK_OUT: &str = "./err/Proposed_Coverage_Audit (no work).tsv";
+    pub(crate) const PROPOSED_COVERAGE_AUDIT_JSON_OUT: &str = "./err/Proposed_Coverage_Audit.json";
+    pub(crate) const PROPOSED_COVERAGE_AUDIT_RANKED_OUT: &str = "./err/Proposed_Coverage_Audit_Top_Issues";
+    pub(crate) const PROPOSED_WEEK_CONTINUITY_OUT: &str = "./err/Proposed_Week_Continuity_Audit";
+    pub(crate) const PROPOSED_DST_NIGHTS_OUT: &str = "./err/Proposed_DST_Nights";
+    pub(crate) const UNSTAFFED_ROTATIONS_OUT: &str = "./err/Unstaffed_Rotations.tsv";
 
     //analysis
     pub(crate) const ACTIVE_COVERAGE_ANALYSIS_OUT: &str = "./output/Active_WeekAnalysis";
     pub(crate) const PROPOSED_COVERAGE_ANALYSIS_OUT: &str = "./output/Proposed_WeekAnalysis";
+    pub(crate) const ACTIVE_COVERAGE_GRID_OUT: &str = "./output/Active_CoverageGrid";
+    pub(crate) const PROPOSED_COVERAGE_GRID_OUT: &str = "./output/Proposed_CoverageGrid";
+    pub(crate) const ACTIVE_TURNAROUND_OUT: &str = "./output/Active_Turnaround";
+    pub(crate) const PROPOSED_TURNAROUND_OUT: &str = "./output/Proposed_Turnaround";
+    pub(crate) const ACTIVE_READERS_OUT: &str = "./output/Active_Readers";
+    pub(crate) const PROPOSED_READERS_OUT: &str = "./output/Proposed_Readers";
+    pub(crate) const ACTIVE_INFERRED_ROSTER_OUT: &str = "./output/Active_Inferred_Roster.csv";
+    pub(crate) const PROPOSED_INFERRED_ROSTER_OUT: &str = "./output/Proposed_Inferred_Roster.csv";
+    pub(crate) const ROSTER_WORKLOAD_OUT: &str = "./output/Roster_Workload.tsv";
+    pub(crate) const ROSTER_CUMULATIVE_OUT: &str = "./output/Roster_Cumulative_Workload.csv";
+    pub(crate) const ACTIVE_ROSTER_EQUITY_OUT: &str = "./output/Active_Roster_Equity.tsv";
+    pub(crate) const PROPOSED_ROSTER_EQUITY_OUT: &str = "./output/Proposed_Roster_Equity.tsv";
+    pub(crate) const ACTIVE_STAFFING_OUT: &str = "./output/Active_Staffing";
+    pub(crate) const PROPOSED_STAFFING_OUT: &str = "./output/Proposed_Staffing";
+    pub(crate) const OPTIMIZED_BOUNDARIES_OUT: &str = "./output/Optimized_Boundaries.tsv";
+    pub(crate) const OPTIMIZED_MANIFEST_OUT: &str = "./output/Optimized_Manifest.yaml";
+    pub(crate) const REASSIGNED_MANIFEST_OUT: &str = "./output/Reassigned_Manifest.yaml";
+    pub(crate) const REASSIGNED_RESPONSIBILITIES_OUT: &str = "./output/Reassigned_Responsibilities.csv";
+    pub(crate) const SCENARIO_VOLUMES_OUT: &str = "./output/Scenario_Volumes.tsv";
+    pub(crate) const ACTIVE_SCENARIO_COMPARISON_OUT: &str = "./output/Active_Scenario_Comparison.json";
+    pub(crate) const PROPOSED_SCENARIO_COMPARISON_OUT: &str = "./output/Proposed_Scenario_Comparison.json";
+    pub(crate) const ACTIVE_SCENARIO_VOLUME_BY_DATE_OUT: &str = "./output/Active_Scenario_Volume_By_Date.json";
+    pub(crate) const PROPOSED_SCENARIO_VOLUME_BY_DATE_OUT: &str = "./output/Proposed_Scenario_Volume_By_Date.json";
+    pub(crate) const ANALYSIS_WORKBOOK_OUT: &str = "./output/Rotation_Analysis.xlsx";
 
     //manifests
     pub(crate) const MANIFEST_ACTIVE:&str = "./rotations/active.yaml";
     pub(crate) const MANIFEST_PROPOSED:&str = "./rotations/proposed.yaml";
+    pub(crate) const STAFFING_MODEL:&str = "./rotations/staffing.yaml";
+    pub(crate) const OPTIMIZER_SETTINGS:&str = "./rotations/optimizer.yaml";
+    pub(crate) const REASSIGNMENT_CONSTRAINTS:&str = "./rotations/reassignment.yaml";
+    pub(crate) const GROWTH_SCENARIO:&str = "./rotations/scenario.yaml";
 
     //frontend
     pub(crate) const VOLUME_BY_DATE_FACILITY: &str = "volume_by_date_and_facility";
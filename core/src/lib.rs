@@ -10,38 +10,59 @@ use constraints::{is_not_holiday, ConstraintSet};
 
 use coverage::{
     analysis::{
+        audit_severity::{rank_audit, ranked_audit_to_tsv},
+        boundary_optimizer::optimize_boundaries,
+        responsibility_reassignment::reassign_responsibilities,
         by_day_of_week::{analysis_to_csv, analyze_by_day_of_week},
-        coverage_audit::{audit, audit_to_stream},
+        coverage_grid::CoverageGrid,
+        reader_workload::{analyze_readers, inferred_roster_to_csv, OUTSIDE_ROTATION_SUFFIX, ROTATIONS_SUFFIX, WORKLOAD_SUFFIX},
+        coverage_audit::{audit, audit_to_records, audit_to_stream},
+        dst_nights::{audit_dst_nights, dst_nights_to_tsv},
+        turnaround::{analyze_turnaround, BY_COORDINATE_SUFFIX as TURNAROUND_BY_COORDINATE_SUFFIX, BY_ROTATION_SUFFIX as TURNAROUND_BY_ROTATION_SUFFIX},
+        week_continuity::{audit_week_continuity, week_continuity_to_tsv},
+        workbook::AnalysisWorkbook,
     },
     work_coverage_map::maps::CoverageMap,
 };
 use globals::file_names::{
-    self, ACTIVE_COVERAGE_ANALYSIS_OUT, ACTIVE_COVERAGE_AUDIT_NOWORK_OUT,
-    ACTIVE_COVERAGE_AUDIT_OUT, MANIFEST_ACTIVE, MANIFEST_PROPOSED, TEST_GROUP_FILE,
+    self, ACTIVE_COVERAGE_ANALYSIS_OUT, ACTIVE_COVERAGE_AUDIT_JSON_OUT, ACTIVE_COVERAGE_AUDIT_NOWORK_OUT,
+    ACTIVE_COVERAGE_AUDIT_OUT, ACTIVE_COVERAGE_AUDIT_RANKED_OUT, ACTIVE_COVERAGE_GRID_OUT, ACTIVE_DST_NIGHTS_OUT, ACTIVE_INFERRED_ROSTER_OUT, ACTIVE_READERS_OUT, ACTIVE_TURNAROUND_OUT, ACTIVE_WEEK_CONTINUITY_OUT, MANIFEST_ACTIVE, MANIFEST_PROPOSED, TEST_GROUP_FILE,
     VOLUME_BY_DATE_ROTATION_PROPOSED,
 };
-use rotations::manifest::Manifest;
-use source_data::tables::{
+use rotations::{
+    baseline::RotationBaseline,
+    canonical::{format_manifest_file, to_flattened_yaml, FormatResult},
+    manifest::Manifest,
+    description::WrappedSortable,
+    optimizer::{OptimizerSettings, ReassignmentSettings},
+    staffing::StaffingModel,
+};
+use source_data::{synthetic::{generate_exams, GeneratorSettings}, tables::{
     exam_data::{Exam, ExamTable},
+    roster::{RosterEntry, RosterTable},
     table::Table,
-};
+}};
 
 use crate::{
+    analysis::scenario::GrowthScenario,
     coverage::analysis::{
-        comparison::compare,
+        comparison::{compare, get_by_rotation_average},
+        roster_workload::{attribute_volumes_to_roster, equity_report_to_tsv, equity_variation},
         rotation_day_details::details,
+        scenario_comparison::{scenario_comparisons_to_tsv, ScenarioComparison},
+        staffing_projection::{project_staffing, PEOPLE_SUFFIX, ROTATIONS_SUFFIX as STAFFING_ROTATIONS_SUFFIX},
         volumes_by_rotation_date::{analysis_to_plot, sort_volumes_by_rotation_date},
         volumes_by_site_date::{
             sort_volumes_by_facility_and_date, volumes_by_facility_and_date_to_plot,
         },
     },
     globals::file_names::{
-        PROPOSED_COVERAGE_ANALYSIS_OUT, PROPOSED_COVERAGE_AUDIT_NOWORK_OUT,
-        PROPOSED_COVERAGE_AUDIT_OUT, PROPOSED_DIFFERENTIAL, VOLUME_BY_DATE_FACILITY,
+        ANALYSIS_WORKBOOK_OUT, PROPOSED_COVERAGE_ANALYSIS_OUT, PROPOSED_COVERAGE_AUDIT_JSON_OUT, PROPOSED_COVERAGE_AUDIT_RANKED_OUT, PROPOSED_COVERAGE_AUDIT_NOWORK_OUT,
+        PROPOSED_COVERAGE_AUDIT_OUT, ACTIVE_ROSTER_EQUITY_OUT, ACTIVE_STAFFING_OUT, OPTIMIZED_BOUNDARIES_OUT, OPTIMIZED_MANIFEST_OUT, OPTIMIZER_SETTINGS, REASSIGNED_MANIFEST_OUT, REASSIGNED_RESPONSIBILITIES_OUT, REASSIGNMENT_CONSTRAINTS, GROWTH_SCENARIO, SCENARIO_VOLUMES_OUT, ACTIVE_SCENARIO_COMPARISON_OUT, PROPOSED_SCENARIO_COMPARISON_OUT, ACTIVE_SCENARIO_VOLUME_BY_DATE_OUT, PROPOSED_SCENARIO_VOLUME_BY_DATE_OUT, PROPOSED_ROSTER_EQUITY_OUT, PROPOSED_STAFFING_OUT, STAFFING_MODEL, PROPOSED_ROSTER_FILE, ROSTER_CUMULATIVE_OUT, ROSTER_FILE, ROSTER_WORKLOAD_OUT, UNSTAFFED_ROTATIONS_OUT, PROPOSED_COVERAGE_GRID_OUT, PROPOSED_DIFFERENTIAL, PROPOSED_DST_NIGHTS_OUT, PROPOSED_INFERRED_ROSTER_OUT, PROPOSED_READERS_OUT, PROPOSED_TURNAROUND_OUT, PROPOSED_WEEK_CONTINUITY_OUT, VOLUME_BY_DATE_FACILITY,
         VOLUME_BY_DATE_ROTATION_ACTIVE,
     },
     serialization::output::JSONFileOut,
-    source_data::processing::processed_source::ProcessedSource,
+    source_data::processing::processed_source::{ProcessedSource, SourcePaths},
 };
 
 mod analysis;
@@ -50,13 +71,32 @@ mod coverage;
 mod dates;
 mod error;
 mod globals;
+mod random;
 mod rotations;
 mod serialization;
 mod source_data;
 
+/// The stages of the analysis pipeline, for tests and tools that drive it without writing the usual outputs.
+pub mod pipeline {
+    pub use crate::analysis::scenario::GrowthScenario;
+    pub use crate::build_coverage_tree_from_manifest;
+    pub use crate::coverage::analysis::comparison::{compare, get_by_rotation_average};
+    pub use crate::coverage::analysis::coverage_audit::{audit, audit_to_records};
+    pub use crate::coverage::analysis::dst_nights::audit_dst_nights;
+    pub use crate::dates::TimeZone;
+    pub use crate::coverage::analysis::week_continuity::audit_week_continuity;
+    pub use crate::coverage::work_coverage_map::maps::CoverageMap;
+    pub use crate::rotations::manifest::Manifest;
+    pub use crate::source_data::processing::processed_source::{ProcessedSource, SourcePaths};
+}
+
 pub struct MainCommon {
     pub coverage_tree: CoverageMap,
     pub source: ProcessedSource,
+    /// The active manifest `coverage_tree` was built from.
+    pub manifest: Manifest,
+    /// The proposed manifest and its coverage tree, if there's a proposal and it parses.
+    pub proposed: Option<(Manifest, CoverageMap)>,
 }
 
 pub enum ManifestType {
@@ -81,6 +121,67 @@ impl ManifestType {
     }
 }
 
+pub fn format_manifests(filenames: &[String], check: bool) -> Result<(), Box<dyn Error>> {
+    let mut filenames: Vec<String> = filenames.to_vec();
+    if filenames.is_empty() {
+        for manifest_type in [ManifestType::Active, ManifestType::Proposed] {
+            if manifest_type.exists() {
+                filenames.push(manifest_type.filename().to_string());
+            }
+        }
+    }
+
+    let mut not_canonical: Vec<String> = Vec::new();
+    for filename in &filenames {
+        match format_manifest_file(filename, check)? {
+            FormatResult::Unchanged => println!("{} is canonical.", filename),
+            FormatResult::Reformatted => println!("Reformatted {}.", filename),
+            FormatResult::NotCanonical => {
+                println!("{} is not canonical.", filename);
+                not_canonical.push(filename.to_string());
+            }
+        }
+    }
+
+    if not_canonical.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Manifests not in canonical form: {}", not_canonical.join(", ")).into())
+    }
+}
+
+/// Writes synthetic exam data consistent with the categories files, for demos and tests without the real data.
+/// Settings come from `settings_filename`, or the default generator settings file if it exists.
+pub fn generate_synthetic_data(
+    output_filename: Option<&str>,
+    settings_filename: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let settings = match settings_filename {
+        Some(filename) => GeneratorSettings::read(filename)?,
+        None => {
+            if std::path::Path::new(file_names::GENERATOR_SETTINGS).exists() {
+                GeneratorSettings::read(file_names::GENERATOR_SETTINGS)?
+            } else {
+                GeneratorSettings::default()
+            }
+        }
+    };
+    let output_filename = output_filename.unwrap_or(file_names::SYNTHETIC_DATA_FILE);
+    if output_filename == file_names::MAIN_DATA_FILE {
+        return Err("Synthetic data would overwrite the main data file.".into());
+    }
+
+    let exams = generate_exams(&settings, &SourcePaths::default())?;
+    ExamTable::write_exams(output_filename, &exams)?;
+    println!(
+        "Wrote {} synthetic exams over {} days to {}.",
+        exams.len(),
+        settings.days(),
+        output_filename
+    );
+    Ok(())
+}
+
 pub fn source_check() -> Result<(), Box<dyn Error>> {
     let main_data_1: Vec<Exam> = ExamTable::create(file_names::MAIN_DATA_FILE)
         .iter()
@@ -94,14 +195,47 @@ pub fn source_check() -> Result<(), Box<dyn Error>> {
 }
 
 pub fn build_main_common() -> Result<MainCommon, Box<dyn Error>> {
-    let source = ProcessedSource::build()?;
+    let proposed_manifest = if ManifestType::Proposed.exists() {
+        match ManifestType::Proposed.get() {
+            Ok(proposed_manifest) => Some(proposed_manifest),
+            Err(e) => {
+                println!("Proposed manifest error, skipping the proposal: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    build_main_common_from(
+        &SourcePaths::default(),
+        ManifestType::Active.get()?,
+        proposed_manifest,
+    )
+}
 
-    let manifest = ManifestType::Active.get()?;
-    let coverage_tree = build_coverage_tree_from_manifest(manifest, &source)?;
+/// Like build_main_common, but with source data from `paths`, coverage from `manifest`, and the proposal, if any,
+/// from `proposed_manifest`.
+pub fn build_main_common_from(
+    paths: &SourcePaths,
+    manifest: Manifest,
+    proposed_manifest: Option<Manifest>,
+) -> Result<MainCommon, Box<dyn Error>> {
+    let source = ProcessedSource::build(paths)?;
+    let coverage_tree = build_coverage_tree_from_manifest(&manifest, &source)?;
+    let proposed = match proposed_manifest {
+        Some(proposed_manifest) => {
+            let proposed_coverage_tree =
+                build_coverage_tree_from_manifest(&proposed_manifest, &source)?;
+            Some((proposed_manifest, proposed_coverage_tree))
+        }
+        None => None,
+    };
 
     Ok(MainCommon {
         coverage_tree,
         source,
+        manifest,
+        proposed,
     })
 }
 
@@ -148,8 +282,8 @@ pub fn print_averages_by_modality_and_day(
     println!();
 }
 
-fn build_coverage_tree_from_manifest(
-    manifest: Manifest,
+pub fn build_coverage_tree_from_manifest(
+    manifest: &Manifest,
     source: &ProcessedSource,
 ) -> Result<CoverageMap, Box<dyn Error>> {
     println!("Building coverage tree.");
@@ -161,50 +295,208 @@ fn build_coverage_tree_from_manifest(
     println!("Adding coverage.");
     coverage_tree.add_coverage_from_manifest(manifest)?;
 
-    println!("Adding work to tree.");
-    coverage_tree.add_work_from_source(&source, &date_constraint_set)?;
+    //Overnight rotations are responsible for the exams they finalize, so a manifest may attribute by a later timestamp than the list time.
+    let attribution_time = manifest.attribution_time.unwrap_or_default();
+    println!("Adding work to tree by {:?} time.", attribution_time);
+    coverage_tree.add_work_from_source(&source, &date_constraint_set, attribution_time)?;
 
     Ok(coverage_tree)
 }
 
 const RVU_SUFFIX: &str = "_rvu.csv";
 const BVU_SUFFIX: &str = "_bvu.csv";
+const CSV_SUFFIX: &str = ".csv";
+const HTML_SUFFIX: &str = ".html";
+const TSV_SUFFIX: &str = ".tsv";
+const JSON_SUFFIX: &str = ".json";
+
+const TOP_ISSUES_PRINTED: usize = 5;
+
+const COVERAGE_GRID_STEP_MINUTES: u64 = 60;
+
+struct CoverageTreeOutputs {
+    label: &'static str,
+    coverage_audit_out: &'static str,
+    coverage_audit_nowork_out: &'static str,
+    coverage_audit_json_out: &'static str,
+    coverage_audit_ranked_out: &'static str,
+    week_continuity_out: &'static str,
+    dst_nights_out: &'static str,
+    coverage_analysis_out: &'static str,
+    coverage_grid_out: &'static str,
+    turnaround_out: &'static str,
+    readers_out: &'static str,
+    inferred_roster_out: &'static str,
+    roster_file: &'static str,
+}
+
+const ACTIVE_OUTPUTS: CoverageTreeOutputs = CoverageTreeOutputs {
+    label: "Active",
+    coverage_audit_out: ACTIVE_COVERAGE_AUDIT_OUT,
+    coverage_audit_nowork_out: ACTIVE_COVERAGE_AUDIT_NOWORK_OUT,
+    coverage_audit_json_out: ACTIVE_COVERAGE_AUDIT_JSON_OUT,
+    coverage_audit_ranked_out: ACTIVE_COVERAGE_AUDIT_RANKED_OUT,
+    week_continuity_out: ACTIVE_WEEK_CONTINUITY_OUT,
+    dst_nights_out: ACTIVE_DST_NIGHTS_OUT,
+    coverage_analysis_out: ACTIVE_COVERAGE_ANALYSIS_OUT,
+    coverage_grid_out: ACTIVE_COVERAGE_GRID_OUT,
+    turnaround_out: ACTIVE_TURNAROUND_OUT,
+    readers_out: ACTIVE_READERS_OUT,
+    inferred_roster_out: ACTIVE_INFERRED_ROSTER_OUT,
+    roster_file: ROSTER_FILE,
+};
+
+const PROPOSED_OUTPUTS: CoverageTreeOutputs = CoverageTreeOutputs {
+    label: "Proposed",
+    coverage_audit_out: PROPOSED_COVERAGE_AUDIT_OUT,
+    coverage_audit_nowork_out: PROPOSED_COVERAGE_AUDIT_NOWORK_OUT,
+    coverage_audit_json_out: PROPOSED_COVERAGE_AUDIT_JSON_OUT,
+    coverage_audit_ranked_out: PROPOSED_COVERAGE_AUDIT_RANKED_OUT,
+    week_continuity_out: PROPOSED_WEEK_CONTINUITY_OUT,
+    dst_nights_out: PROPOSED_DST_NIGHTS_OUT,
+    coverage_analysis_out: PROPOSED_COVERAGE_ANALYSIS_OUT,
+    coverage_grid_out: PROPOSED_COVERAGE_GRID_OUT,
+    turnaround_out: PROPOSED_TURNAROUND_OUT,
+    readers_out: PROPOSED_READERS_OUT,
+    inferred_roster_out: PROPOSED_INFERRED_ROSTER_OUT,
+    roster_file: PROPOSED_ROSTER_FILE,
+};
 
 impl MainCommon {
-    fn clear_coveragetree_analyses(
-        coverage_audit_out: &str,
-        coverage_audit_nowork_out: &str,
-        coverage_analysis_out: &str,
-    ) -> () {
-        let _ = std::fs::remove_file(coverage_audit_out);
-        let _ = std::fs::remove_file(coverage_audit_nowork_out);
-        let _ = std::fs::remove_file(coverage_analysis_out.to_string() + RVU_SUFFIX);
-        let _ = std::fs::remove_file(coverage_analysis_out.to_string() + BVU_SUFFIX);
+    fn clear_coveragetree_analyses(outputs: &CoverageTreeOutputs) -> () {
+        let _ = std::fs::remove_file(outputs.coverage_audit_out);
+        let _ = std::fs::remove_file(outputs.coverage_audit_nowork_out);
+        let _ = std::fs::remove_file(outputs.coverage_audit_json_out);
+        let _ = std::fs::remove_file(outputs.coverage_audit_ranked_out.to_string() + TSV_SUFFIX);
+        let _ = std::fs::remove_file(outputs.coverage_audit_ranked_out.to_string() + JSON_SUFFIX);
+        let _ = std::fs::remove_file(outputs.week_continuity_out.to_string() + TSV_SUFFIX);
+        let _ = std::fs::remove_file(outputs.week_continuity_out.to_string() + JSON_SUFFIX);
+        let _ = std::fs::remove_file(outputs.dst_nights_out.to_string() + TSV_SUFFIX);
+        let _ = std::fs::remove_file(outputs.dst_nights_out.to_string() + JSON_SUFFIX);
+        let _ = std::fs::remove_file(outputs.coverage_analysis_out.to_string() + RVU_SUFFIX);
+        let _ = std::fs::remove_file(outputs.coverage_analysis_out.to_string() + BVU_SUFFIX);
+        let _ = std::fs::remove_file(outputs.coverage_grid_out.to_string() + CSV_SUFFIX);
+        let _ = std::fs::remove_file(outputs.coverage_grid_out.to_string() + HTML_SUFFIX);
+        let _ = std::fs::remove_file(outputs.turnaround_out.to_string() + TURNAROUND_BY_ROTATION_SUFFIX);
+        let _ = std::fs::remove_file(outputs.turnaround_out.to_string() + TURNAROUND_BY_COORDINATE_SUFFIX);
+        let _ = std::fs::remove_file(outputs.readers_out.to_string() + WORKLOAD_SUFFIX);
+        let _ = std::fs::remove_file(outputs.readers_out.to_string() + ROTATIONS_SUFFIX);
+        let _ = std::fs::remove_file(outputs.readers_out.to_string() + OUTSIDE_ROTATION_SUFFIX);
+        let _ = std::fs::remove_file(outputs.inferred_roster_out);
     }
     fn analyze_coveragetree(
         coverage_tree: &mut CoverageMap,
-        coverage_audit_out: &str,
-        coverage_audit_nowork_out: &str,
-        coverage_analysis_out: &str,
+        manifest: &Manifest,
+        source: &ProcessedSource,
+        outputs: &CoverageTreeOutputs,
     ) -> Result<(), Box<dyn Error>> {
-        let auditfile: File = File::create(coverage_audit_out)?;
+        //The grid is written before the audit so it's available when the audit finds errors.
+        let grid = CoverageGrid::build(coverage_tree, COVERAGE_GRID_STEP_MINUTES);
+        grid.to_csv(&(outputs.coverage_grid_out.to_string() + CSV_SUFFIX))?;
+        grid.to_html(
+            &(outputs.coverage_grid_out.to_string() + HTML_SUFFIX),
+            &format!("{} Coverage", outputs.label),
+        )?;
+
+        let auditfile: File = File::create(outputs.coverage_audit_out)?;
         let mut writer = BufWriter::new(auditfile);
 
-        let auditfile_nowork: File = File::create(coverage_audit_nowork_out)?;
+        let auditfile_nowork: File = File::create(outputs.coverage_audit_nowork_out)?;
         let mut writer_nowork = BufWriter::new(auditfile_nowork);
 
         let audit_result = audit(coverage_tree);
+        audit_to_records(&audit_result).to_json(outputs.coverage_audit_json_out)?;
+
+        let ranked_issues = rank_audit(coverage_tree, &audit_result);
+        ranked_audit_to_tsv(
+            &ranked_issues,
+            &(outputs.coverage_audit_ranked_out.to_string() + TSV_SUFFIX),
+        )?;
+        ranked_issues.to_json(&(outputs.coverage_audit_ranked_out.to_string() + JSON_SUFFIX))?;
+        if !ranked_issues.is_empty() {
+            println!("Top {} coverage issues:", outputs.label.to_lowercase());
+            for issue in ranked_issues.iter().take(TOP_ISSUES_PRINTED) {
+                println!(
+                    "{} {} {} {} {:?} {}-{}: {:.2} rvus on {} dates",
+                    issue.coordinates.facility,
+                    issue.coordinates.subspecialty,
+                    issue.coordinates.context,
+                    issue.coordinates.weekday,
+                    issue.kind,
+                    issue.start,
+                    issue.end,
+                    issue.rvus,
+                    issue.affected_dates
+                );
+            }
+        }
+
+        //The per-day audit only sees weekdays with coverage or work, so check continuity around the whole week too.
+        let continuity_gaps = audit_week_continuity(coverage_tree);
+        week_continuity_to_tsv(
+            &continuity_gaps,
+            &(outputs.week_continuity_out.to_string() + TSV_SUFFIX),
+        )?;
+        continuity_gaps.to_json(&(outputs.week_continuity_out.to_string() + JSON_SUFFIX))?;
+        let unaudited = continuity_gaps
+            .iter()
+            .filter(|gap| !gap.unaudited_days.is_empty())
+            .count();
+        if unaudited > 0 {
+            println!(
+                "{} {} week continuity gaps fall on days the coverage audit has no record of.",
+                unaudited,
+                outputs.label.to_lowercase()
+            );
+        }
+
+        //Rotations through a daylight saving transition are an hour short or long, so their volume that night is unusual.
+        if let Some(settings) = &source.time_zone {
+            let dst_nights = audit_dst_nights(manifest, coverage_tree, &settings.time_zone);
+            dst_nights_to_tsv(&dst_nights, &(outputs.dst_nights_out.to_string() + TSV_SUFFIX))?;
+            dst_nights.to_json(&(outputs.dst_nights_out.to_string() + JSON_SUFFIX))?;
+            if !dst_nights.is_empty() {
+                println!(
+                    "{} {} rotation dates run through a daylight saving transition.",
+                    dst_nights.len(),
+                    outputs.label.to_lowercase()
+                );
+            }
+        }
+
+        analyze_turnaround(coverage_tree).to_tsv(outputs.turnaround_out)?;
+
+        //Exams are checked against the roster when there is one, otherwise against whoever read the most.
+        let roster: Option<Vec<RosterEntry>> = if std::path::Path::new(outputs.roster_file).exists() {
+            Some(RosterTable::create(outputs.roster_file).iter().collect())
+        } else {
+            None
+        };
+        let reader_analysis = analyze_readers(coverage_tree);
+        let responsible = reader_analysis.responsible_readers(roster.as_deref(), &source.readers);
+        reader_analysis.to_tsv(outputs.readers_out, &source.readers, &responsible)?;
+        println!(
+            "{:.1}% of {} rotation RVUs were read by someone other than the {} responsible reader.",
+            reader_analysis.outside_rotation_fraction(&responsible, &source.readers) * 100.0,
+            outputs.label.to_lowercase(),
+            responsible.label().to_lowercase()
+        );
+        inferred_roster_to_csv(
+            &reader_analysis.infer_roster(&source.readers),
+            outputs.inferred_roster_out,
+        )?;
+
         audit_to_stream(&audit_result, &mut writer, &mut writer_nowork)?;
 
         let analysis = analyze_by_day_of_week(coverage_tree);
         analysis_to_csv(
             &analysis,
-            coverage_analysis_out.to_owned() + "_rvu.csv",
+            outputs.coverage_analysis_out.to_owned() + RVU_SUFFIX,
             true,
         );
         analysis_to_csv(
             &analysis,
-            coverage_analysis_out.to_owned() + "_bvu.csv",
+            outputs.coverage_analysis_out.to_owned() + BVU_SUFFIX,
             false,
         );
 
@@ -212,47 +504,393 @@ impl MainCommon {
     }
 
     pub fn analyze_rotations(&mut self) -> Result<(), Box<dyn Error>> {
-        Self::clear_coveragetree_analyses(
-            ACTIVE_COVERAGE_AUDIT_OUT,
-            ACTIVE_COVERAGE_AUDIT_NOWORK_OUT,
-            ACTIVE_COVERAGE_ANALYSIS_OUT,
-        );
+        Self::clear_coveragetree_analyses(&ACTIVE_OUTPUTS);
 
         Self::analyze_coveragetree(
             &mut self.coverage_tree,
-            ACTIVE_COVERAGE_AUDIT_OUT,
-            ACTIVE_COVERAGE_AUDIT_NOWORK_OUT,
-            ACTIVE_COVERAGE_ANALYSIS_OUT,
+            &self.manifest,
+            &self.source,
+            &ACTIVE_OUTPUTS,
         )?;
 
-        Self::clear_coveragetree_analyses(
-            PROPOSED_COVERAGE_AUDIT_OUT,
-            PROPOSED_COVERAGE_AUDIT_NOWORK_OUT,
-            PROPOSED_COVERAGE_ANALYSIS_OUT,
+        Self::clear_coveragetree_analyses(&PROPOSED_OUTPUTS);
+
+        if let Some((proposed_manifest, proposed_coverage_tree)) = &mut self.proposed {
+            println!();
+            println!("Analyzing proposed manifest.");
+            Self::analyze_coveragetree(
+                proposed_coverage_tree,
+                proposed_manifest,
+                &self.source,
+                &PROPOSED_OUTPUTS,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn add_coveragetree_to_workbook(
+        workbook: &mut AnalysisWorkbook,
+        coverage_tree: &mut CoverageMap,
+        baselines: &Option<Vec<RotationBaseline>>,
+        outputs: &CoverageTreeOutputs,
+    ) -> Result<(), Box<dyn Error>> {
+        let audit_result = audit(coverage_tree);
+        workbook.add_audit(outputs.label, &audit_result)?;
+
+        let analysis = analyze_by_day_of_week(coverage_tree);
+        workbook.add_day_of_week_analysis(outputs.label, &analysis)?;
+
+        if let Some(baselines) = baselines {
+            workbook.add_baseline_deviations(
+                outputs.label,
+                baselines,
+                &get_by_rotation_average(coverage_tree),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the audits, analyses, comparison, and baseline deviations of the active and proposed manifests to a single workbook.
+    pub fn write_workbook(&mut self) -> Result<(), Box<dyn Error>> {
+        let _ = std::fs::remove_file(ANALYSIS_WORKBOOK_OUT);
+
+        let mut workbook = AnalysisWorkbook::new();
+
+        Self::add_coveragetree_to_workbook(
+            &mut workbook,
+            &mut self.coverage_tree,
+            &self.manifest.baselines,
+            &ACTIVE_OUTPUTS,
+        )?;
+
+        if let Some((proposed_manifest, proposed_coverage_tree)) = &mut self.proposed {
+            Self::add_coveragetree_to_workbook(
+                &mut workbook,
+                proposed_coverage_tree,
+                &proposed_manifest.baselines,
+                &PROPOSED_OUTPUTS,
+            )?;
+
+            let comparison = compare(&self.coverage_tree, proposed_coverage_tree);
+            workbook.add_comparison(&comparison)?;
+        }
+
+        workbook.save(ANALYSIS_WORKBOOK_OUT)
+    }
+
+    /// Attributes the active manifest's rotation volumes to the people rostered to them, reports rotations nobody was rostered to,
+    /// and compares workload across radiologists for the active manifest and, given a roster for it, the proposed manifest.
+    pub fn analyze_roster(
+        &self,
+        rotation_start: &NaiveDate,
+        rotation_end: &NaiveDate,
+    ) -> Result<(), Box<dyn Error>> {
+        let _ = std::fs::remove_file(ROSTER_WORKLOAD_OUT);
+        let _ = std::fs::remove_file(ROSTER_CUMULATIVE_OUT);
+        let _ = std::fs::remove_file(UNSTAFFED_ROTATIONS_OUT);
+        let _ = std::fs::remove_file(ACTIVE_ROSTER_EQUITY_OUT);
+        let _ = std::fs::remove_file(PROPOSED_ROSTER_EQUITY_OUT);
+
+        if !std::path::Path::new(ROSTER_FILE).exists() {
+            println!("No roster at {}, skipping roster analysis.", ROSTER_FILE);
+            return Ok(());
+        }
+
+        let roster: Vec<RosterEntry> = RosterTable::create(ROSTER_FILE).iter().collect();
+        let volumes = sort_volumes_by_rotation_date(&self.coverage_tree);
+        let attribution =
+            attribute_volumes_to_roster(&volumes, &roster, rotation_start, rotation_end);
+
+        for rotation in attribution.get_unknown_rotations() {
+            println!("Roster rotation {} isn't in the active manifest.", rotation);
+        }
+        println!(
+            "{} rotation dates had volume but nobody rostered.",
+            attribution.get_unstaffed().len()
         );
 
-        if ManifestType::Proposed.exists() {
-            match ManifestType::Proposed.get() {
-                Ok(proposed_manifest) => {
-                    println!();
-                    println!("Analyzing proposed manifest.");
-                    let mut proposed_coverage_tree =
-                        build_coverage_tree_from_manifest(proposed_manifest, &self.source)?;
-
-                    Self::analyze_coveragetree(
-                        &mut proposed_coverage_tree,
-                        PROPOSED_COVERAGE_AUDIT_OUT,
-                        PROPOSED_COVERAGE_AUDIT_NOWORK_OUT,
-                        PROPOSED_COVERAGE_ANALYSIS_OUT,
-                    )?;
+        attribution.workload_to_tsv(ROSTER_WORKLOAD_OUT)?;
+        attribution.cumulative_to_csv(ROSTER_CUMULATIVE_OUT)?;
+        attribution.unstaffed_to_tsv(UNSTAFFED_ROTATIONS_OUT)?;
+
+        let equity = attribution.equity_report();
+        equity_report_to_tsv(&equity, ACTIVE_ROSTER_EQUITY_OUT)?;
+        println!(
+            "Active RVUs per day vary by {:.1}% across radiologists.",
+            equity_variation(&equity) * 100.0
+        );
+
+        //A proposed manifest is judged against a roster written for its rotations.
+        if std::path::Path::new(PROPOSED_ROSTER_FILE).exists() {
+            if let Some((_, proposed_coverage_tree)) = &self.proposed {
+                let proposed_roster: Vec<RosterEntry> =
+                    RosterTable::create(PROPOSED_ROSTER_FILE).iter().collect();
+                let proposed_attribution = attribute_volumes_to_roster(
+                    &sort_volumes_by_rotation_date(proposed_coverage_tree),
+                    &proposed_roster,
+                    rotation_start,
+                    rotation_end,
+                );
+                for rotation in proposed_attribution.get_unknown_rotations() {
+                    println!("Proposed roster rotation {} isn't in the proposed manifest.", rotation);
                 }
-                Err(_) => (), //nothing
+
+                let proposed_equity = proposed_attribution.equity_report();
+                equity_report_to_tsv(&proposed_equity, PROPOSED_ROSTER_EQUITY_OUT)?;
+                println!(
+                    "Proposed RVUs per day vary by {:.1}% across radiologists.",
+                    equity_variation(&proposed_equity) * 100.0
+                );
             }
         }
 
         Ok(())
     }
 
+    fn write_staffing_projection(
+        coverage_tree: &CoverageMap,
+        model: &StaffingModel,
+        rotation_start: &NaiveDate,
+        rotation_end: &NaiveDate,
+        label: &str,
+        filename_base: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let projection = project_staffing(
+            &sort_volumes_by_rotation_date(coverage_tree),
+            model,
+            rotation_start,
+            rotation_end,
+        );
+        projection.to_tsv(filename_base)?;
+
+        let per_fte = projection.per_fte();
+        println!(
+            "{} manifest needs {:.0} of {:.0} available shifts per year, {:.2} FTE against {:.2} available ({:.0} RVU and {:.0} BVU per FTE).",
+            label,
+            projection.shifts_needed,
+            projection.shifts_available,
+            projection.fte_needed,
+            projection.fte_available,
+            per_fte.rvu,
+            per_fte.bvu
+        );
+        Ok(())
+    }
+
+    /// Projects annual volume per FTE and the FTE needed to staff the active and proposed manifests.
+    pub fn analyze_staffing(
+        &self,
+        rotation_start: &NaiveDate,
+        rotation_end: &NaiveDate,
+    ) -> Result<(), Box<dyn Error>> {
+        for filename_base in [ACTIVE_STAFFING_OUT, PROPOSED_STAFFING_OUT] {
+            let _ = std::fs::remove_file(filename_base.to_string() + STAFFING_ROTATIONS_SUFFIX);
+            let _ = std::fs::remove_file(filename_base.to_string() + PEOPLE_SUFFIX);
+        }
+
+        if !std::path::Path::new(STAFFING_MODEL).exists() {
+            println!("No staffing model at {}, skipping staffing projection.", STAFFING_MODEL);
+            return Ok(());
+        }
+        let model = StaffingModel::read(STAFFING_MODEL)?;
+
+        Self::write_staffing_projection(
+            &self.coverage_tree,
+            &model,
+            rotation_start,
+            rotation_end,
+            "Active",
+            ACTIVE_STAFFING_OUT,
+        )?;
+
+        if let Some((_, proposed_coverage_tree)) = &self.proposed {
+            Self::write_staffing_projection(
+                proposed_coverage_tree,
+                &model,
+                rotation_start,
+                rotation_end,
+                "Proposed",
+                PROPOSED_STAFFING_OUT,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves rotation handoffs in the proposed manifest, or the active one if there is no proposal, to balance volume
+    /// or meet baselines. The result is written as a manifest for review rather than replacing either manifest.
+    pub fn optimize_boundaries(&self) -> Result<(), Box<dyn Error>> {
+        let settings = if std::path::Path::new(OPTIMIZER_SETTINGS).exists() {
+            OptimizerSettings::read(OPTIMIZER_SETTINGS)?
+        } else {
+            println!("No optimizer settings at {}, using defaults.", OPTIMIZER_SETTINGS);
+            OptimizerSettings::default()
+        };
+
+        let manifest_type = if ManifestType::Proposed.exists() {
+            println!("Optimizing boundaries of the proposed manifest.");
+            ManifestType::Proposed
+        } else {
+            println!("Optimizing boundaries of the active manifest.");
+            ManifestType::Active
+        };
+        let mut manifest = manifest_type.get()?;
+
+        let result = optimize_boundaries(&self.coverage_tree, &mut manifest, &settings)?;
+        println!(
+            "Found {} movable boundaries. Objective went from {:.3} to {:.3} in {} passes.",
+            result.boundaries.len(),
+            result.initial_score,
+            result.final_score,
+            result.passes
+        );
+        for boundary in result.moved() {
+            println!(
+                "{} to {}: {} -> {}",
+                boundary.from_rotation,
+                boundary.to_rotation,
+                boundary.original.to_string(),
+                boundary.time.to_string()
+            );
+        }
+        result.to_tsv(OPTIMIZED_BOUNDARIES_OUT)?;
+
+        //Moved spans may come from templates or includes, so the whole expanded manifest is written rather than edits to the original.
+        manifest.title = manifest.title.to_string() + " (optimized boundaries)";
+        std::fs::write(
+            OPTIMIZED_MANIFEST_OUT,
+            to_flattened_yaml(&manifest, manifest_type.filename(), OPTIMIZED_BOUNDARIES_OUT)?,
+        )?;
+        println!(
+            "Wrote {} with includes and templates flattened; carry the moves in {} back to {} by hand.",
+            OPTIMIZED_MANIFEST_OUT,
+            OPTIMIZED_BOUNDARIES_OUT,
+            manifest_type.filename()
+        );
+
+        Ok(())
+    }
+
+    /// Trades whole responsibilities among the rotations named in the reassignment constraints to balance volume
+    /// or meet baselines. Writes the resulting manifest and a list of the responsibilities that moved.
+    pub fn reassign_responsibilities(
+        &self,
+        rotation_start: &NaiveDate,
+        rotation_end: &NaiveDate,
+    ) -> Result<(), Box<dyn Error>> {
+        if !std::path::Path::new(REASSIGNMENT_CONSTRAINTS).exists() {
+            println!(
+                "No reassignment constraints at {}, skipping responsibility reassignment.",
+                REASSIGNMENT_CONSTRAINTS
+            );
+            return Ok(());
+        }
+        let settings = ReassignmentSettings::read(REASSIGNMENT_CONSTRAINTS)?;
+
+        let manifest_type = if ManifestType::Proposed.exists() {
+            println!("Reassigning responsibilities of the proposed manifest.");
+            ManifestType::Proposed
+        } else {
+            println!("Reassigning responsibilities of the active manifest.");
+            ManifestType::Active
+        };
+        let mut manifest = manifest_type.get()?;
+
+        let mut mutable_temporary_coverage_tree = self.coverage_tree.clone();
+        mutable_temporary_coverage_tree.populate_responsibility_volumes(
+            &mut manifest,
+            rotation_start,
+            rotation_end,
+        )?;
+
+        let result = reassign_responsibilities(&mut manifest, &settings)?;
+        println!(
+            "Moved {} responsibilities. Objective went from {:.3} to {:.3}.",
+            result.moved.len(),
+            result.initial_score,
+            result.final_score
+        );
+        result.to_csv(REASSIGNED_RESPONSIBILITIES_OUT)?;
+
+        //Volumes were only needed to decide the moves.
+        for desc in &mut manifest.rotation_manifest {
+            for responsibility in desc.responsibilities.get_mut().into_iter().flatten() {
+                responsibility.volume = None;
+            }
+        }
+        manifest.title = manifest.title.to_string() + " (reassigned responsibilities)";
+        std::fs::write(
+            REASSIGNED_MANIFEST_OUT,
+            to_flattened_yaml(&manifest, manifest_type.filename(), REASSIGNED_RESPONSIBILITIES_OUT)?,
+        )?;
+
+        Ok(())
+    }
+
+    /// Applies the growth scenario to the work covered by the active and proposed manifests and compares
+    /// each rotation's volume with and without it, and against the manifest's baselines. The scenario's
+    /// volumes by rotation date and its comparison are written in the same layouts as the historical ones.
+    pub fn analyze_scenario(
+        &self,
+        rotation_start: &NaiveDate,
+        rotation_end: &NaiveDate,
+    ) -> Result<(), Box<dyn Error>> {
+        let _ = std::fs::remove_file(SCENARIO_VOLUMES_OUT);
+        let _ = std::fs::remove_file(ACTIVE_SCENARIO_COMPARISON_OUT);
+        let _ = std::fs::remove_file(PROPOSED_SCENARIO_COMPARISON_OUT);
+        let _ = std::fs::remove_file(ACTIVE_SCENARIO_VOLUME_BY_DATE_OUT);
+        let _ = std::fs::remove_file(PROPOSED_SCENARIO_VOLUME_BY_DATE_OUT);
+
+        if !std::path::Path::new(GROWTH_SCENARIO).exists() {
+            println!("No growth scenario at {}, skipping scenario analysis.", GROWTH_SCENARIO);
+            return Ok(());
+        }
+        let scenario = GrowthScenario::read(GROWTH_SCENARIO)?;
+        println!("Applying growth scenario {}.", scenario.title);
+
+        let mut comparisons: Vec<ScenarioComparison> = Vec::new();
+
+        let mut scenario_coverage_tree = self.coverage_tree.clone();
+        scenario.apply(&mut scenario_coverage_tree, &self.source)?;
+        Self::volume_heatmap_to_json(
+            &scenario_coverage_tree,
+            rotation_start,
+            rotation_end,
+            ACTIVE_SCENARIO_VOLUME_BY_DATE_OUT.to_string(),
+        )?;
+        let comparison = ScenarioComparison::build(
+            ACTIVE_OUTPUTS.label,
+            &self.coverage_tree,
+            &scenario_coverage_tree,
+            self.manifest.baselines.clone(),
+        );
+        comparison.comparison.to_json(ACTIVE_SCENARIO_COMPARISON_OUT)?;
+        comparisons.push(comparison);
+
+        if let Some((proposed_manifest, proposed_coverage_tree)) = &self.proposed {
+            let mut scenario_coverage_tree = proposed_coverage_tree.clone();
+            scenario.apply(&mut scenario_coverage_tree, &self.source)?;
+            Self::volume_heatmap_to_json(
+                &scenario_coverage_tree,
+                rotation_start,
+                rotation_end,
+                PROPOSED_SCENARIO_VOLUME_BY_DATE_OUT.to_string(),
+            )?;
+            let comparison = ScenarioComparison::build(
+                PROPOSED_OUTPUTS.label,
+                proposed_coverage_tree,
+                &scenario_coverage_tree,
+                proposed_manifest.baselines.clone(),
+            );
+            comparison.comparison.to_json(PROPOSED_SCENARIO_COMPARISON_OUT)?;
+            comparisons.push(comparison);
+        }
+
+        scenario_comparisons_to_tsv(&comparisons, SCENARIO_VOLUMES_OUT)
+    }
+
     fn volume_heatmap_to_json(
         coverage_tree: &CoverageMap,
         rotation_start: &NaiveDate,
@@ -288,38 +926,35 @@ impl MainCommon {
 
         //Add volumes to the manifest before creating active manifest json
         {
-            let mut manifest = ManifestType::Active.get()?;
             let mut mutable_temporary_coverage_tree = self.coverage_tree.clone();
             mutable_temporary_coverage_tree.populate_responsibility_volumes(
-                &mut manifest,
+                &mut self.manifest,
                 rotation_start,
                 rotation_end,
             )?;
-            manifest
+            self.manifest
                 .to_json(&(BASE.to_string() + "/active_rotation_manifest" + &millistr + ".json"))?;
         }
 
         //Add volumes to the manifest before creating proposed manifest json
-        if ManifestType::Proposed.exists() {
-            {
-                let proposed_json_filename =
-                    &(BASE.to_string() + "/proposed_rotation_manifest" + &millistr + ".json");
-                match ManifestType::Proposed.get() {
-                    Ok(mut manifest) => {
-                        let mut mutable_temporary_coverage_tree = self.coverage_tree.clone();
-                        mutable_temporary_coverage_tree.populate_responsibility_volumes(
-                            &mut manifest,
-                            rotation_start,
-                            rotation_end,
-                        )?;
-                        manifest.to_json(proposed_json_filename)?;
-                    }
-                    Err(_) => {
-                        println!("No proposed rotation. Deleting file if it exists.");
-                        if std::fs::metadata(proposed_json_filename).is_ok() {
-                            std::fs::remove_file(proposed_json_filename)
-                                .expect("Should be able to delete this file.");
-                        }
+        {
+            let proposed_json_filename =
+                &(BASE.to_string() + "/proposed_rotation_manifest" + &millistr + ".json");
+            match &mut self.proposed {
+                Some((manifest, _)) => {
+                    let mut mutable_temporary_coverage_tree = self.coverage_tree.clone();
+                    mutable_temporary_coverage_tree.populate_responsibility_volumes(
+                        manifest,
+                        rotation_start,
+                        rotation_end,
+                    )?;
+                    manifest.to_json(proposed_json_filename)?;
+                }
+                None => {
+                    println!("No proposed rotation. Deleting file if it exists.");
+                    if std::fs::metadata(proposed_json_filename).is_ok() {
+                        std::fs::remove_file(proposed_json_filename)
+                            .expect("Should be able to delete this file.");
                     }
                 }
             }
@@ -349,35 +984,20 @@ impl MainCommon {
         )?;
 
         //Proposal
-        if ManifestType::Proposed.exists() {
-            match ManifestType::Proposed.get() {
-                Ok(proposed_manifest) => {
-                    println!();
-                    println!("Generating proposal frontend statics.");
-                    let proposed_coverage_tree =
-                        build_coverage_tree_from_manifest(proposed_manifest, &self.source)?;
-                    Self::volume_heatmap_to_json(
-                        &proposed_coverage_tree,
-                        rotation_start,
-                        rotation_end,
-                        BASE.to_string()
-                            + "/"
-                            + VOLUME_BY_DATE_ROTATION_PROPOSED
-                            + &millistr
-                            + ".json",
-                    )?;
+        if let Some((_, proposed_coverage_tree)) = &self.proposed {
+            println!();
+            println!("Generating proposal frontend statics.");
+            Self::volume_heatmap_to_json(
+                proposed_coverage_tree,
+                rotation_start,
+                rotation_end,
+                BASE.to_string() + "/" + VOLUME_BY_DATE_ROTATION_PROPOSED + &millistr + ".json",
+            )?;
 
-                    let comparison = compare(&self.coverage_tree, &proposed_coverage_tree);
-                    comparison.to_json(
-                        (BASE.to_string() + "/" + PROPOSED_DIFFERENTIAL + &millistr + ".json")
-                            .as_str(),
-                    )?;
-                }
-                Err(e) => {
-                    println!("Proposed manifest error: {}", e.to_string());
-                    return Err(e);
-                }
-            }
+            let comparison = compare(&self.coverage_tree, proposed_coverage_tree);
+            comparison.to_json(
+                (BASE.to_string() + "/" + PROPOSED_DIFFERENTIAL + &millistr + ".json").as_str(),
+            )?;
         }
 
         Ok(())
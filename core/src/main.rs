@@ -3,7 +3,31 @@ use std::error::Error;
 use chrono::NaiveDate;
 use rotation_tool::{print_averages_by_modality_and_day, source_check, MainCommon};
 
+const FMT_COMMAND: &str = "fmt";
+const CHECK_FLAG: &str = "--check";
+const GENERATE_COMMAND: &str = "generate";
+
 fn main() -> Result<(), Box<dyn Error>> {
+    //`fmt [--check] [manifest files]` rewrites manifests in canonical form instead of running analyses.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(|x| x.as_str()) == Some(FMT_COMMAND) {
+        let check = args.iter().any(|arg| arg == CHECK_FLAG);
+        let filenames: Vec<String> = args
+            .iter()
+            .skip(1)
+            .filter(|arg| *arg != CHECK_FLAG)
+            .cloned()
+            .collect();
+        return rotation_tool::format_manifests(&filenames, check);
+    }
+    //`generate [output file] [settings file]` writes synthetic exam data instead of running analyses.
+    if args.first().map(|x| x.as_str()) == Some(GENERATE_COMMAND) {
+        return rotation_tool::generate_synthetic_data(
+            args.get(1).map(|x| x.as_str()),
+            args.get(2).map(|x| x.as_str()),
+        );
+    }
+
     print!("{}[2J", 27 as char);
     for _ in 1..10 {
         println!();
@@ -38,6 +62,36 @@ fn main() -> Result<(), Box<dyn Error>> {
         MainCommon::analyze_rotations(&mut common)?;
     }
 
+    let roster_analysis: bool = true;
+    if roster_analysis {
+        common.analyze_roster(rotation_start, rotation_end)?;
+    }
+
+    let staffing_projection: bool = true;
+    if staffing_projection {
+        common.analyze_staffing(rotation_start, rotation_end)?;
+    }
+
+    let scenario_analysis: bool = true;
+    if scenario_analysis {
+        common.analyze_scenario(rotation_start, rotation_end)?;
+    }
+
+    let optimize_boundaries: bool = false;
+    if optimize_boundaries {
+        common.optimize_boundaries()?;
+    }
+
+    let reassign_responsibilities: bool = false;
+    if reassign_responsibilities {
+        common.reassign_responsibilities(rotation_start, rotation_end)?;
+    }
+
+    let write_workbook: bool = true;
+    if write_workbook {
+        common.write_workbook()?;
+    }
+
     let generate_frontend_statics: bool = true;
     if generate_frontend_statics {
         common.generate_frontend_statics(
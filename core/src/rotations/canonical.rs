@@ -0,0 +1,224 @@
+use std::error::Error;
+
+use serde_yaml::{Mapping, Value};
+
+use super::manifest::Manifest;
+
+//Keys whose values are StringTypes. These are written in slash separated form when possible.
+const STRING_TYPE_KEYS: &[&str] = &["sites", "exams", "contexts", "days"];
+const DAYS_KEY: &str = "days";
+const DELIMITER: &str = "/";
+
+//Weekdays are written in week order rather than alphabetically.
+fn weekday_rank(day: &str) -> usize {
+    match day.parse::<chrono::Weekday>() {
+        Ok(weekday) => weekday.num_days_from_monday() as usize,
+        Err(_) => 7,
+    }
+}
+
+fn string_members(sequence: &[Value]) -> Option<Vec<String>> {
+    let mut retval: Vec<String> = Vec::new();
+    for member in sequence {
+        match member {
+            Value::String(x) => retval.push(x.to_string()),
+            _ => {
+                return None;
+            }
+        }
+    }
+    Some(retval)
+}
+
+fn canonicalize_string_types(key: &str, value: Value) -> Value {
+    let sequence = match &value {
+        Value::Sequence(x) => x,
+        _ => {
+            return value;
+        }
+    };
+
+    let mut members = match string_members(sequence) {
+        Some(x) => x,
+        None => {
+            return value;
+        }
+    };
+
+    //A member containing the delimiter can't be represented in the slash separated form.
+    if members.is_empty() || members.iter().any(|member| member.contains(DELIMITER)) {
+        return value;
+    }
+
+    members.sort();
+    members.dedup();
+    if key == DAYS_KEY {
+        members.sort_by_key(|day| weekday_rank(day));
+    }
+
+    Value::String(members.join(DELIMITER))
+}
+
+fn canonicalize_value(value: Value) -> Value {
+    match value {
+        Value::Mapping(mapping) => {
+            let mut retval = Mapping::new();
+            for (key, member) in mapping {
+                //Omitted optional members are left out rather than written as null.
+                if member.is_null() {
+                    continue;
+                }
+                let member = canonicalize_value(member);
+                let member = match key.as_str() {
+                    Some(keystr) if STRING_TYPE_KEYS.contains(&keystr) => {
+                        canonicalize_string_types(keystr, member)
+                    }
+                    _ => member,
+                };
+                retval.insert(key, member);
+            }
+            Value::Mapping(retval)
+        }
+        Value::Sequence(sequence) => {
+            Value::Sequence(sequence.into_iter().map(canonicalize_value).collect())
+        }
+        x => x,
+    }
+}
+
+/// Serializes a manifest to its canonical YAML form. Responsibilities and time periods are sorted,
+/// string sets are alphabetized (weekdays in week order) and written slash separated, and
+/// times are written as zero padded 24 hour times. Comments in the source YAML are not preserved.
+pub fn to_canonical_yaml(manifest: &Manifest) -> Result<String, Box<dyn Error>> {
+    let value = serde_yaml::to_value(manifest)?;
+    Ok(serde_yaml::to_string(&canonicalize_value(value))?)
+}
+
+/// Canonical YAML of a manifest expanded from `source`, headed by a comment saying so. An expanded manifest has its
+/// includes and responsibility templates written out in full, so it can't be copied back over `source` as is;
+/// `changes` names the output listing what was changed, to be carried back by hand.
+pub fn to_flattened_yaml(
+    manifest: &Manifest,
+    source: &str,
+    changes: &str,
+) -> Result<String, Box<dyn Error>> {
+    Ok(format!(
+        "# Flattened from {}: includes and responsibility templates are written out in full.\n# The changes are listed in {}.\n{}",
+        source,
+        changes,
+        to_canonical_yaml(manifest)?
+    ))
+}
+
+pub enum FormatResult {
+    Unchanged,
+    Reformatted,
+    NotCanonical,
+}
+
+/// Rewrites the manifest at `filename` in canonical form. If `check` is set, the file is left
+/// untouched and `FormatResult::NotCanonical` is returned if it differs from its canonical form.
+pub fn format_manifest_file(filename: &str, check: bool) -> Result<FormatResult, Box<dyn Error>> {
+    let current = std::fs::read_to_string(filename)?;
+
+    //Validate the expanded manifest, but write the manifest as authored so templates and includes are kept.
+    Manifest::parse(filename)?;
+    let manifest = Manifest::read(filename)?;
+    let canonical = to_canonical_yaml(&manifest)?;
+
+    if current == canonical {
+        Ok(FormatResult::Unchanged)
+    } else if check {
+        Ok(FormatResult::NotCanonical)
+    } else {
+        std::fs::write(filename, canonical)?;
+        Ok(FormatResult::Reformatted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rotations::description::WrappedSortable;
+    use crate::rotations::stringtypes::StringTypes;
+
+    //Members are listed out of order and as sequences, so neither file is in canonical form.
+    const SLASH_SEPARATED: &str = "title: Test\nresponsibility_templates:\n  ct:\n  - sites: SH\n    exams: General CT\n    contexts: ED\n    time_periods:\n    - 08:00 CD-17:00 CD\nrotation_manifest:\n- rotation: Day\n  location: Remote\n  templates:\n  - template: ct\n    days: Fri/Mon\n";
+    const SEQUENCES: &str = "title: Test\nresponsibility_templates:\n  ct:\n  - sites: [SH]\n    exams: [General CT]\n    contexts: [ED]\n    time_periods:\n    - 08:00 CD-17:00 CD\nrotation_manifest:\n- rotation: Day\n  location: Remote\n  templates:\n  - template: ct\n    days: [Fri, Mon]\n";
+
+    fn write_file(test: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "rotation_tool_canonical_{}_{}.yaml",
+            test,
+            std::process::id()
+        ));
+        std::fs::write(&path, contents).expect("Should be able to write the manifest");
+        path.to_str().expect("Scratch paths are UTF-8").to_string()
+    }
+
+    #[test]
+    fn round_trip() {
+        let manifest = Manifest::read(&write_file("round_trip", SLASH_SEPARATED))
+            .expect("Manifest should read");
+        let canonical = to_canonical_yaml(&manifest).expect("Manifest should serialize");
+        assert_ne!(canonical, SLASH_SEPARATED);
+
+        let reparsed: Manifest =
+            serde_yaml::from_str(&canonical).expect("Canonical YAML should parse");
+        assert_eq!(reparsed, manifest);
+        assert_eq!(
+            to_canonical_yaml(&reparsed).expect("Manifest should serialize"),
+            canonical
+        );
+    }
+
+    #[test]
+    fn check_leaves_file_untouched() {
+        let filename = write_file("check", SEQUENCES);
+        assert!(matches!(
+            format_manifest_file(&filename, true).expect("Manifest should format"),
+            FormatResult::NotCanonical
+        ));
+        assert_eq!(
+            std::fs::read_to_string(&filename).expect("Manifest should be readable"),
+            SEQUENCES
+        );
+
+        assert!(matches!(
+            format_manifest_file(&filename, false).expect("Manifest should format"),
+            FormatResult::Reformatted
+        ));
+        assert!(matches!(
+            format_manifest_file(&filename, true).expect("Manifest should format"),
+            FormatResult::Unchanged
+        ));
+    }
+
+    #[test]
+    fn templates_survive_formatting() {
+        let filename = write_file("templates", SEQUENCES);
+        format_manifest_file(&filename, false).expect("Manifest should format");
+        let formatted = Manifest::read(&filename).expect("Formatted manifest should read");
+
+        let templates = formatted
+            .responsibility_templates
+            .as_ref()
+            .expect("Templates should be kept");
+        assert!(templates.contains_key("ct"));
+
+        let references = formatted.rotation_manifest[0]
+            .templates
+            .as_ref()
+            .expect("Template references should be kept");
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].template, "ct");
+        assert_eq!(
+            references[0].days,
+            Some(StringTypes::new_slash_separated_string_vec("Mon/Fri"))
+        );
+        assert!(formatted.rotation_manifest[0]
+            .responsibilities
+            .get()
+            .is_none());
+    }
+}
@@ -1,9 +1,12 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 use serde::{Deserialize, Serialize};
 
 use super::{
-    responsibility::RotationResponsibility, stringtypes::StringTypes, time_modifiers::RelativeTime,
+    responsibility::{validate_days, validate_timespan, weekdays, RotationResponsibility},
+    stringtypes::StringTypes,
+    template::TemplateReference,
+    time_modifiers::RelativeTime,
     timespan::Timespan,
 };
 
@@ -20,6 +23,17 @@ impl RotationHours {
             days,
         }
     }
+
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors: Vec<String> = Vec::new();
+        validate_days(&self.days, &mut errors);
+        validate_timespan(&self.hours, &weekdays(&self.days), &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -30,7 +44,8 @@ pub struct RotationDescription {
     pub(crate) hours: Option<Vec<RotationHours>>,
     pub(crate) breaktime: Option<(Timespan, Option<String>)>,
     pub(crate) responsibilities: Responsibilities,
-    pub(crate) comments: Option<HashSet<String>>,
+    pub(crate) templates: Option<Vec<TemplateReference>>,
+    pub(crate) comments: Option<BTreeSet<String>>,
 }
 
 impl PartialOrd for RotationDescription {
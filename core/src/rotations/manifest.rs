@@ -1,17 +1,25 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::error::Error;
 use std::fs::{self};
+use std::path::{Path, PathBuf};
 
 use crate::globals::file_names::EXAMPLE_ROTATION_DESCRIPTIONS;
 use crate::serialization::output::JSONFileOut;
+use crate::source_data::tables::exam_data::AttributionTime;
 use crate::MainCommon;
 
 use super::baseline::RotationBaseline;
+use super::overlap_policy::OverlapPolicy;
 use super::description::{Responsibilities, RotationDescription, RotationHours, WrappedSortable};
 use super::responsibility::{RotationResponsibility, TimePeriods};
+use super::rotation_error::RotationManifestParseError;
 use super::special::weekdays;
 use super::stringtypes::StringTypes;
+use super::template::{
+    expand_reference, ManifestFragment, ResponsibilityTemplate, ResponsibilityTemplates,
+    TemplateReference,
+};
 use super::time_modifiers::{RelativeTime, TimeSinceMidnight};
 use super::timespan::Timespan;
 
@@ -19,14 +27,165 @@ use super::timespan::Timespan;
 #[serde(deny_unknown_fields)]
 pub struct Manifest {
     pub title: String,
+    pub includes: Option<Vec<String>>,
+    pub responsibility_templates: Option<ResponsibilityTemplates>,
     pub rotation_manifest: Vec<RotationDescription>,
+    pub overlap_policies: Option<Vec<OverlapPolicy>>,
     pub baselines: Option<Vec<RotationBaseline>>,
+    //Which exam timestamp places work in a rotation's hours. Defaults to the list time.
+    pub attribution_time: Option<AttributionTime>,
+}
+
+/// The first line of `filename` after line `after` containing `needle`, counting from 1, or 0 if there's none.
+fn line_of(filename: &Path, needle: &str, after: u64) -> u64 {
+    let text = fs::read_to_string(filename).unwrap_or_default();
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| (index as u64 + 1, line))
+        .find(|(number, line)| *number > after && line.contains(needle))
+        .map(|(number, _)| number)
+        .unwrap_or(0)
+}
+
+/// Where included files are loaded from and what they contributed. `stack` is the chain of files being included,
+/// to find cycles, and `loaded` every file already included, so a file reached along two paths is only loaded once.
+/// `origins` is the file each rotation came from, in the same order as the rotations.
+struct IncludeState {
+    stack: Vec<PathBuf>,
+    loaded: HashSet<PathBuf>,
+    origins: Vec<PathBuf>,
+}
+
+fn load_includes(
+    including: &Path,
+    includes: &[String],
+    state: &mut IncludeState,
+    templates: &mut ResponsibilityTemplates,
+    rotations: &mut Vec<RotationDescription>,
+) -> Result<(), Box<dyn Error>> {
+    let base = including.parent().unwrap_or(Path::new("."));
+    for include in includes {
+        let path = base.join(include);
+        let canonical = match fs::canonicalize(&path) {
+            Ok(x) => x,
+            Err(e) => {
+                return RotationManifestParseError::generate_boxed(
+                    line_of(including, include, 0),
+                    format!("Can't include {} from {}: {}", path.display(), including.display(), e),
+                );
+            }
+        };
+        if state.stack.contains(&canonical) {
+            return RotationManifestParseError::generate_boxed(
+                line_of(including, include, 0),
+                format!("{} includes itself through {}.", path.display(), including.display()),
+            );
+        }
+        if !state.loaded.insert(canonical.to_path_buf()) {
+            continue;
+        }
+
+        let rdr = fs::File::open(&path)?;
+        let fragment: ManifestFragment = serde_yaml::from_reader(rdr)?;
+
+        for (name, template) in fragment.responsibility_templates.unwrap_or_default() {
+            if templates.insert(name.to_string(), template).is_some() {
+                return RotationManifestParseError::generate_boxed(
+                    line_of(&path, &format!("{}:", name), 0),
+                    format!(
+                        "Responsibility template '{}' in {} is already defined.",
+                        name,
+                        path.display()
+                    ),
+                );
+            }
+        }
+        let fragment_rotations = fragment.rotation_manifest.unwrap_or_default();
+        state
+            .origins
+            .extend(fragment_rotations.iter().map(|_| path.to_path_buf()));
+        rotations.extend(fragment_rotations);
+
+        state.stack.push(canonical);
+        load_includes(
+            &path,
+            &fragment.includes.unwrap_or_default(),
+            state,
+            templates,
+            rotations,
+        )?;
+        state.stack.pop();
+    }
+    Ok(())
 }
 
 impl Manifest {
-    pub fn parse(filename: &str) -> Result<Manifest, Box<dyn Error>> {
+    /// Reads a manifest as written, without expanding includes or templates.
+    pub fn read(filename: &str) -> Result<Manifest, Box<dyn Error>> {
         let rdr = fs::File::open(filename)?;
         let retval: Manifest = serde_yaml::from_reader(rdr)?;
+        Ok(retval)
+    }
+
+    /// Merges included files and replaces template references with the responsibilities they describe.
+    fn expand(&mut self, filename: &str) -> Result<(), Box<dyn Error>> {
+        let mut templates = self.responsibility_templates.take().unwrap_or_default();
+
+        let path = Path::new(filename);
+        let canonical = fs::canonicalize(path)?;
+        let mut state = IncludeState {
+            stack: vec![canonical.to_path_buf()],
+            loaded: HashSet::from([canonical]),
+            origins: self
+                .rotation_manifest
+                .iter()
+                .map(|_| path.to_path_buf())
+                .collect(),
+        };
+        load_includes(
+            path,
+            &self.includes.take().unwrap_or_default(),
+            &mut state,
+            &mut templates,
+            &mut self.rotation_manifest,
+        )?;
+
+        for (desc, origin) in self.rotation_manifest.iter_mut().zip(&state.origins) {
+            let references = match desc.templates.take() {
+                Some(x) => x,
+                None => continue,
+            };
+
+            let mut expanded: Vec<RotationResponsibility> = Vec::new();
+            for reference in &references {
+                match expand_reference(reference, &templates) {
+                    Ok(mut x) => expanded.append(&mut x),
+                    Err(e) => {
+                        eprintln!("Error in {} rotation.", desc.rotation);
+                        let rotation_line = line_of(origin, &desc.rotation, 0);
+                        return RotationManifestParseError::generate_boxed(
+                            line_of(origin, &reference.template, rotation_line),
+                            format!("{} in {}", e, origin.display()),
+                        );
+                    }
+                }
+            }
+
+            match desc.responsibilities.get_mut() {
+                Some(existing) => {
+                    existing.append(&mut expanded);
+                    existing.sort();
+                }
+                None => desc.responsibilities = Responsibilities::fromval(Some(expanded)),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn parse(filename: &str) -> Result<Manifest, Box<dyn Error>> {
+        let mut retval = Manifest::read(filename)?;
+        retval.expand(filename)?;
 
         let mut noerrs = true;
         for desc in &retval.rotation_manifest {
@@ -46,6 +205,28 @@ impl Manifest {
                 }
                 None => (),
             };
+            for hours in desc.hours.iter().flatten() {
+                if let Err(x) = hours.validate() {
+                    noerrs = false;
+                    for e in x {
+                        eprintln!("Error in {} rotation hours. {}", desc.rotation, e);
+                    }
+                }
+            }
+        }
+
+        let known_rotations: BTreeSet<&str> = retval
+            .rotation_manifest
+            .iter()
+            .map(|desc| desc.rotation.as_str())
+            .collect();
+        for policy in retval.overlap_policies.iter().flatten() {
+            if let Err(x) = policy.validate(&known_rotations) {
+                noerrs = false;
+                for e in x {
+                    eprintln!("Error in overlap policy. {}", e);
+                }
+            }
         }
 
         if noerrs {
@@ -59,7 +240,24 @@ impl Manifest {
         let mut example = Manifest {
             title: "Rotation Description Example".to_string(),
             rotation_manifest: Vec::new(),
+            overlap_policies: None,
             baselines: None,
+            attribution_time: None,
+            includes: None,
+            responsibility_templates: Some(BTreeMap::from([(
+                "Weekend Outpatient".to_string(),
+                vec![ResponsibilityTemplate {
+                    sites: None,
+                    exams: None,
+                    contexts: Some(StringTypes::new_slash_separated_string_vec("Outpatient")),
+                    days: Some(StringTypes::new_slash_separated_string_vec("Sat/Sun")),
+                    weekly_fraction: None,
+                    time_periods: Some(
+                        TimePeriods::from_strings(Vec::from(["08:00 CD-17:00 CD"]))
+                            .expect("Should be valid."),
+                    ),
+                }],
+            )])),
         };
 
         example.rotation_manifest.push(RotationDescription {
@@ -128,7 +326,16 @@ impl Manifest {
                     volume: None,
                 },
             ]),
-            comments: Some(HashSet::from([
+            templates: Some(vec![TemplateReference {
+                template: "Weekend Outpatient".to_string(),
+                sites: Some(StringTypes::new_slash_separated_string_vec("Site 1")),
+                exams: Some(StringTypes::new_slash_separated_string_vec("Subspecialty 1")),
+                contexts: None,
+                days: None,
+                weekly_fraction: None,
+                time_periods: None,
+            }]),
+            comments: Some(BTreeSet::from([
                 "Comments can go here.".to_string(),
                 "Comments are an array.".to_string(),
                 "But this section can be omitted entirely.".to_string(),
@@ -165,3 +372,96 @@ impl Manifest {
 }
 
 impl JSONFileOut for Manifest {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "responsibility_templates:\n  ct:\n  - sites: SH\n    exams: General CT\n    contexts: ED\n    days: Mon\n    time_periods:\n    - 08:00 CD-17:00 CD\n";
+
+    //Writes each (name, contents) to a scratch directory for the test and returns the first file's path.
+    fn write_files(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("rotation_tool_manifest_{}_{}", test, std::process::id()));
+        fs::create_dir_all(&directory).expect("Should be able to create a scratch directory");
+        for (name, contents) in files {
+            let path = directory.join(name);
+            fs::create_dir_all(path.parent().expect("Files are in the directory"))
+                .expect("Should be able to create a scratch directory");
+            fs::write(path, contents).expect("Should be able to write manifest files");
+        }
+        directory.join(files[0].0)
+    }
+
+    fn main_file(includes: &[&str], template: &str) -> String {
+        format!(
+            "title: Test\nincludes:\n{}rotation_manifest:\n- rotation: Day\n  location: Remote\n  templates:\n  - template: {}\n",
+            includes.iter().map(|x| format!("- {}\n", x)).collect::<String>(),
+            template
+        )
+    }
+
+    fn expand(filename: &Path) -> Result<Manifest, Box<dyn Error>> {
+        let filename = filename.to_str().expect("Scratch paths are UTF-8");
+        let mut manifest = Manifest::read(filename)?;
+        manifest.expand(filename)?;
+        Ok(manifest)
+    }
+
+    fn responsibilities(manifest: &Manifest) -> usize {
+        manifest.rotation_manifest[0].responsibilities.get().map(|x| x.len()).unwrap_or(0)
+    }
+
+    #[test]
+    fn nested_includes() {
+        let main = main_file(&["a.yaml"], "ct");
+        let filename = write_files(
+            "nested",
+            &[
+                ("main.yaml", &main),
+                ("a.yaml", "includes:\n- sub/b.yaml\n"),
+                ("sub/b.yaml", TEMPLATE),
+            ],
+        );
+        assert_eq!(responsibilities(&expand(&filename).expect("Nested includes expand")), 1);
+    }
+
+    #[test]
+    fn diamond_includes_load_once() {
+        let main = main_file(&["b.yaml", "c.yaml"], "ct");
+        let filename = write_files(
+            "diamond",
+            &[
+                ("main.yaml", &main),
+                ("b.yaml", "includes:\n- d.yaml\n"),
+                ("c.yaml", "includes:\n- d.yaml\n"),
+                ("d.yaml", TEMPLATE),
+            ],
+        );
+        assert_eq!(responsibilities(&expand(&filename).expect("Diamond includes expand")), 1);
+    }
+
+    #[test]
+    fn cyclic_includes_are_rejected() {
+        let main = main_file(&["b.yaml"], "ct");
+        let filename = write_files(
+            "cycle",
+            &[
+                ("main.yaml", &main),
+                ("b.yaml", "includes:\n- c.yaml\n"),
+                ("c.yaml", "includes:\n- b.yaml\n"),
+            ],
+        );
+        let error = expand(&filename).expect_err("Cycles are errors").to_string();
+        assert!(error.contains("includes itself"), "{}", error);
+        assert!(error.contains("at line 2:"), "{}", error);
+    }
+
+    #[test]
+    fn template_errors_have_lines() {
+        let main = main_file(&["d.yaml"], "mr");
+        let filename = write_files("unknown", &[("main.yaml", &main), ("d.yaml", TEMPLATE)]);
+        let error = expand(&filename).expect_err("Unknown templates are errors").to_string();
+        assert!(error.contains("Unknown responsibility template 'mr'"), "{}", error);
+        assert!(error.contains("at line 8:"), "{}", error);
+    }
+}
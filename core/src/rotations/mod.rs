@@ -1,9 +1,14 @@
 pub(crate) mod baseline;
+pub(crate) mod canonical;
 pub(crate) mod description;
 pub(crate) mod manifest;
+pub(crate) mod optimizer;
+pub(crate) mod overlap_policy;
 pub(crate) mod responsibility;
 pub(crate) mod rotation_error;
 pub(crate) mod special;
+pub(crate) mod staffing;
 pub(crate) mod stringtypes;
+pub(crate) mod template;
 pub(crate) mod time_modifiers;
 pub(crate) mod timespan;
@@ -0,0 +1,177 @@
+use std::{collections::BTreeSet, error::Error, fs};
+
+use serde::{Deserialize, Serialize};
+
+use crate::globals;
+
+use super::{
+    responsibility::{check, validate_days},
+    stringtypes::StringTypes,
+    time_modifiers::NEXT_MIDNIGHT,
+};
+
+const DEFAULT_GRID_MINUTES: u64 = 30;
+const DEFAULT_MAX_PASSES: usize = 10;
+const DEFAULT_MAX_MOVES: usize = 50;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OptimizerObjective {
+    /// Make the average daily volume of the rotations sharing boundaries as even as possible.
+    #[default]
+    Variance,
+    /// Bring each rotation's average daily volume as close as possible to the manifest's baselines.
+    Baselines,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OptimizerMeasure {
+    #[default]
+    Rvu,
+    Bvu,
+}
+
+/// How the boundary optimizer searches. Every field is optional.
+/// `rotations` limits which rotations may have their boundaries moved; by default any handoff between two rotations may move.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct OptimizerSettings {
+    pub grid_minutes: Option<u64>,
+    pub objective: Option<OptimizerObjective>,
+    pub measure: Option<OptimizerMeasure>,
+    pub rotations: Option<BTreeSet<String>>,
+    pub max_passes: Option<usize>,
+}
+
+impl OptimizerSettings {
+    pub fn read(filename: &str) -> Result<OptimizerSettings, Box<dyn Error>> {
+        let rdr = fs::File::open(filename)?;
+        let retval: OptimizerSettings = serde_yaml::from_reader(rdr)?;
+        retval.validate()?;
+        Ok(retval)
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let grid = self.grid_minutes();
+        if grid == 0 || !NEXT_MIDNIGHT.to_minutes().is_multiple_of(grid) {
+            eprintln!("Grid of {} minutes doesn't evenly divide a day.", grid);
+            return Err("Error in optimizer settings.".into());
+        }
+        Ok(())
+    }
+
+    pub fn grid_minutes(&self) -> u64 {
+        self.grid_minutes.unwrap_or(DEFAULT_GRID_MINUTES)
+    }
+
+    pub fn objective(&self) -> OptimizerObjective {
+        self.objective.unwrap_or_default()
+    }
+
+    pub fn measure(&self) -> OptimizerMeasure {
+        self.measure.unwrap_or_default()
+    }
+
+    pub fn max_passes(&self) -> usize {
+        self.max_passes.unwrap_or(DEFAULT_MAX_PASSES)
+    }
+
+    pub fn is_adjustable(&self, rotation: &str) -> bool {
+        match &self.rotations {
+            Some(rotations) => rotations.contains(rotation),
+            None => true,
+        }
+    }
+}
+
+/// Work a rotation may not be given. Each listed field must overlap the block for the constraint to apply,
+/// so `rotation: X` with only `sites: Y` keeps every block at site Y away from rotation X.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ForbiddenCoverage {
+    pub rotation: String,
+    pub sites: Option<StringTypes>,
+    pub exams: Option<StringTypes>,
+    pub contexts: Option<StringTypes>,
+    pub days: Option<StringTypes>,
+}
+
+/// Which rotations may trade responsibilities and the constraints on what they may be given.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ReassignmentSettings {
+    pub rotations: BTreeSet<String>,
+    pub objective: Option<OptimizerObjective>,
+    pub measure: Option<OptimizerMeasure>,
+    pub max_moves: Option<usize>,
+    pub forbidden: Option<Vec<ForbiddenCoverage>>,
+}
+
+impl ReassignmentSettings {
+    pub fn read(filename: &str) -> Result<ReassignmentSettings, Box<dyn Error>> {
+        let rdr = fs::File::open(filename)?;
+        let retval: ReassignmentSettings = serde_yaml::from_reader(rdr)?;
+        retval.validate()?;
+        Ok(retval)
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let mut errors: Vec<String> = Vec::new();
+        if self.rotations.len() < 2 {
+            errors.push(
+                "At least two rotations are needed to reassign responsibilities.".to_string(),
+            );
+        }
+        for forbidden in self.forbidden.iter().flatten() {
+            if !self.rotations.contains(&forbidden.rotation) {
+                errors.push(format!(
+                    "Constraint on {} has no effect since it isn't one of the rotations.",
+                    forbidden.rotation
+                ));
+            }
+            let mut field_errors: Vec<String> = Vec::new();
+            if let Some(sites) = &forbidden.sites {
+                check(sites, globals::FACILITIES, "site", &mut field_errors);
+            }
+            if let Some(exams) = &forbidden.exams {
+                check(
+                    exams,
+                    globals::SUBSPECIALTIES,
+                    "subspecialty",
+                    &mut field_errors,
+                );
+            }
+            if let Some(contexts) = &forbidden.contexts {
+                check(contexts, globals::CONTEXTS, "context", &mut field_errors);
+            }
+            if let Some(days) = &forbidden.days {
+                validate_days(days, &mut field_errors);
+            }
+            for error in field_errors {
+                errors.push(format!("Constraint on {}. {}", forbidden.rotation, error));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            Err("Error in reassignment constraints.".into())
+        }
+    }
+
+    pub fn objective(&self) -> OptimizerObjective {
+        self.objective.unwrap_or_default()
+    }
+
+    pub fn measure(&self) -> OptimizerMeasure {
+        self.measure.unwrap_or_default()
+    }
+
+    pub fn max_moves(&self) -> usize {
+        self.max_moves.unwrap_or(DEFAULT_MAX_MOVES)
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlapSplit {
+    Equal,
+    Primary,
+    Weighted,
+}
+
+/// Declares that overlapping coverage among `rotations` is intentional and how work in the overlap is divided.
+/// `primary` is required for the primary split and `weights` for the weighted split.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OverlapPolicy {
+    pub rotations: BTreeSet<String>,
+    pub split: OverlapSplit,
+    pub primary: Option<String>,
+    pub weights: Option<BTreeMap<String, f64>>,
+}
+
+impl OverlapPolicy {
+    pub fn validate(&self, known_rotations: &BTreeSet<&str>) -> Result<(), Vec<String>> {
+        let mut errors: Vec<String> = Vec::new();
+
+        if self.rotations.len() < 2 {
+            errors.push("An overlap policy must list at least two rotations.".to_string());
+        }
+        for rotation in &self.rotations {
+            if !known_rotations.contains(rotation.as_str()) {
+                errors.push(format!(
+                    "Overlap policy rotation '{}' isn't in the manifest.",
+                    rotation
+                ));
+            }
+        }
+
+        match (&self.split, &self.primary) {
+            (OverlapSplit::Primary, Some(primary)) => {
+                if !self.rotations.contains(primary) {
+                    errors.push(format!(
+                        "Primary rotation '{}' isn't one of the overlapping rotations.",
+                        primary
+                    ));
+                }
+            }
+            (OverlapSplit::Primary, None) => {
+                errors.push("The primary split requires 'primary'.".to_string());
+            }
+            (_, Some(_)) => {
+                errors.push("'primary' is only used by the primary split.".to_string());
+            }
+            (_, None) => (),
+        }
+
+        match (&self.split, &self.weights) {
+            (OverlapSplit::Weighted, Some(weights)) => {
+                if weights.keys().collect::<BTreeSet<&String>>()
+                    != self.rotations.iter().collect::<BTreeSet<&String>>()
+                {
+                    errors.push(
+                        "'weights' must have exactly one weight for each overlapping rotation."
+                            .to_string(),
+                    );
+                }
+                if weights.values().any(|weight| *weight < 0.0)
+                    || weights.values().sum::<f64>() <= 0.0
+                {
+                    errors.push(
+                        "Weights must be non-negative and can't all be zero.".to_string(),
+                    );
+                }
+            }
+            (OverlapSplit::Weighted, None) => {
+                errors.push("The weighted split requires 'weights'.".to_string());
+            }
+            (_, Some(_)) => {
+                errors.push("'weights' is only used by the weighted split.".to_string());
+            }
+            (_, None) => (),
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// True if every one of the covering rotations is governed by this policy.
+    pub fn governs(&self, covering: &BTreeSet<String>) -> bool {
+        covering.len() > 1 && covering.is_subset(&self.rotations)
+    }
+
+    /// The share of work attributed to `rotation` when it's covered by all of `covering` at once.
+    pub fn share(&self, rotation: &str, covering: &BTreeSet<String>) -> f64 {
+        let equal = 1.0 / covering.len() as f64;
+        match self.split {
+            OverlapSplit::Equal => equal,
+            OverlapSplit::Primary => match &self.primary {
+                Some(primary) if covering.contains(primary) => {
+                    if primary == rotation {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                }
+                //Secondaries overlapping each other without the primary split evenly.
+                _ => equal,
+            },
+            OverlapSplit::Weighted => {
+                let weights = match &self.weights {
+                    Some(x) => x,
+                    None => {
+                        return equal;
+                    }
+                };
+                let total: f64 = covering
+                    .iter()
+                    .map(|x| weights.get(x).copied().unwrap_or(0.0))
+                    .sum();
+                if total > 0.0 {
+                    weights.get(rotation).copied().unwrap_or(0.0) / total
+                } else {
+                    equal
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(yaml: &str) -> Result<(), Vec<String>> {
+        let policy: OverlapPolicy = serde_yaml::from_str(yaml).expect("Policies parse");
+        policy.validate(&BTreeSet::from(["Day", "Late", "Night"]))
+    }
+
+    #[test]
+    fn policies_validate_their_split() {
+        assert!(validate("rotations: [Day, Late]\nsplit: equal\n").is_ok());
+        assert!(validate("rotations: [Day, Late]\nsplit: primary\nprimary: Day\n").is_ok());
+        assert!(validate("rotations: [Day, Late]\nsplit: primary\n").is_err());
+        assert!(validate("rotations: [Day, Late]\nsplit: primary\nprimary: Night\n").is_err());
+        assert!(validate("rotations: [Day, Late]\nsplit: weighted\nweights:\n  Day: 1\n").is_err());
+        assert!(validate("rotations: [Day, Late]\nsplit: equal\nweights:\n  Day: 1\n  Late: 1\n").is_err());
+        assert!(validate("rotations: [Day, Evening]\nsplit: equal\n").is_err());
+        assert!(validate("rotations: [Day]\nsplit: equal\n").is_err());
+        assert!(serde_yaml::from_str::<OverlapPolicy>("rotations: [Day, Late]\nsplit: half\n").is_err());
+    }
+}
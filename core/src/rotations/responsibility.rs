@@ -43,6 +43,37 @@ pub fn validate_days(days_to_check: &StringTypes, errors: &mut Vec<String>) {
     check(days_to_check, days, "weekday", errors);
 }
 
+pub fn weekdays(days: &StringTypes) -> Vec<chrono::Weekday> {
+    let all_days: Vec<String> = globals::ALL_DAYS
+        .iter()
+        .map(|day| day.to_string())
+        .collect();
+    let all_days: Vec<&str> = all_days.iter().map(|day| day.as_str()).collect();
+    let days = days.to_vec(&all_days);
+    globals::ALL_DAYS
+        .iter()
+        .map(|day| **day)
+        .filter(|day| days.contains(&day.to_string()))
+        .collect()
+}
+
+/// Relative times like PBD+1 fall on different days depending on the weekday, so a span has to end after it starts
+/// on every day it's used, e.g. 17:00 PD-08:00 PBD+1 is fine on Tuesday but runs backwards from Sunday to Saturday on Monday.
+pub fn validate_timespan(time_span: &Timespan, days: &[chrono::Weekday], errors: &mut Vec<String>) {
+    let inverted: Vec<String> = days
+        .iter()
+        .filter(|day| time_span.duration_on(**day) <= 0)
+        .map(|day| day.to_string())
+        .collect();
+    if !inverted.is_empty() {
+        errors.push(format!(
+            "Time period {} doesn't end after it starts on {}",
+            time_span,
+            inverted.join(", ")
+        ));
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct RotationResponsibility {
@@ -57,6 +88,11 @@ pub struct RotationResponsibility {
 }
 
 impl RotationResponsibility {
+    /// The weekdays this responsibility applies to, Monday first.
+    pub fn weekdays(&self) -> Vec<chrono::Weekday> {
+        weekdays(&self.days)
+    }
+
     pub fn validate(&self) -> Result<(), Vec<String>> {
         let mut errors: Vec<String> = Vec::new();
 
@@ -70,14 +106,14 @@ impl RotationResponsibility {
         check(&self.contexts, globals::CONTEXTS, "context", &mut errors);
         validate_days(&self.days, &mut errors);
 
-        for time_period in self.time_periods.get()
+        for time_span in self.time_periods.get().iter().flatten()
         {
-            for time_span in time_period
-            {
-                if time_span.start==time_span.stop
-                {
-                    errors.push(format!("Zero duration time period {} {}",time_span.start.get_time(), time_span.stop.get_time()));
-                }
+            validate_timespan(time_span, &self.weekdays(), &mut errors);
+        }
+
+        if let Some(fraction) = self.weekly_fraction {
+            if fraction <= 0.0 || fraction > 1.0 {
+                errors.push(format!("Fraction {} must be greater than 0 and at most 1", fraction));
             }
         }
 
@@ -181,6 +217,10 @@ impl TimePeriods {
     pub fn get(&self) -> &Option<Vec<Timespan>> {
         &self.value
     }
+
+    pub fn none() -> TimePeriods {
+        TimePeriods { value: None }
+    }
 }
 
 impl<'de> Deserialize<'de> for TimePeriods {
@@ -217,3 +257,33 @@ impl Serialize for TimePeriods {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn responsibility(days: &str, time_period: &str) -> RotationResponsibility {
+        serde_yaml::from_str(&format!(
+            "sites: SH\nexams: General XR\ncontexts: ED\ndays: {}\ntime_periods:\n- {}\n",
+            days, time_period
+        ))
+        .expect("Test responsibilities deserialize")
+    }
+
+    #[test]
+    fn spans_must_run_forward_on_every_day() {
+        //PBD+1 is the current day midweek but Saturday for a Monday rotation.
+        assert!(responsibility("Tue/Wed/Thu/Fri", "17:00 PD-08:00 PBD+1")
+            .validate()
+            .is_ok());
+        let errors = responsibility("Mon/Tue", "17:00 PD-08:00 PBD+1")
+            .validate()
+            .expect_err("Inverted on Monday");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].ends_with("on Mon"), "{}", errors[0]);
+
+        //The same instant written two ways is still empty.
+        assert!(responsibility("Wed", "24:00 CD-00:00 ND").validate().is_err());
+        assert!(responsibility("Sat/Sun", "17:00 CD-08:00 ND").validate().is_ok());
+    }
+}
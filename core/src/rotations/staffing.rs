@@ -0,0 +1,106 @@
+use std::{collections::BTreeMap, error::Error, fs};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dates::BUSINESS_DAYS_PER_YEAR;
+
+const DEFAULT_DAYS_PER_WEEK: f64 = 5.0;
+pub const WEEKS_PER_YEAR: f64 = 52.0;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct StaffMember {
+    pub name: String,
+    pub fte: f64,
+    pub vacation_weeks: f64,
+}
+
+/// The people available to staff a manifest.
+/// `rotation_frequency` gives shifts per week for rotations whose history doesn't reflect how often they'll be staffed.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct StaffingModel {
+    pub people: Vec<StaffMember>,
+    pub working_days_per_year: Option<f64>,
+    pub days_per_week: Option<f64>,
+    pub rotation_frequency: Option<BTreeMap<String, f64>>,
+}
+
+impl StaffingModel {
+    pub fn read(filename: &str) -> Result<StaffingModel, Box<dyn Error>> {
+        let rdr = fs::File::open(filename)?;
+        let retval: StaffingModel = serde_yaml::from_reader(rdr)?;
+        retval.validate()?;
+        Ok(retval)
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let mut errors: Vec<String> = Vec::new();
+        for person in &self.people {
+            if person.fte <= 0.0 || person.fte > 1.0 {
+                errors.push(format!(
+                    "{} has FTE {}, expected more than 0 and at most 1.",
+                    person.name, person.fte
+                ));
+            }
+            if person.vacation_weeks < 0.0 || person.vacation_weeks >= WEEKS_PER_YEAR {
+                errors.push(format!(
+                    "{} has {} vacation weeks.",
+                    person.name, person.vacation_weeks
+                ));
+            }
+        }
+        for (rotation, frequency) in self.rotation_frequency.iter().flatten() {
+            if *frequency < 0.0 || *frequency > 7.0 {
+                errors.push(format!(
+                    "{} is staffed {} times per week.",
+                    rotation, frequency
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            Err("Error in staffing model.".into())
+        }
+    }
+
+    fn days_per_week(&self) -> f64 {
+        self.days_per_week.unwrap_or(DEFAULT_DAYS_PER_WEEK)
+    }
+
+    /// Shifts a full time person works in a year with no vacation, after holidays.
+    fn working_days_per_year(&self) -> f64 {
+        self.working_days_per_year.unwrap_or(BUSINESS_DAYS_PER_YEAR)
+    }
+
+    /// Shifts the person is available for in a year.
+    pub fn shifts_per_year(&self, person: &StaffMember) -> f64 {
+        person.fte * (self.working_days_per_year() - person.vacation_weeks * self.days_per_week())
+    }
+
+    /// Shifts a 1.0 FTE with the group's FTE weighted average vacation is available for in a year.
+    pub fn shifts_per_fte(&self) -> f64 {
+        let total_fte: f64 = self.people.iter().map(|person| person.fte).sum();
+        let average_vacation = if total_fte > 0.0 {
+            self.people
+                .iter()
+                .map(|person| person.fte * person.vacation_weeks)
+                .sum::<f64>()
+                / total_fte
+        } else {
+            0.0
+        };
+        self.working_days_per_year() - average_vacation * self.days_per_week()
+    }
+
+    pub fn get_rotation_frequency(&self, rotation: &str) -> Option<f64> {
+        self.rotation_frequency
+            .as_ref()
+            .and_then(|frequencies| frequencies.get(rotation).copied())
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::description::RotationDescription;
+use super::responsibility::{RotationResponsibility, TimePeriods};
+use super::stringtypes::StringTypes;
+
+/// A responsibility block that may leave members unspecified so they can be provided by the
+/// rotations that reference it.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ResponsibilityTemplate {
+    pub sites: Option<StringTypes>,
+    pub exams: Option<StringTypes>,
+    pub contexts: Option<StringTypes>,
+    pub days: Option<StringTypes>,
+    pub weekly_fraction: Option<f64>,
+    pub time_periods: Option<TimePeriods>,
+}
+
+/// A reference from a rotation to a named template. Any member provided here overrides the
+/// corresponding member of every responsibility in the template.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateReference {
+    pub template: String,
+    pub sites: Option<StringTypes>,
+    pub exams: Option<StringTypes>,
+    pub contexts: Option<StringTypes>,
+    pub days: Option<StringTypes>,
+    pub weekly_fraction: Option<f64>,
+    pub time_periods: Option<TimePeriods>,
+}
+
+pub type ResponsibilityTemplates = BTreeMap<String, Vec<ResponsibilityTemplate>>;
+
+/// A YAML file included by a manifest. It may contribute templates, rotations, and further includes.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestFragment {
+    pub includes: Option<Vec<String>>,
+    pub responsibility_templates: Option<ResponsibilityTemplates>,
+    pub rotation_manifest: Option<Vec<RotationDescription>>,
+}
+
+fn required<T: Clone>(
+    value: &Option<T>,
+    member: &str,
+    reference: &TemplateReference,
+) -> Result<T, String> {
+    match value {
+        Some(x) => Ok(x.clone()),
+        None => Err(format!(
+            "Template '{}' doesn't provide '{}' and the reference doesn't override it.",
+            reference.template, member
+        )),
+    }
+}
+
+impl ResponsibilityTemplate {
+    fn instantiate(
+        &self,
+        reference: &TemplateReference,
+    ) -> Result<RotationResponsibility, String> {
+        let sites = reference.sites.as_ref().or(self.sites.as_ref()).cloned();
+        let exams = reference.exams.as_ref().or(self.exams.as_ref()).cloned();
+        let contexts = reference.contexts.as_ref().or(self.contexts.as_ref()).cloned();
+        let days = reference.days.as_ref().or(self.days.as_ref()).cloned();
+
+        //Coverage type is overridden as a unit so a reference can switch a temporal template to a fractional one.
+        let (weekly_fraction, time_periods) =
+            if reference.weekly_fraction.is_some() || reference.time_periods.is_some() {
+                (reference.weekly_fraction, reference.time_periods.clone())
+            } else {
+                (self.weekly_fraction, self.time_periods.clone())
+            };
+
+        Ok(RotationResponsibility {
+            sites: required(&sites, "sites", reference)?,
+            exams: required(&exams, "exams", reference)?,
+            contexts: required(&contexts, "contexts", reference)?,
+            days: required(&days, "days", reference)?,
+            weekly_fraction,
+            time_periods: time_periods.unwrap_or(TimePeriods::none()),
+            volume: None,
+        })
+    }
+}
+
+/// Builds the responsibilities referenced by `reference` from the named templates.
+/// Errors are messages only, since the caller knows where the reference is.
+pub fn expand_reference(
+    reference: &TemplateReference,
+    templates: &ResponsibilityTemplates,
+) -> Result<Vec<RotationResponsibility>, String> {
+    let template = match templates.get(&reference.template) {
+        Some(x) => x,
+        None => {
+            return Err(format!(
+                "Unknown responsibility template '{}'",
+                reference.template
+            ));
+        }
+    };
+
+    let mut retval: Vec<RotationResponsibility> = Vec::new();
+    for block in template {
+        retval.push(block.instantiate(reference)?);
+    }
+    Ok(retval)
+}
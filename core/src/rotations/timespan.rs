@@ -5,6 +5,8 @@ use serde::{
     Deserialize, Serialize,
 };
 
+use crate::dates::TimeZone;
+
 use super::{
     rotation_error::RotationManifestParseError,
     time_modifiers::{
@@ -64,12 +66,30 @@ fn get_intervening_days(start: chrono::Weekday, end: chrono::Weekday) -> Vec<chr
 }
 
 impl Timespan {
+    /// Minutes from start to stop for a rotation on `day`; zero or negative if the span is empty or inverted that day.
+    pub fn duration_on(&self, day: chrono::Weekday) -> i64 {
+        self.stop.resolve(day) - self.start.resolve(day)
+    }
+
+    /// Minutes actually worked for a rotation on `date`, an hour more or less than `duration_on` when the span
+    /// runs through a daylight saving transition.
+    pub fn elapsed_minutes_on(&self, date: chrono::NaiveDate, time_zone: &TimeZone) -> i64 {
+        time_zone.elapsed_minutes(self.start.resolve_on(date), self.stop.resolve_on(date))
+    }
+
+    /// The wall-clock periods the span covers on `day`, as (day offset, start, stop). Days are always 24 hours
+    /// here; daylight saving transitions are only accounted for by `elapsed_minutes_on`.
     pub fn instantiate_periods(
         &self,
         day: chrono::Weekday,
     ) -> Vec<(i64, TimeSinceMidnight, TimeSinceMidnight)> {
         let mut retval: Vec<(i64, TimeSinceMidnight, TimeSinceMidnight)> = Vec::new();
 
+        //Manifest validation rejects these, but an inverted span would otherwise instantiate across the wrong days.
+        if self.duration_on(day) <= 0 {
+            return retval;
+        }
+
         let startday = self.start.get_day_offset(day);
         let stopday = self.stop.get_day_offset(day);
         let start = *self.start.get_time();
@@ -85,39 +105,27 @@ impl Timespan {
             retval.push((stopday, THIS_MIDNIGHT, stop));
         }
 
+        //A span starting at 24:00 or ending at 00:00 touches a day without covering any of it.
+        retval.retain(|(_, start, stop)| start < stop);
         retval
     }
 }
 
+//PD-n contains the delimiter too, so split wherever both sides are relative times. Only one place can be.
 fn parse_time_span(strval: &str) -> Result<Timespan, RotationManifestParseError> {
     let err =
         RotationManifestParseError::generate(0, format!("Malformed relative time {}", strval));
 
-    let spl = strval.split(DELIMITER);
-    let mut members = Vec::new();
-    for item in spl {
-        members.push(item);
-    }
+    let mut spans = strval.match_indices(DELIMITER).filter_map(|(index, _)| {
+        let start = parse_relative_time(&strval[..index]).ok()?;
+        let stop = parse_relative_time(&strval[index + DELIMITER.len()..]).ok()?;
+        Some(Timespan { start, stop })
+    });
 
-    if members.len() != 2 {
-        return err;
+    match (spans.next(), spans.next()) {
+        (Some(ts), None) => Ok(ts),
+        _ => err,
     }
-
-    let start = match parse_relative_time(members.first().expect("Checked")) {
-        Ok(x) => x,
-        Err(e) => {
-            return Err(e);
-        }
-    };
-    let end = match parse_relative_time(members.get(1).expect("Checked")) {
-        Ok(x) => x,
-        Err(e) => {
-            return Err(e);
-        }
-    };
-
-    let ts = Timespan { start, stop: end };
-    Ok(ts)
 }
 
 struct TimespanVisitor;
@@ -125,7 +133,7 @@ impl<'de> Visitor<'de> for TimespanVisitor {
     type Value = Timespan;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        formatter.write_str("A timespan in the format 'HH:mm Mod-HH:mm Mod' where 'Mod' is a relative time indicator 'PBD' for previous business day, 'CD' for current day, or 'PD' for previous day. Times are in 24-hours.")
+        formatter.write_str("A timespan in the format 'HH:mm Mod-HH:mm Mod' where 'Mod' is a relative time indicator 'PBD' for previous business day, 'PBD+1' for the day after it, 'NBD' for next business day, 'PD' for previous day, 'CD' for current day, 'ND' for next day, 'ND+n' or 'PD-n' for n more days, or 'SOW' or 'EOW' for the Monday or Friday of the work week. Times are in 24-hours.")
     }
 
     fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
@@ -147,3 +155,146 @@ impl<'de> Deserialize<'de> for Timespan {
         deserializer.deserialize_str(TimespanVisitor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        globals::ALL_DAYS,
+        random::SplitMix64,
+        rotations::time_modifiers::tests::{grid, random, RANDOM_CASES},
+    };
+
+    //Instantiated pieces must be non-empty, in order, contiguous across midnight, and add up to the span.
+    fn check_instantiation(span: &Timespan, day: chrono::Weekday) {
+        let duration = span.duration_on(day);
+        let periods = span.instantiate_periods(day);
+        let description = format!("{} on {}", span, day);
+        if duration <= 0 {
+            assert!(periods.is_empty(), "{}", description);
+            return;
+        }
+        assert!(!periods.is_empty(), "{}", description);
+
+        let resolve = |offset: i64, time: &TimeSinceMidnight| {
+            offset * NEXT_MIDNIGHT.to_minutes() as i64 + time.to_minutes() as i64
+        };
+        let (first_offset, first_start, _) = &periods[0];
+        let (last_offset, _, last_stop) = &periods[periods.len() - 1];
+        assert_eq!(
+            resolve(*first_offset, first_start),
+            span.start.resolve(day),
+            "{}",
+            description
+        );
+        assert_eq!(
+            resolve(*last_offset, last_stop),
+            span.stop.resolve(day),
+            "{}",
+            description
+        );
+
+        let mut total: i64 = 0;
+        for (_, start, stop) in &periods {
+            assert!(start < stop, "{}", description);
+            assert!(*stop <= NEXT_MIDNIGHT, "{}", description);
+            total += (stop.to_minutes() - start.to_minutes()) as i64;
+        }
+        for window in periods.windows(2) {
+            let (offset, _, stop) = &window[0];
+            let (next_offset, next_start, _) = &window[1];
+            assert_eq!(*next_offset, offset + 1, "{}", description);
+            assert_eq!(
+                resolve(*offset, stop),
+                resolve(*next_offset, next_start),
+                "{}",
+                description
+            );
+        }
+        assert_eq!(total, duration, "{}", description);
+    }
+
+    #[test]
+    fn instantiated_periods_cover_exactly_the_span() {
+        let times = grid(60);
+        for start in &times {
+            for stop in &times {
+                let span = Timespan {
+                    start: start.clone(),
+                    stop: stop.clone(),
+                };
+                for day in ALL_DAYS {
+                    check_instantiation(&span, **day);
+                }
+            }
+        }
+
+        let mut rng = SplitMix64::new(2);
+        for _ in 0..RANDOM_CASES {
+            let span = Timespan {
+                start: random(&mut rng),
+                stop: random(&mut rng),
+            };
+            for day in ALL_DAYS {
+                check_instantiation(&span, **day);
+            }
+        }
+    }
+
+    #[test]
+    fn spans_parse_around_modifiers_containing_the_delimiter() {
+        let times = grid(240);
+        for start in &times {
+            for stop in &times {
+                let span = Timespan {
+                    start: start.clone(),
+                    stop: stop.clone(),
+                };
+                assert_eq!(
+                    Timespan::from_string(&span.to_string()).ok(),
+                    Some(span.clone()),
+                    "{}",
+                    span
+                );
+            }
+        }
+        assert!(Timespan::from_string("17:00 PD-2").is_err());
+        assert!(Timespan::from_string("17:00 PD-08:00 CD-12:00 CD").is_err());
+    }
+
+    //A Friday-evening-to-Monday-morning weekend is one span for a Saturday or Sunday rotation.
+    #[test]
+    fn weekend_spans_between_work_weeks() {
+        let weekend = Timespan::from_string("17:00 EOW-08:00 SOW").expect("Valid span");
+        for day in [chrono::Weekday::Sat, chrono::Weekday::Sun] {
+            assert_eq!(weekend.duration_on(day), (2 * 24 + 15) * 60);
+        }
+        assert!(weekend.duration_on(chrono::Weekday::Wed) < 0);
+
+        let friday = Timespan::from_string("17:00 CD-08:00 NBD").expect("Valid span");
+        assert_eq!(
+            friday.duration_on(chrono::Weekday::Fri),
+            weekend.duration_on(chrono::Weekday::Sat)
+        );
+    }
+
+    #[test]
+    fn overnights_on_transition_nights() {
+        let overnight = Timespan::from_string("17:00 CD-08:00 ND").expect("Valid span");
+        let date = |month, day| chrono::NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+        let eastern = TimeZone::Eastern;
+        assert_eq!(overnight.elapsed_minutes_on(date(3, 9), &eastern), 14 * 60);
+        assert_eq!(overnight.elapsed_minutes_on(date(11, 2), &eastern), 16 * 60);
+        assert_eq!(
+            overnight.elapsed_minutes_on(date(11, 3), &eastern),
+            overnight.duration_on(chrono::Weekday::Sun)
+        );
+
+        //Friday's weekend shift runs through the Sunday morning transition.
+        let weekend = Timespan::from_string("17:00 CD-08:00 NBD").expect("Valid span");
+        assert_eq!(
+            weekend.elapsed_minutes_on(date(3, 8), &eastern),
+            (2 * 24 + 15 - 1) * 60
+        );
+    }
+}
@@ -1,3 +1,5 @@
 pub(crate) mod access;
 pub(crate) mod processing;
-pub(crate) mod tables;
\ No newline at end of file
+pub(crate) mod tables;
+pub(crate) mod synthetic;
+pub(crate) mod time_zone;
@@ -5,7 +5,6 @@ use std::{
 };
 
 use crate::{
-    globals::file_names::{self, UNACCOUNTED_EXAM_CODES_FILE},
     source_data::tables::{
         bvu_map::{BVUMap, BVUMapEntry},
         exam_categories::{
@@ -22,6 +21,7 @@ use crate::{
 pub(crate) fn check_categories_list(
     main_data: &Vec<Exam>,
     exam_categories_table: &ExamCategories,
+    unaccounted_exam_codes_file: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let existing_exam_categories = exam_categories_table.get_procedure_codes();
 
@@ -42,13 +42,13 @@ pub(crate) fn check_categories_list(
             entries.push(vec![code, desc])
         }
 
-        ExamTable::write(UNACCOUNTED_EXAM_CODES_FILE, entries)?;
+        ExamTable::write(unaccounted_exam_codes_file, entries)?;
         Err(Box::new(std::io::Error::new(
             ErrorKind::InvalidData,
             "Unaccounted exam codes.".to_string(),
         )))
     } else {
-        let _ = std::fs::remove_file(UNACCOUNTED_EXAM_CODES_FILE.to_string());
+        let _ = std::fs::remove_file(unaccounted_exam_codes_file);
         Ok(())
     }
 }
@@ -193,6 +193,7 @@ pub fn build_salem_rvumap(main_data_table: &Vec<Exam>) -> Result<HashMap<String,
 pub fn check_bvusource(
     main_data: &Vec<Exam>,
     bvu_data_table: &BVUMap,
+    bvu_update_file: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut bvu_exam_codes: HashSet<String> = HashSet::new();
     for bvu_entry in bvu_data_table.iter() {
@@ -223,14 +224,14 @@ pub fn check_bvusource(
                     .to_string(),
             ]);
         }
-        BVUMap::write(file_names::BVU_UPDATE_FILE, vecofvec).unwrap();
+        BVUMap::write(bvu_update_file, vecofvec).unwrap();
 
         Err(Box::new(std::io::Error::new(
             ErrorKind::InvalidData,
             "Missing BVU codes.",
         )))
     } else {
-        let _ = std::fs::remove_file(file_names::BVU_UPDATE_FILE.to_string());
+        let _ = std::fs::remove_file(bvu_update_file);
         Ok(())
     }
 }
@@ -239,6 +240,7 @@ pub fn check_bvusource(
 pub fn check_readers(
     main_data: &Vec<Exam>,
     readers: &BTreeMap<u64, ExamReader>,
+    unrecognized_readers_file: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!();
     println!("Excluded Readers:");
@@ -297,14 +299,14 @@ pub fn check_readers(
                 reader.excluded.to_string(),
             ]);
         }
-        ReaderTable::write(file_names::UNRECOGNIZED_READERS_FILE, vecofvec).unwrap();
+        ReaderTable::write(unrecognized_readers_file, vecofvec).unwrap();
 
         Err(Box::new(std::io::Error::new(
             ErrorKind::InvalidData,
             "Unrecognized Readers.",
         )))
     } else {
-        let _ = std::fs::remove_file(file_names::BVU_UPDATE_FILE.to_string());
+        let _ = std::fs::remove_file(unrecognized_readers_file);
         Ok(())
     }
 }
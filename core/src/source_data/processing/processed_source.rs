@@ -1,6 +1,9 @@
 use std::{collections::BTreeMap, error::Error};
 
+use chrono::Datelike;
+
 use crate::{
+    dates::FIRST_DST_RULES_YEAR,
     globals::file_names,
     source_data::tables::{
         bvu_map::BVUMap,
@@ -12,12 +15,77 @@ use crate::{
         table::Table,
         types::{Context, ExamCode, Location, Subspecialty},
     },
+    source_data::time_zone::{TimeZoneSettings, Timestamps},
 };
 
 use super::categorization::{
     check_bvusource, check_categories_list, check_readers, get_site_and_location_context_map,
 };
 
+/// Where the source data and categories are read from, and where reports of exam codes and readers
+/// missing from the categories are written. The default is the usual files under the working directory.
+/// `time_zone` is optional, and there is none if the file doesn't exist.
+#[derive(Debug, Clone)]
+pub struct SourcePaths {
+    pub main_data: String,
+    pub bvu_data: String,
+    pub categories_exam: String,
+    pub categories_location: String,
+    pub exam_aliases: String,
+    pub readers: String,
+    pub time_zone: Option<String>,
+    pub source_cache: Option<String>,
+    pub unaccounted_exam_codes: String,
+    pub bvu_update: String,
+    pub unrecognized_readers: String,
+}
+
+impl Default for SourcePaths {
+    fn default() -> Self {
+        SourcePaths {
+            main_data: file_names::MAIN_DATA_FILE.to_string(),
+            bvu_data: file_names::BVU_DATA_FILE.to_string(),
+            categories_exam: file_names::CATEGORIES_EXAM_FILE.to_string(),
+            categories_location: file_names::CATEGORIES_LOCATION_FILE.to_string(),
+            exam_aliases: file_names::EXAM_ALIAS_FILE.to_string(),
+            readers: file_names::READERS_FILE.to_string(),
+            time_zone: Some(file_names::TIME_ZONE_SETTINGS.to_string()),
+            source_cache: Some(file_names::SOURCE_CACHE.to_string()),
+            unaccounted_exam_codes: file_names::UNACCOUNTED_EXAM_CODES_FILE.to_string(),
+            bvu_update: file_names::BVU_UPDATE_FILE.to_string(),
+            unrecognized_readers: file_names::UNRECOGNIZED_READERS_FILE.to_string(),
+        }
+    }
+}
+
+impl SourcePaths {
+    /// The usual layout of data, categories, and error reports under `directory` instead of the working directory.
+    /// The source isn't cached.
+    pub fn in_directory(directory: &str) -> SourcePaths {
+        let rebase = |filename: &str| {
+            format!(
+                "{}/{}",
+                directory.trim_end_matches('/'),
+                filename.trim_start_matches("./")
+            )
+        };
+        let default = SourcePaths::default();
+        SourcePaths {
+            main_data: rebase(&default.main_data),
+            bvu_data: rebase(&default.bvu_data),
+            categories_exam: rebase(&default.categories_exam),
+            categories_location: rebase(&default.categories_location),
+            exam_aliases: rebase(&default.exam_aliases),
+            readers: rebase(&default.readers),
+            time_zone: default.time_zone.as_deref().map(rebase),
+            source_cache: None,
+            unaccounted_exam_codes: rebase(&default.unaccounted_exam_codes),
+            bvu_update: rebase(&default.bvu_update),
+            unrecognized_readers: rebase(&default.unrecognized_readers),
+        }
+    }
+}
+
 pub struct ProcessedSource {
     pub main_data: Vec<Exam>,
     pub bvu_map: BTreeMap<ExamCode, f64>,
@@ -26,13 +94,14 @@ pub struct ProcessedSource {
     pub context_map: BTreeMap<u64, BTreeMap<Location, Context>>,
     pub alias_map: BTreeMap<ExamCode, String>,
     pub readers: BTreeMap<u64, ExamReader>,
+    pub time_zone: Option<TimeZoneSettings>,
 }
 
 impl ProcessedSource {
-    pub fn build() -> Result<ProcessedSource, Box<dyn Error>> {
-        let bvu_data_table = BVUMap::create(file_names::BVU_DATA_FILE);
+    pub fn build(paths: &SourcePaths) -> Result<ProcessedSource, Box<dyn Error>> {
+        let bvu_data_table = BVUMap::create(&paths.bvu_data);
         let exam_categories_table: ExamCategories =
-            ExamCategories::create(file_names::CATEGORIES_EXAM_FILE);
+            ExamCategories::create(&paths.categories_exam);
 
         let mut bvu_map: BTreeMap<ExamCode, f64> = BTreeMap::new();
         for bvu_entry in bvu_data_table.iter() {
@@ -45,7 +114,7 @@ impl ProcessedSource {
         }
 
         let mut alias_map: BTreeMap<String, ExamCode> = BTreeMap::new();
-        let alias_table = Exam_Aliases::create(file_names::EXAM_ALIAS_FILE);
+        let alias_table = Exam_Aliases::create(&paths.exam_aliases);
         for alias in alias_table.iter() {
             match alias_map.insert(alias.alias, alias.exam_code) {
                 Some(x) => panic!("Duplicate alias {:?}", x),
@@ -54,15 +123,40 @@ impl ProcessedSource {
         }
 
         let mut reader_map: BTreeMap<u64, ExamReader> = BTreeMap::new();
-        for reader in ReaderTable::create(file_names::READERS_FILE).iter() {
+        for reader in ReaderTable::create(&paths.readers).iter() {
             reader_map.insert(reader.signer_acct_id, reader);
         }
 
-        let mut main_data: Vec<Exam> =
-            ExamTable::create(file_names::MAIN_DATA_FILE).get_from_cache_or_build_and_cache()?;
+        let main_data_table = ExamTable::create(&paths.main_data);
+        let mut main_data: Vec<Exam> = match &paths.source_cache {
+            Some(cache) => main_data_table.get_from_cache_or_build_and_cache(cache)?,
+            None => main_data_table.iter().collect(),
+        };
 
         println!("Data set contains {} exams.", main_data.len());
 
+        let time_zone = match &paths.time_zone {
+            Some(filename) => TimeZoneSettings::read_if_present(filename)?,
+            None => None,
+        };
+        if let Some(settings) = &time_zone {
+            if let Some(exam) = main_data
+                .iter()
+                .find(|exam| exam.list_datetime.year() < FIRST_DST_RULES_YEAR)
+            {
+                eprintln!(
+                    "Exam {} is dated {}, but time zones only follow the daylight saving rules in effect since {}.",
+                    exam.accession, exam.list_datetime, FIRST_DST_RULES_YEAR
+                );
+                return Err("Error in time zone settings.".into());
+            }
+            if settings.timestamps == Timestamps::Utc {
+                for exam in &mut main_data {
+                    exam.convert_from_utc(&settings.time_zone);
+                }
+            }
+        }
+
         //Substitute aliases and check readers
         for exam in &mut main_data {
             match alias_map.get(&exam.exam_code) {
@@ -71,9 +165,9 @@ impl ProcessedSource {
             }
         }
         //Checks should happen after alias substitution
-        check_categories_list(&main_data, &exam_categories_table)?;
-        check_bvusource(&main_data, &bvu_data_table)?;
-        check_readers(&main_data, &reader_map)?;
+        check_categories_list(&main_data, &exam_categories_table, &paths.unaccounted_exam_codes)?;
+        check_bvusource(&main_data, &bvu_data_table, &paths.bvu_update)?;
+        check_readers(&main_data, &reader_map, &paths.unrecognized_readers)?;
 
         Ok(ProcessedSource {
             main_data: main_data,
@@ -81,10 +175,11 @@ impl ProcessedSource {
             exam_categories_table: exam_categories_table,
             subspecialty_map: subspecialty_map,
             context_map: get_site_and_location_context_map(&Location_Categories::create(
-                file_names::CATEGORIES_LOCATION_FILE,
+                &paths.categories_location,
             ))?,
             alias_map: alias_map,
             readers: reader_map,
+            time_zone,
         })
     }
 }
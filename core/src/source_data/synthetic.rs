@@ -0,0 +1,305 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fs,
+};
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+
+use crate::{
+    globals,
+    random::SplitMix64,
+    source_data::{
+        processing::processed_source::SourcePaths,
+        tables::{
+            bvu_map::BVUMap, exam_categories::ExamCategories, exam_data::Exam,
+            location_categories::Location_Categories, readers::ReaderTable, table::Table,
+        },
+    },
+};
+
+const DEFAULT_SEED: u64 = 1;
+const DEFAULT_DAYS: u64 = 28;
+const DEFAULT_EXAMS_PER_DAY: f64 = 400.0;
+const DEFAULT_WEEKEND_WEIGHT: f64 = 0.35;
+const DEFAULT_TURNAROUND_MINUTES: f64 = 60.0;
+const DEFAULT_FINALIZE_MINUTES: f64 = 30.0;
+const MIN_EXAM_MINUTES: u64 = 5;
+const MAX_EXAM_MINUTES: u64 = 30;
+
+//Relative volume by hour, quiet overnight and busiest late morning through afternoon.
+const DEFAULT_HOURLY_WEIGHTS: [f64; 24] = [
+    0.3, 0.25, 0.2, 0.2, 0.2, 0.25, 0.4, 0.7, 1.0, 1.2, 1.3, 1.3, 1.2, 1.2, 1.3, 1.3, 1.2, 1.0,
+    0.8, 0.7, 0.6, 0.5, 0.4, 0.35,
+];
+
+/// How much synthetic data to generate and when it happens. Every field is optional.
+/// `exams_per_day` is the average on a day with weight 1; weekdays default to 1 and weekends to 0.35.
+/// `hourly_weights` has 24 entries, the relative volume in each hour starting at midnight.
+#[derive(Debug, PartialEq, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct GeneratorSettings {
+    pub seed: Option<u64>,
+    pub start: Option<NaiveDate>,
+    pub days: Option<u64>,
+    pub exams_per_day: Option<f64>,
+    pub weekday_weights: Option<BTreeMap<String, f64>>,
+    pub hourly_weights: Option<Vec<f64>>,
+    pub turnaround_minutes: Option<f64>,
+}
+
+impl GeneratorSettings {
+    pub fn read(filename: &str) -> Result<GeneratorSettings, Box<dyn Error>> {
+        let rdr = fs::File::open(filename)?;
+        let retval: GeneratorSettings = serde_yaml::from_reader(rdr)?;
+        retval.validate()?;
+        Ok(retval)
+    }
+
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        let mut errors: Vec<String> = Vec::new();
+        if self.exams_per_day() < 0.0 {
+            errors.push(format!(
+                "{} exams per day is negative.",
+                self.exams_per_day()
+            ));
+        }
+        if self.turnaround_minutes() < 0.0 {
+            errors.push(format!(
+                "Turnaround of {} minutes is negative.",
+                self.turnaround_minutes()
+            ));
+        }
+        let day_names: Vec<String> = globals::ALL_DAYS
+            .iter()
+            .map(|day| day.to_string())
+            .collect();
+        for (day, weight) in self.weekday_weights.iter().flatten() {
+            if !day_names.contains(day) {
+                errors.push(format!(
+                    "Invalid day {}. Valid values are {:?}",
+                    day, day_names
+                ));
+            }
+            if *weight < 0.0 {
+                errors.push(format!("Weight {} for {} is negative.", weight, day));
+            }
+        }
+        let hourly_weights = self.hourly_weights();
+        if hourly_weights.len() != 24 {
+            errors.push(format!(
+                "Hourly weights need 24 entries but have {}.",
+                hourly_weights.len()
+            ));
+        }
+        if hourly_weights.iter().any(|weight| *weight < 0.0)
+            || hourly_weights.iter().sum::<f64>() <= 0.0
+        {
+            errors.push("Hourly weights must be non-negative and not all zero.".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            for error in &errors {
+                eprintln!("{}", error);
+            }
+            Err("Error in generator settings.".into())
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed.unwrap_or(DEFAULT_SEED)
+    }
+
+    pub fn start(&self) -> NaiveDate {
+        self.start
+            .unwrap_or(NaiveDate::from_ymd_opt(2024, 1, 1).expect("Should be a valid date."))
+    }
+
+    pub fn days(&self) -> u64 {
+        self.days.unwrap_or(DEFAULT_DAYS)
+    }
+
+    pub fn exams_per_day(&self) -> f64 {
+        self.exams_per_day.unwrap_or(DEFAULT_EXAMS_PER_DAY)
+    }
+
+    pub fn weekday_weight(&self, weekday: chrono::Weekday) -> f64 {
+        match self
+            .weekday_weights
+            .as_ref()
+            .and_then(|weights| weights.get(&weekday.to_string()))
+        {
+            Some(x) => *x,
+            None => match weekday {
+                chrono::Weekday::Sat | chrono::Weekday::Sun => DEFAULT_WEEKEND_WEIGHT,
+                _ => 1.0,
+            },
+        }
+    }
+
+    pub fn hourly_weights(&self) -> Vec<f64> {
+        match &self.hourly_weights {
+            Some(x) => x.clone(),
+            None => DEFAULT_HOURLY_WEIGHTS.to_vec(),
+        }
+    }
+
+    pub fn turnaround_minutes(&self) -> f64 {
+        self.turnaround_minutes
+            .unwrap_or(DEFAULT_TURNAROUND_MINUTES)
+    }
+}
+
+struct SyntheticLocation {
+    site_id: u64,
+    location: String,
+    facility: String,
+    class: u64,
+}
+
+struct SyntheticExamCode {
+    exam_code: String,
+    description: String,
+    rvu: f64,
+}
+
+//Patient class IDs seen with each context in the real data.
+fn class_for_context(context: &str) -> u64 {
+    match context {
+        "Inpatient" => 0,
+        "ED" => 4,
+        globals::OUTPATIENT => 5,
+        _ => 2,
+    }
+}
+
+//The facility the tool will attribute an exam at this location to, which also prefixes SH accessions.
+fn facility_for_location(site_id: u64, location: &String) -> Option<String> {
+    if site_id == globals::SH_SITE_ID {
+        if location.len() >= 2 {
+            if let Some(x) = globals::map_sh_location_to_facility(location) {
+                return Some(x);
+            }
+        }
+        return Some(globals::SH.to_string());
+    }
+    globals::siteid_to_sitename(site_id).or_else(|| globals::get_location_site_mapping(location))
+}
+
+/// Builds exams that pass every source check against the categories and readers files: each exam code is
+/// categorized and has a BVU, each location has a context, and each signer is a recognized reader.
+/// Exam codes are drawn in proportion to their counts in the BVU file. Non-radiology work isn't generated.
+pub fn generate_exams(
+    settings: &GeneratorSettings,
+    paths: &SourcePaths,
+) -> Result<Vec<Exam>, Box<dyn Error>> {
+    let mut rng = SplitMix64::new(settings.seed());
+
+    let locations: Vec<SyntheticLocation> = Location_Categories::create(&paths.categories_location)
+        .iter()
+        .filter(|entry| entry.context != globals::NON_RADIOLOGY && entry.location != "NULL")
+        .filter_map(|entry| {
+            facility_for_location(entry.site_id, &entry.location).map(|facility| {
+                SyntheticLocation {
+                    site_id: entry.site_id,
+                    class: class_for_context(&entry.context),
+                    location: entry.location,
+                    facility,
+                }
+            })
+        })
+        .collect();
+
+    let subspecialties: HashMap<String, (String, String)> =
+        ExamCategories::create(&paths.categories_exam)
+            .iter()
+            .map(|entry| {
+                (
+                    entry.exam_code,
+                    (entry.subspecialty, entry.exam_description),
+                )
+            })
+            .collect();
+    let mut exam_codes: Vec<SyntheticExamCode> = Vec::new();
+    let mut exam_code_weights: Vec<f64> = Vec::new();
+    for entry in BVUMap::create(&paths.bvu_data).iter() {
+        if let Some((subspecialty, description)) = subspecialties.get(&entry.exam_code) {
+            if subspecialty == globals::NON_RADIOLOGY {
+                continue;
+            }
+            exam_code_weights.push(entry.exam_count.unwrap_or(1.0).max(1.0));
+            exam_codes.push(SyntheticExamCode {
+                exam_code: entry.exam_code,
+                description: description.to_string(),
+                rvu: entry.average_rvu.unwrap_or(1.0),
+            });
+        }
+    }
+
+    let readers: Vec<_> = ReaderTable::create(&paths.readers)
+        .iter()
+        .filter(|reader| !reader.excluded)
+        .collect();
+
+    if locations.is_empty() || exam_codes.is_empty() || readers.is_empty() {
+        return Err(
+            "The categories need radiology locations, exam codes, and readers to generate exams."
+                .into(),
+        );
+    }
+
+    let hourly_weights = settings.hourly_weights();
+    let mut retval: Vec<Exam> = Vec::new();
+    for day in 0..settings.days() {
+        let date = settings.start() + Duration::days(day as i64);
+        let mean = settings.exams_per_day() * settings.weekday_weight(date.weekday());
+        let count = rng.normal(mean, mean.sqrt()).round().max(0.0) as u64;
+        let midnight = date.and_hms_opt(0, 0, 0).expect("Midnight is valid");
+
+        let mut list_times: Vec<NaiveDateTime> = (0..count)
+            .map(|_| {
+                let hour = rng.weighted_index(&hourly_weights) as i64;
+                let minute = rng.below(60) as i64;
+                midnight + Duration::minutes(hour * 60 + minute)
+            })
+            .collect();
+        list_times.sort();
+
+        for list_datetime in list_times {
+            let location = &locations[rng.below(locations.len() as u64) as usize];
+            let exam_code = &exam_codes[rng.weighted_index(&exam_code_weights)];
+            let reader = &readers[rng.below(readers.len() as u64) as usize];
+
+            let completed = list_datetime
+                + Duration::minutes(
+                    (MIN_EXAM_MINUTES + rng.below(MAX_EXAM_MINUTES - MIN_EXAM_MINUTES + 1)) as i64,
+                );
+            let preliminary = completed
+                + Duration::minutes(rng.exponential(settings.turnaround_minutes()).round() as i64);
+            let finalized = preliminary
+                + Duration::minutes(rng.exponential(DEFAULT_FINALIZE_MINUTES).round() as i64);
+
+            retval.push(Exam {
+                accession: format!("{}{:08}", location.facility, retval.len() + 1),
+                exam_code: exam_code.exam_code.to_string(),
+                procedure_description: exam_code.description.to_string(),
+                signer_acct_id: reader.signer_acct_id,
+                rad_last_name: reader.rad_last_name.to_string(),
+                rad_first_name: reader.rad_first_name.to_string(),
+                list_datetime,
+                completed_datetime: Some(completed),
+                preliminary_datetime: Some(preliminary),
+                finalized_datetime: Some(finalized),
+                rvu: exam_code.rvu,
+                site_id: location.site_id,
+                location: location.location.to_string(),
+                class: location.class,
+            });
+        }
+    }
+
+    Ok(retval)
+}
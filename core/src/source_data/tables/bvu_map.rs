@@ -5,10 +5,14 @@ use super::{table::Table, types::ExamCode};
 pub struct BVUMapEntry {
     pub exam_code: ExamCode,
     pub bvu: f64,
+    pub exam_count: Option<f64>,
+    pub average_rvu: Option<f64>,
 }
 
 const EXAM_CODE_HEADER: &str = "Location group";
 const BVU_HEADER: &str = "50th";
+const EXAM_COUNT_HEADER: &str = "# Exams";
+const AVERAGE_RVU_HEADER: &str = "av wRVU";
 
 pub struct BVUMap {
     filename: String,
@@ -27,6 +31,8 @@ impl Table for BVUMap {
         Ok(BVUMapEntry {
             exam_code: Self::get_from_row_with_header(EXAM_CODE_HEADER, header_map, row),
             bvu: Self::parse(BVU_HEADER, header_map, row)?,
+            exam_count: Self::parse_optional(EXAM_COUNT_HEADER, header_map, row),
+            average_rvu: Self::parse_optional(AVERAGE_RVU_HEADER, header_map, row),
         })
     }
 }
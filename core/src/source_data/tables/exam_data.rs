@@ -3,6 +3,8 @@ use std::collections::{HashMap, HashSet};
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
+use crate::dates::TimeZone;
+
 use super::{
     table::Table,
     types::{ExamCode, ExamDescription, Location},
@@ -17,12 +19,51 @@ pub struct Exam {
     pub rad_last_name: String,                  //RadLastNm
     pub rad_first_name: String,                 //RadFirstNm
     pub list_datetime: NaiveDateTime,           //Exam Started
+    #[serde(default)]
+    pub completed_datetime: Option<NaiveDateTime>, //Exam Completed
+    #[serde(default)]
+    pub preliminary_datetime: Option<NaiveDateTime>, //Exam Preliminary
+    #[serde(default)]
+    pub finalized_datetime: Option<NaiveDateTime>, //Exam Finalized
     pub rvu: f64,                               //WorkRVU
     pub site_id: u64,                           //SiteID
     pub location: Location,
     pub class: u64, //PatientClassID
 }
 
+/// Which of an exam's timestamps decides the rotation responsible for it.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttributionTime {
+    #[default]
+    Started,
+    Completed,
+    Finalized,
+}
+
+impl Exam {
+    /// The exam's timestamp for the given attribution time, or None if the exam doesn't have it.
+    pub fn get_attribution_datetime(&self, attribution_time: AttributionTime) -> Option<NaiveDateTime> {
+        match attribution_time {
+            AttributionTime::Started => Some(self.list_datetime),
+            AttributionTime::Completed => self.completed_datetime,
+            AttributionTime::Finalized => self.finalized_datetime,
+        }
+    }
+
+    /// Converts every timestamp from UTC to wall-clock time in `time_zone`, which is what rotations are scheduled in.
+    pub fn convert_from_utc(&mut self, time_zone: &TimeZone) {
+        self.list_datetime = time_zone.from_utc(self.list_datetime);
+        for datetime in [
+            &mut self.completed_datetime,
+            &mut self.preliminary_datetime,
+            &mut self.finalized_datetime,
+        ] {
+            *datetime = datetime.map(|x| time_zone.from_utc(x));
+        }
+    }
+}
+
 const ACCESSION_HEADER: &str = "Accession";
 const PROCEDURE_CODE_HEADER: &str = "ProcedureCodeList";
 const PROCEDURE_DESCRIPTION_HEADER: &str = "ProcedureDescList";
@@ -30,11 +71,16 @@ const SIGNER_ACCT_ID_HEADER: &str = "SignerAcctID";
 const RAD_LAST_NAME_HEADER: &str = "RadLastNm";
 const RAD_FIRST_NAME_HEADER: &str = "RadFirstNm";
 const LIST_TIME_HEADER: &str = "Exam Started";
+const COMPLETED_TIME_HEADER: &str = "Exam Completed";
+const PRELIMINARY_TIME_HEADER: &str = "Exam Preliminary";
+const FINALIZED_TIME_HEADER: &str = "Exam Finalized";
 const RVU_HEADER: &str = "WorkRVU";
 const SITE_ID_HEADER: &str = "SiteID";
 const LOCATION_HEADER: &str = "LocationDescription";
 const PATIENT_CLASS_HEADER: &str = "PatientClassID";
 
+const DATE_FORMAT: &str = "%m/%d/%Y %H:%M";
+
 pub struct ExamTable {
     filename: String,
 }
@@ -61,6 +107,9 @@ impl Table for ExamTable {
             rad_last_name: Self::get_from_row_with_header(RAD_LAST_NAME_HEADER, header_map, row),
             rad_first_name: Self::get_from_row_with_header(RAD_FIRST_NAME_HEADER, header_map, row),
             list_datetime: Self::get_as_date(LIST_TIME_HEADER, header_map, row)?,
+            completed_datetime: Self::get_as_optional_date(COMPLETED_TIME_HEADER, header_map, row)?,
+            preliminary_datetime: Self::get_as_optional_date(PRELIMINARY_TIME_HEADER, header_map, row)?,
+            finalized_datetime: Self::get_as_optional_date(FINALIZED_TIME_HEADER, header_map, row)?,
             rvu: Self::parse(RVU_HEADER, header_map, row)?,
             site_id: Self::parse(SITE_ID_HEADER, header_map, row)?,
             location: Self::get_from_row_with_header(LOCATION_HEADER, header_map, row),
@@ -69,8 +118,13 @@ impl Table for ExamTable {
     }
 }
 
+//Bump when Exam changes so caches written by older versions are rebuilt rather than read with missing fields.
+const CACHE_VERSION: u32 = 2;
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ExamTableCache {
+    #[serde(default)]
+    version: u32,
     file_timestamp: std::time::SystemTime,
     data: Vec<Exam>,
 }
@@ -81,6 +135,53 @@ impl ExamTable {
             filename: filename.to_string(),
         }
     }
+    /// Writes exams with the same headers and date format they're read with.
+    pub fn write_exams(filename: &str, exams: &[Exam]) -> Result<(), Box<dyn std::error::Error>> {
+        let format_date = |datetime: &NaiveDateTime| datetime.format(DATE_FORMAT).to_string();
+        let format_optional_date = |datetime: &Option<NaiveDateTime>| match datetime {
+            Some(x) => format_date(x),
+            None => String::new(),
+        };
+
+        let mut entries: Vec<Vec<String>> = vec![[
+            ACCESSION_HEADER,
+            PROCEDURE_CODE_HEADER,
+            PROCEDURE_DESCRIPTION_HEADER,
+            SIGNER_ACCT_ID_HEADER,
+            RAD_LAST_NAME_HEADER,
+            RAD_FIRST_NAME_HEADER,
+            LIST_TIME_HEADER,
+            COMPLETED_TIME_HEADER,
+            PRELIMINARY_TIME_HEADER,
+            FINALIZED_TIME_HEADER,
+            RVU_HEADER,
+            SITE_ID_HEADER,
+            LOCATION_HEADER,
+            PATIENT_CLASS_HEADER,
+        ]
+        .iter()
+        .map(|header| header.to_string())
+        .collect()];
+        for exam in exams {
+            entries.push(vec![
+                exam.accession.to_string(),
+                exam.exam_code.to_string(),
+                exam.procedure_description.to_string(),
+                exam.signer_acct_id.to_string(),
+                exam.rad_last_name.to_string(),
+                exam.rad_first_name.to_string(),
+                format_date(&exam.list_datetime),
+                format_optional_date(&exam.completed_datetime),
+                format_optional_date(&exam.preliminary_datetime),
+                format_optional_date(&exam.finalized_datetime),
+                exam.rvu.to_string(),
+                exam.site_id.to_string(),
+                exam.location.to_string(),
+                exam.class.to_string(),
+            ]);
+        }
+        Self::write(filename, entries)
+    }
     pub fn get_procedure_codes(&self) -> HashSet<String> {
         let mut retval: HashSet<String> = HashSet::new();
         for entry in self.iter() {
@@ -95,6 +196,7 @@ impl ExamTable {
     }
     pub fn get_from_cache_or_build_and_cache(
         &self,
+        cache_filename: &str,
     ) -> Result<Vec<Exam>, Box<dyn std::error::Error>> {
         println!("Checking for cache.");
         let last_modified_time = match std::fs::File::open(self.get_file_path()) {
@@ -115,14 +217,16 @@ impl ExamTable {
         };
 
         let cache: Option<ExamTableCache> =
-            match std::fs::File::open(crate::globals::file_names::SOURCE_CACHE) {
+            match std::fs::File::open(cache_filename) {
                 Ok(cache_file) => {
                     let reader = std::io::BufReader::new(cache_file);
                     let read_result: Result<ExamTableCache, serde_json::Error> =
                         serde_json::from_reader(reader);
                     let result = match read_result {
                         Ok(deserialized) => {
-                            if last_modified_time == deserialized.file_timestamp {
+                            if deserialized.version == CACHE_VERSION
+                                && last_modified_time == deserialized.file_timestamp
+                            {
                                 Some(deserialized)
                             } else {
                                 None
@@ -143,10 +247,11 @@ impl ExamTable {
             None => {
                 println!("Updating cache.");
                 let new_cache = ExamTableCache {
+                    version: CACHE_VERSION,
                     file_timestamp: last_modified_time,
                     data: self.iter().collect(),
                 };
-                let cache_file = std::fs::File::create(crate::globals::file_names::SOURCE_CACHE)?;
+                let cache_file = std::fs::File::create(cache_filename)?;
                 let writer = std::io::BufWriter::new(cache_file);
                 serde_json::to_writer(writer, &new_cache)?;
                 Ok(new_cache.data)
@@ -154,3 +259,55 @@ impl ExamTable {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caches_from_older_versions_are_rebuilt() {
+        let table = ExamTable::create("tests/fixtures/basic/exams.csv");
+        let cache_filename = std::env::temp_dir()
+            .join(format!(
+                "rotation_tool_exam_cache_{}.json",
+                std::process::id()
+            ))
+            .to_string_lossy()
+            .to_string();
+
+        //A cache from before the completion timestamps were read, for the current data file.
+        let mut exams: Vec<Exam> = table.iter().collect();
+        for exam in &mut exams {
+            exam.completed_datetime = None;
+            exam.preliminary_datetime = None;
+            exam.finalized_datetime = None;
+        }
+        let file_timestamp = std::fs::metadata(table.get_file_path())
+            .and_then(|metadata| metadata.modified())
+            .expect("Fixture should have a modification time");
+        let mut stale = serde_json::to_value(ExamTableCache {
+            version: CACHE_VERSION,
+            file_timestamp,
+            data: exams,
+        })
+        .expect("Cache should serialize");
+        stale
+            .as_object_mut()
+            .expect("Cache is an object")
+            .remove("version");
+        std::fs::write(&cache_filename, stale.to_string())
+            .expect("Should be able to write the cache");
+
+        let exams = table
+            .get_from_cache_or_build_and_cache(&cache_filename)
+            .expect("Cache should rebuild");
+        assert!(exams.iter().all(|exam| exam.completed_datetime.is_some()));
+
+        //The rebuilt cache is current and used as is.
+        let cached = table
+            .get_from_cache_or_build_and_cache(&cache_filename)
+            .expect("Cache should load");
+        assert_eq!(cached.len(), exams.len());
+        std::fs::remove_file(&cache_filename).expect("Should be able to remove the cache");
+    }
+}
@@ -5,4 +5,5 @@ pub(crate) mod exam_categories;
 pub(crate) mod exam_aliases;
 pub(crate) mod location_categories;
 pub(crate) mod types;
-pub(crate) mod readers;
\ No newline at end of file
+pub(crate) mod readers;
+pub(crate) mod roster;
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::table::Table;
+
+/// One radiologist working one rotation on one date.
+#[derive(Debug, Serialize, Deserialize, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct RosterEntry {
+    pub date: NaiveDate,     //Date
+    pub rotation: String,    //Rotation
+    pub radiologist: String, //Radiologist
+}
+
+const DATE_HEADER: &str = "Date";
+const ROTATION_HEADER: &str = "Rotation";
+const RADIOLOGIST_HEADER: &str = "Radiologist";
+
+//Accepts the inferred roster's ISO dates as well as the scheduling export's dates like 11/09/2023.
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%m/%d/%Y"];
+
+pub struct RosterTable {
+    filename: String,
+}
+
+impl Table for RosterTable {
+    type Entry = RosterEntry;
+    fn get_file_path(&self) -> &str {
+        &self.filename
+    }
+
+    fn build_from_headers_and_row(
+        header_map: &HashMap<String, usize>,
+        row: &Vec<String>,
+    ) -> Result<RosterEntry, Box<dyn std::error::Error>> {
+        let date_string = Self::get_from_row_with_header(DATE_HEADER, header_map, row);
+        let date = match DATE_FORMATS
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(date_string.trim(), format).ok())
+        {
+            Some(x) => x,
+            None => {
+                eprintln!("Bad roster date {}", date_string);
+                return Err(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "Bad roster date",
+                )));
+            }
+        };
+
+        Ok(RosterEntry {
+            date,
+            rotation: Self::get_from_row_with_header(ROTATION_HEADER, header_map, row)
+                .trim()
+                .to_string(),
+            radiologist: Self::get_from_row_with_header(RADIOLOGIST_HEADER, header_map, row)
+                .trim()
+                .to_string(),
+        })
+    }
+}
+
+impl RosterTable {
+    pub fn create(filename: &str) -> RosterTable {
+        RosterTable {
+            filename: filename.to_string(),
+        }
+    }
+}
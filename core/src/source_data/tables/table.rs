@@ -18,7 +18,7 @@ pub trait Table {
     fn get_from_row_with_header(
         header: &str,
         header_map: &HashMap<String, usize>,
-        row: &Vec<String>,
+        row: &[String],
     ) -> String {
         let index = *(header_map
             .get(header)
@@ -29,7 +29,7 @@ pub trait Table {
     fn get_as_date(
         header: &str,
         header_map: &HashMap<String, usize>,
-        row: &Vec<String>,
+        row: &[String],
     ) -> Result<NaiveDateTime, Box<dyn std::error::Error>> {
         let time_string = Self::get_from_row_with_header(header, header_map, row);
 
@@ -47,6 +47,34 @@ pub trait Table {
         }
     }
 
+    //Optional columns may be missing from older exports or empty for exams that haven't reached that step.
+    fn get_as_optional_date(
+        header: &str,
+        header_map: &HashMap<String, usize>,
+        row: &[String],
+    ) -> Result<Option<NaiveDateTime>, Box<dyn std::error::Error>> {
+        if !header_map.contains_key(header) {
+            return Ok(None);
+        }
+        match Self::get_from_row_with_header(header, header_map, row).as_str() {
+            "" | "NULL" => Ok(None),
+            _ => Ok(Some(Self::get_as_date(header, header_map, row)?)),
+        }
+    }
+
+    //Like parse, but for informational columns that may be missing or blank.
+    fn parse_optional<U>(
+        header: &str,
+        header_map: &HashMap<String, usize>,
+        row: &[String],
+    ) -> Option<U>
+    where
+        U: FromStr,
+    {
+        let index = header_map.get(header)?;
+        row.get(*index)?.parse().ok()
+    }
+
     fn parse<U>(
         header: &str,
         header_map: &HashMap<String, usize>,
@@ -0,0 +1,69 @@
+use std::{error::Error, fs};
+
+use serde::Deserialize;
+
+use crate::dates::TimeZone;
+
+/// Whether exam timestamps in the source data are local wall-clock times or UTC.
+#[derive(Debug, PartialEq, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Timestamps {
+    #[default]
+    Local,
+    Utc,
+}
+
+/// The time zone rotations are scheduled in. Without it, the data is taken as wall-clock times with every
+/// day 24 hours long. With it, UTC timestamps are converted to wall-clock times and daylight saving
+/// transitions are audited. Coverage and work attribution still treat every day as 24 wall-clock hours either
+/// way; only the audit measures the hour gained or lost. Only the US zones in `TimeZone` are supported, and
+/// only for data from `FIRST_DST_RULES_YEAR` on.
+#[derive(Debug, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TimeZoneSettings {
+    pub time_zone: TimeZone,
+    #[serde(default)]
+    pub timestamps: Timestamps,
+}
+
+impl TimeZoneSettings {
+    pub fn read(filename: &str) -> Result<TimeZoneSettings, Box<dyn Error>> {
+        let rdr = fs::File::open(filename)?;
+        match serde_yaml::from_reader(rdr) {
+            Ok(x) => Ok(x),
+            Err(e) => {
+                eprintln!("{}", e);
+                Err(format!("Error in time zone settings {}.", filename).into())
+            }
+        }
+    }
+
+    /// The settings in `filename`, or None if there's no such file.
+    pub fn read_if_present(filename: &str) -> Result<Option<TimeZoneSettings>, Box<dyn Error>> {
+        if std::path::Path::new(filename).exists() {
+            Ok(Some(TimeZoneSettings::read(filename)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_parse_tz_names() {
+        let settings: TimeZoneSettings =
+            serde_yaml::from_str("time_zone: America/Chicago\ntimestamps: utc\n")
+                .expect("Settings should parse");
+        assert_eq!(settings.time_zone, TimeZone::Central);
+        assert_eq!(settings.timestamps, Timestamps::Utc);
+
+        let settings: TimeZoneSettings =
+            serde_yaml::from_str("time_zone: America/Phoenix\n").expect("Settings should parse");
+        assert_eq!(settings.timestamps, Timestamps::Local);
+
+        assert!(serde_yaml::from_str::<TimeZoneSettings>("time_zone: EST\n").is_err());
+    }
+}
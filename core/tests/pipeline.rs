@@ -0,0 +1,474 @@
+//End-to-end tests of the analysis pipeline over the fixture in tests/fixtures/basic.
+//Results are compared to the golden files in its expected directory. Run with UPDATE_GOLDEN=1 to rewrite them
+//after an intended change, and review the diff before committing.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+use rotation_tool::pipeline::{
+    audit, audit_dst_nights, audit_to_records, audit_week_continuity,
+    build_coverage_tree_from_manifest, compare, get_by_rotation_average, CoverageMap,
+    GrowthScenario, Manifest, ProcessedSource, SourcePaths, TimeZone,
+};
+use serde_json::Value;
+
+const FIXTURE: &str = "tests/fixtures/basic";
+const TOLERANCE: f64 = 1e-9;
+
+//A scratch directory for the error reports, unique to each test so they can run in parallel.
+fn scratch_directory(test: &str) -> PathBuf {
+    let directory =
+        std::env::temp_dir().join(format!("rotation_tool_{}_{}", test, std::process::id()));
+    fs::create_dir_all(&directory).expect("Should be able to create a scratch directory");
+    directory
+}
+
+fn fixture_paths(test: &str) -> SourcePaths {
+    let scratch = scratch_directory(test);
+    let report = |name: &str| scratch.join(name).to_string_lossy().to_string();
+    SourcePaths {
+        main_data: format!("{}/exams.csv", FIXTURE),
+        unaccounted_exam_codes: report("Unaccounted_Exam_Codes.csv"),
+        bvu_update: report("Unaccounted BVU Codes.csv"),
+        unrecognized_readers: report("UnrecognizedReaders.csv"),
+        ..SourcePaths::in_directory(FIXTURE)
+    }
+}
+
+fn manifest(name: &str) -> Manifest {
+    Manifest::parse(&format!("{}/rotations/{}.yaml", FIXTURE, name))
+        .expect("Fixture manifests should parse")
+}
+
+fn coverage_tree(source: &ProcessedSource, name: &str) -> CoverageMap {
+    build_coverage_tree_from_manifest(&manifest(name), source)
+        .expect("Fixture coverage tree should build")
+}
+
+fn assert_close(actual: &Value, expected: &Value, path: &str) {
+    match (actual, expected) {
+        (Value::Number(a), Value::Number(e)) => {
+            let (a, e) = (a.as_f64().unwrap(), e.as_f64().unwrap());
+            assert!((a - e).abs() <= TOLERANCE, "{}: {} != {}", path, a, e);
+        }
+        (Value::Array(a), Value::Array(e)) => {
+            assert_eq!(a.len(), e.len(), "{}: array lengths differ", path);
+            for (index, (a, e)) in a.iter().zip(e).enumerate() {
+                assert_close(a, e, &format!("{}[{}]", path, index));
+            }
+        }
+        (Value::Object(a), Value::Object(e)) => {
+            let actual_keys: Vec<&String> = a.keys().collect();
+            let expected_keys: Vec<&String> = e.keys().collect();
+            assert_eq!(actual_keys, expected_keys, "{}: keys differ", path);
+            for (key, a) in a {
+                assert_close(a, &e[key], &format!("{}.{}", path, key));
+            }
+        }
+        (a, e) => assert_eq!(a, e, "{}", path),
+    }
+}
+
+fn assert_golden(actual: &Value, name: &str) {
+    let filename = Path::new(FIXTURE).join("expected").join(name);
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        let pretty = serde_json::to_string_pretty(actual).expect("Values serialize");
+        fs::write(&filename, pretty + "\n").expect("Should be able to write golden file");
+        return;
+    }
+    let expected: Value = serde_json::from_str(
+        &fs::read_to_string(&filename)
+            .unwrap_or_else(|_| panic!("Missing golden file {}", filename.display())),
+    )
+    .expect("Golden files are JSON");
+    assert_close(actual, &expected, name);
+}
+
+//The structure of a JSON value without its data: the frontend depends on the shape, not the numbers.
+fn shape(value: &Value) -> Value {
+    match value {
+        Value::Null => Value::from("null"),
+        Value::Bool(_) => Value::from("bool"),
+        Value::Number(_) => Value::from("number"),
+        Value::String(_) => Value::from("string"),
+        Value::Array(items) => Value::Array(items.first().map(shape).into_iter().collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.to_string(), shape(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn find_record<'a>(
+    records: &'a Value,
+    facility: &str,
+    subspecialty: &str,
+    context: &str,
+    weekday: &str,
+) -> &'a Value {
+    records
+        .as_array()
+        .expect("Audit records are an array")
+        .iter()
+        .find(|record| {
+            record["facility"] == facility
+                && record["subspecialty"] == subspecialty
+                && record["context"] == context
+                && record["weekday"] == weekday
+        })
+        .unwrap_or_else(|| {
+            panic!(
+                "No audit record for {} {} {} {}",
+                facility, subspecialty, context, weekday
+            )
+        })
+}
+
+#[test]
+fn source_substitutes_aliases_and_passes_checks() {
+    let source =
+        ProcessedSource::build(&fixture_paths("source")).expect("Fixture source should build");
+    assert_eq!(source.main_data.len(), 12);
+    let aliased = source
+        .main_data
+        .iter()
+        .find(|exam| exam.accession == "SH00000004")
+        .expect("Fixture exam");
+    assert_eq!(aliased.exam_code, "CAT6045");
+}
+
+#[test]
+fn unrecognized_readers_are_reported() {
+    let mut paths = fixture_paths("readers");
+    let readers = scratch_directory("readers").join("Readers.csv");
+    fs::write(
+        &readers,
+        "SignerAcctID,RadLastNm,RadFirstNm,Excluded\n6,Burrows,S,false\n",
+    )
+    .expect("Should be able to write readers");
+    paths.readers = readers.to_string_lossy().to_string();
+
+    assert!(ProcessedSource::build(&paths).is_err());
+    let report = fs::read_to_string(&paths.unrecognized_readers).expect("Report should be written");
+    assert!(report.contains("Fromwiller"));
+}
+
+#[test]
+fn audit_matches_golden() {
+    let source =
+        ProcessedSource::build(&fixture_paths("audit")).expect("Fixture source should build");
+    let mut tree = coverage_tree(&source, "active");
+    let audit_result = audit(&mut tree);
+    let records = serde_json::to_value(audit_to_records(&audit_result)).expect("Records serialize");
+
+    //SC XR is only covered until noon, so the 14:00 exam falls in a gap.
+    let sc = find_record(&records, "SC", "General XR", "Outpatient", "Wed");
+    let gaps = sc["error"]["MalformedCoverage"]["gaps"]
+        .as_array()
+        .expect("SC XR has gaps");
+    assert!(gaps.iter().any(|gap| gap["rvus"].as_f64() > Some(0.0)));
+
+    //Nobody covers weekends.
+    let saturday = find_record(&records, "SH", "General XR", "ED", "Sat");
+    assert!(saturday["error"]["NoCoverage"].as_f64() > Some(0.0));
+
+    assert_golden(&records, "audit.json");
+}
+
+#[test]
+fn week_continuity_matches_golden() {
+    let source =
+        ProcessedSource::build(&fixture_paths("continuity")).expect("Fixture source should build");
+    let tree = coverage_tree(&source, "active");
+    let gaps = serde_json::to_value(audit_week_continuity(&tree)).expect("Gaps serialize");
+
+    //Nobody covers the weekend, and with no Sunday exams the per-day audit has no Sunday record to report it in.
+    let weekend = gaps
+        .as_array()
+        .expect("Gaps are an array")
+        .iter()
+        .find(|gap| {
+            gap["facility"] == "SH" && gap["subspecialty"] == "General CT" && gap["context"] == "ED"
+        })
+        .expect("SH CT ED has a weekend gap");
+    assert_eq!(weekend["start"], "Sat 00:00");
+    assert_eq!(weekend["end"], "Mon 00:00");
+    assert_eq!(weekend["hours"], 48.0);
+    assert!(weekend["unaudited_days"]
+        .as_array()
+        .expect("Unaudited days")
+        .contains(&Value::from("Sun")));
+
+    assert_golden(&gaps, "week_continuity.json");
+}
+
+#[test]
+fn utc_timestamps_are_converted_to_the_time_zone() {
+    let scratch = scratch_directory("utc");
+    let settings = scratch.join("time_zone.yaml");
+    fs::write(&settings, "time_zone: America/New_York\ntimestamps: utc\n")
+        .expect("Should be able to write settings");
+    let paths = SourcePaths {
+        time_zone: Some(settings.to_string_lossy().to_string()),
+        ..fixture_paths("utc")
+    };
+
+    let source = ProcessedSource::build(&paths).expect("Fixture source should build");
+    let exam = source
+        .main_data
+        .iter()
+        .find(|exam| exam.accession == "SH00000001")
+        .expect("Fixture exam");
+    //03:15 UTC on a January Monday is Sunday evening in New York.
+    assert_eq!(exam.list_datetime.to_string(), "2024-01-07 22:15:00");
+    assert_eq!(
+        exam.finalized_datetime.map(|x| x.to_string()),
+        Some("2024-01-07 23:20:00".to_string())
+    );
+}
+
+#[test]
+fn time_zones_reject_data_before_the_current_dst_rules() {
+    let scratch = scratch_directory("pre_2007");
+    let settings = scratch.join("time_zone.yaml");
+    fs::write(&settings, "time_zone: America/New_York\n")
+        .expect("Should be able to write settings");
+    let exams = scratch.join("exams.csv");
+    let data = fs::read_to_string(format!("{}/exams.csv", FIXTURE)).expect("Fixture exams");
+    fs::write(
+        &exams,
+        data.replacen("01/08/2024 03:15", "01/08/2006 03:15", 1),
+    )
+    .expect("Should be able to write exams");
+    let paths = SourcePaths {
+        main_data: exams.to_string_lossy().to_string(),
+        time_zone: Some(settings.to_string_lossy().to_string()),
+        ..fixture_paths("pre_2007")
+    };
+
+    assert!(ProcessedSource::build(&paths).is_err());
+}
+
+#[test]
+fn overnights_through_dst_transitions_are_reported() {
+    let scratch = scratch_directory("dst");
+    let exams = scratch.join("exams.csv");
+    fs::write(
+        &exams,
+        "Accession,ProcedureCodeList,ProcedureDescList,SignerAcctID,RadLastNm,RadFirstNm,Exam Started,Exam Completed,Exam Preliminary,Exam Finalized,WorkRVU,SiteID,LocationDescription,PatientClassID\n\
+         SH00000001,CAT6045,CT ABDOMEN WO IV CONTRAST,9,Fromwiller,Travis,03/09/2024 22:00,,,,1.19,1,EDX,4\n\
+         SH00000002,CAT6045,CT ABDOMEN WO IV CONTRAST,9,Fromwiller,Travis,03/10/2024 04:00,,,,1.19,1,EDX,4\n\
+         SH00000003,CAT6045,CT ABDOMEN WO IV CONTRAST,9,Fromwiller,Travis,03/16/2024 22:00,,,,1.19,1,EDX,4\n",
+    )
+    .expect("Should be able to write exams");
+    let manifest_file = scratch.join("weekend.yaml");
+    fs::write(
+        &manifest_file,
+        "title: Weekend\nrotation_manifest:\n\
+         - rotation: Weekend Night\n  location: Remote\n  responsibilities:\n\
+         \x20 - sites: SH\n    exams: General CT\n    contexts: ED\n    days: Sat/Sun\n    time_periods:\n    - 17:00 CD-08:00 ND\n",
+    )
+    .expect("Should be able to write manifest");
+
+    let paths = SourcePaths {
+        main_data: exams.to_string_lossy().to_string(),
+        ..fixture_paths("dst")
+    };
+    let source = ProcessedSource::build(&paths).expect("Source should build");
+    let manifest =
+        Manifest::parse(&manifest_file.to_string_lossy()).expect("Manifest should parse");
+    let tree = build_coverage_tree_from_manifest(
+        &Manifest::parse(&manifest_file.to_string_lossy()).expect("Manifest should parse"),
+        &source,
+    )
+    .expect("Coverage tree should build");
+
+    //Only Saturday night runs through 02:00 on the Sunday clocks go forward.
+    let nights = serde_json::to_value(audit_dst_nights(&manifest, &tree, &TimeZone::Eastern))
+        .expect("Nights serialize");
+    let nights = nights.as_array().expect("Nights are an array");
+    assert_eq!(nights.len(), 1);
+    assert_eq!(nights[0]["date"], "2024-03-09");
+    assert_eq!(nights[0]["transition"], "SpringForward");
+    assert_eq!(nights[0]["nominal_hours"], 15.0);
+    assert_eq!(nights[0]["actual_hours"], 14.0);
+    assert_close(&nights[0]["rvus"], &Value::from(2.38), "rvus");
+    assert_close(
+        &nights[0]["typical_rvus"],
+        &Value::from(1.19),
+        "typical_rvus",
+    );
+
+    assert!(audit_dst_nights(&manifest, &tree, &TimeZone::Arizona).is_empty());
+}
+
+#[test]
+fn rotation_volumes_match_golden() {
+    let source =
+        ProcessedSource::build(&fixture_paths("volumes")).expect("Fixture source should build");
+    let tree = coverage_tree(&source, "active");
+    let volumes = serde_json::to_value(get_by_rotation_average(&tree)).expect("Volumes serialize");
+
+    let rotations: Vec<&String> = volumes
+        .as_object()
+        .expect("Volumes by rotation")
+        .keys()
+        .collect();
+    assert_eq!(rotations, ["Day", "Night"]);
+
+    assert_golden(&volumes, "rotation_volumes.json");
+}
+
+#[test]
+fn manifests_choose_the_attribution_time() {
+    let scratch = scratch_directory("attribution");
+    let exams = scratch.join("exams.csv");
+    fs::write(
+        &exams,
+        "Accession,ProcedureCodeList,ProcedureDescList,SignerAcctID,RadLastNm,RadFirstNm,Exam Started,Exam Completed,Exam Preliminary,Exam Finalized,WorkRVU,SiteID,LocationDescription,PatientClassID\n\
+         SH00000001,CAT6045,CT ABDOMEN WO IV CONTRAST,9,Fromwiller,Travis,01/08/2024 16:50,01/08/2024 17:00,,01/08/2024 17:20,1.19,1,EDX,4\n",
+    )
+    .expect("Should be able to write exams");
+    let source = ProcessedSource::build(&SourcePaths {
+        main_data: exams.to_string_lossy().to_string(),
+        ..fixture_paths("attribution")
+    })
+    .expect("Source should build");
+
+    let manifest_file = scratch.join("finalized.yaml");
+    let active =
+        fs::read_to_string(format!("{}/rotations/active.yaml", FIXTURE)).expect("Fixture manifest");
+    fs::write(&manifest_file, active + "attribution_time: finalized\n")
+        .expect("Should be able to write manifest");
+    let finalized = build_coverage_tree_from_manifest(
+        &Manifest::parse(&manifest_file.to_string_lossy()).expect("Manifest should parse"),
+        &source,
+    )
+    .expect("Coverage tree should build");
+
+    //The exam is started by the day rotation but finalized by the night rotation.
+    let rotations = |tree: &CoverageMap| -> Vec<String> {
+        serde_json::to_value(get_by_rotation_average(tree))
+            .expect("Volumes serialize")
+            .as_object()
+            .expect("Volumes by rotation")
+            .iter()
+            .filter(|(_, volume)| volume["rvu"].as_f64() > Some(0.0))
+            .map(|(rotation, _)| rotation.to_string())
+            .collect()
+    };
+    assert_eq!(rotations(&coverage_tree(&source, "active")), ["Day"]);
+    assert_eq!(rotations(&finalized), ["Night"]);
+}
+
+#[test]
+fn comparison_matches_golden() {
+    let source =
+        ProcessedSource::build(&fixture_paths("comparison")).expect("Fixture source should build");
+    let active = coverage_tree(&source, "active");
+    let proposed = coverage_tree(&source, "proposed");
+    let comparison =
+        serde_json::to_value(compare(&active, &proposed)).expect("Comparison serializes");
+
+    assert_golden(&comparison, "comparison.json");
+}
+
+#[test]
+fn frontend_json_matches_golden_shapes() {
+    let source =
+        ProcessedSource::build(&fixture_paths("frontend")).expect("Fixture source should build");
+    let tree = coverage_tree(&source, "active");
+
+    let mut active = manifest("active");
+    tree.clone()
+        .populate_responsibility_volumes(
+            &mut active,
+            &NaiveDate::from_ymd_opt(2024, 1, 8).unwrap(),
+            &NaiveDate::from_ymd_opt(2024, 1, 14).unwrap(),
+        )
+        .expect("Volumes should populate");
+    let active = serde_json::to_value(&active).expect("Manifest serializes");
+    assert_golden(&active, "active_manifest.json");
+
+    let tree = serde_json::to_value(&tree).expect("Coverage tree serializes");
+    assert_golden(&shape(&tree), "coverage_tree_shape.json");
+}
+
+#[test]
+fn synthetic_data_passes_source_checks() {
+    let scratch = scratch_directory("synthetic");
+    let settings = scratch.join("generator.yaml");
+    fs::write(&settings, "seed: 7\ndays: 7\nexams_per_day: 50\n")
+        .expect("Should be able to write settings");
+    let output = scratch.join("synthetic.csv");
+    rotation_tool::generate_synthetic_data(output.to_str(), settings.to_str())
+        .expect("Synthetic data should generate");
+
+    let report = |name: &str| scratch.join(name).to_string_lossy().to_string();
+    let paths = SourcePaths {
+        main_data: output.to_string_lossy().to_string(),
+        source_cache: None,
+        unaccounted_exam_codes: report("Unaccounted_Exam_Codes.csv"),
+        bvu_update: report("Unaccounted BVU Codes.csv"),
+        unrecognized_readers: report("UnrecognizedReaders.csv"),
+        ..SourcePaths::default()
+    };
+    let source = ProcessedSource::build(&paths).expect("Synthetic source should pass the checks");
+    assert!(!source.main_data.is_empty());
+}
+
+fn growth_scenario(yaml: &str) -> GrowthScenario {
+    serde_yaml::from_str(yaml).expect("Test scenario should parse")
+}
+
+#[test]
+fn growth_factors_multiply_the_matching_work() {
+    let source = ProcessedSource::build(&fixture_paths("growth_factors"))
+        .expect("Fixture source should build");
+    let historical = coverage_tree(&source, "active");
+    let mut grown = historical.clone();
+    growth_scenario("title: Test\ngrowth:\n- factor: 2.0\n- contexts: Outpatient\n  factor: 1.5\n")
+        .apply(&mut grown, &source)
+        .expect("Scenario should apply");
+
+    let before = get_by_rotation_average(&historical);
+    let after = get_by_rotation_average(&grown);
+    //Night only covers ED work, so only the first factor applies to it. Day's outpatient work gets both.
+    assert!((after["Night"].rvu - 2.0 * before["Night"].rvu).abs() <= TOLERANCE);
+    assert!((after["Night"].bvu - 2.0 * before["Night"].bvu).abs() <= TOLERANCE);
+    assert!(after["Day"].rvu > 2.0 * before["Day"].rvu + TOLERANCE);
+    assert!(after["Day"].rvu < 3.0 * before["Day"].rvu);
+}
+
+#[test]
+fn new_volume_is_valued_like_historical_work() {
+    let source =
+        ProcessedSource::build(&fixture_paths("new_volume")).expect("Fixture source should build");
+    let historical = coverage_tree(&source, "active");
+    let new_volume = |exam_code: &str| {
+        growth_scenario(&format!(
+            "title: Test\nnew_volume:\n- site: SH\n  exam: General CT\n  context: ED\n  exam_code: {}\n  exams_per_day: 2\n  days: Mon\n  start: '09:00'\n  stop: '11:00'\n",
+            exam_code
+        ))
+    };
+
+    let mut grown = historical.clone();
+    new_volume("CAT6045")
+        .apply(&mut grown, &source)
+        .expect("Scenario should apply");
+    let before = get_by_rotation_average(&historical);
+    let after = get_by_rotation_average(&grown);
+    //Monday mornings are Day's, and CAT6045 is worth 1.19 RVU and 295 BVU wherever it's read.
+    let rvu = after["Day"].rvu - before["Day"].rvu;
+    let bvu = after["Day"].bvu - before["Day"].bvu;
+    assert!(rvu > 0.0);
+    assert!((rvu / bvu - 1.19 / 295.0).abs() <= TOLERANCE);
+    assert!((after["Night"].rvu - before["Night"].rvu).abs() <= TOLERANCE);
+
+    let mut grown = historical.clone();
+    assert!(new_volume("UNKNOWN").apply(&mut grown, &source).is_err());
+}
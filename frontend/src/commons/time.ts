@@ -1,5 +1,5 @@
 import type { MinutesSinceMidnight } from "../components/coverage/CoverageTree";
-import type { Weekday } from "../components/manifest/RotationManifest";
+import { splitTimePeriodString, type Weekday } from "../components/manifest/RotationManifest";
 
 export const days_of_the_week = [
     "Sunday",
@@ -55,6 +55,21 @@ export const nd = (di:number)=>{
     }
 }
 
+export const nbd = (di:number)=>{
+    switch(di)
+    {
+        case 5:
+        case 6:
+        case 0:
+            return 1;
+        default: return di+1;
+    }
+}
+
+export const plus_days = (di:number, days:number)=>{
+    return ((di+days)%7+7)%7;
+}
+
 export const day_indices = [0,1,2,3,4,5,6];
 
 export const dowfunc=(di:number)=>{
@@ -98,7 +113,26 @@ export const relative_time_to_string = (relative_time:string, dow:number) => {
             break;
         case "PBD+1":new_dow=pbdp1(dow);
             break;
-        default:new_dow=dow;
+        case "NBD":new_dow=nbd(dow);
+            break;
+        //Monday and Friday of the work week; from a weekend, the coming Monday and the Friday just past.
+        case "SOW":new_dow=1;
+            break;
+        case "EOW":new_dow=5;
+            break;
+        default:
+            if(split[1]?.startsWith("ND+"))
+            {
+                new_dow=plus_days(dow,1+parseInt(split[1].slice(3)));
+            }
+            else if(split[1]?.startsWith("PD-"))
+            {
+                new_dow=plus_days(dow,-1-parseInt(split[1].slice(3)));
+            }
+            else
+            {
+                new_dow=dow;
+            }
     }
     let daystring:string=shortdowfunc(new_dow);
 
@@ -106,7 +140,7 @@ export const relative_time_to_string = (relative_time:string, dow:number) => {
 }
 
 export const time_range_to_string = (time_range:string, dow:number) => {
-    const split = time_range.split("-");
+    const split = splitTimePeriodString(time_range);
 
     return relative_time_to_string(split[0],dow)+" - "+relative_time_to_string(split[1],dow);
 }
\ No newline at end of file
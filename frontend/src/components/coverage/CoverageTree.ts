@@ -22,13 +22,23 @@ export interface WeekdayTree{
 export interface Coverages {
     Temporal?:TemporalCoverage[],
     Fractional?:FractionalCoverage[],
+    Layered?:{temporal:TemporalCoverage[],fractional:FractionalCoverage[]},
+}
+
+export const getTemporalCoverages = (coverages:Coverages):TemporalCoverage[]|undefined => {
+    return coverages.Temporal ?? coverages.Layered?.temporal;
+}
+
+export const getFractionalCoverages = (coverages:Coverages):FractionalCoverage[]|undefined => {
+    return coverages.Fractional ?? coverages.Layered?.fractional;
 }
 
 export interface TemporalCoverage{
     start:MinutesSinceMidnight,
     end:MinutesSinceMidnight,
     rotation:string,
-    work_to_rotation_day_offset:number
+    work_to_rotation_day_offset:number,
+    fraction:number
 }
 
 export interface MinutesSinceMidnight
@@ -4,7 +4,9 @@ export interface RotationManifest
 {
     title:string,
     rotation_manifest:[Rotation],
-    baselines:[Baseline]
+    overlap_policies?:OverlapPolicy[],
+    baselines:[Baseline],
+    attribution_time?:"started"|"completed"|"finalized"
 }
 
 export interface WorkHoursPeriod
@@ -33,13 +35,21 @@ export interface Responsibility
 }
 
 export type TimePeriodString = string;
-export type DayOffset = "CD" | "PD" | "PBD" | "PBD+1" | "ND";
+//ND+n and PD-n add n more days to the next or prior day, up to max_extra_days.
+export type DayOffset = "CD" | "PD" | "PBD" | "PBD+1" | "ND" | "NBD" | "SOW" | "EOW" | `ND+${number}` | `PD-${number}`;
+export const max_extra_days = 5;
+const extra_days = Array.from({length:max_extra_days},(_,i)=>i+1);
 export const all_dayoffsets:DayOffset[] = [
     "CD",
     "PD",
     "PBD",
     "PBD+1",
-    "ND"
+    "ND",
+    "NBD",
+    "SOW",
+    "EOW",
+    ...extra_days.map((n):DayOffset=>`ND+${n}`),
+    ...extra_days.map((n):DayOffset=>`PD-${n}`)
 ]
 export function dayoffsetToDisplayString(dayoffset:DayOffset)
 {
@@ -50,7 +60,15 @@ export function dayoffsetToDisplayString(dayoffset:DayOffset)
         case "PBD":return "Prior Business Day";
         case "PBD+1":return "Day After Prior Business Day";
         case "ND":return "Next Day";
+        case "NBD":return "Next Business Day";
+        case "SOW":return "Start of Work Week";
+        case "EOW":return "End of Work Week";
     }
+    if(dayoffset.startsWith("ND+"))
+    {
+        return "Next Day + " + dayoffset.slice(3);
+    }
+    return "Prior Day - " + dayoffset.slice(3);
 }
 export interface RelativeTime {
     time:string, //format hh:mm
@@ -74,9 +92,14 @@ export function relativeTimeToString(rt:RelativeTime)
 {
     return rt.time + " " + rt.day;
 }
+//PD-n contains a dash too, so only split at the dash before the second time.
+export function splitTimePeriodString(str:TimePeriodString)
+{
+    return str.split(/-(?=\d{1,2}:\d{2})/);
+}
 export function parseTimePeriodString(str:TimePeriodString)
 {
-    let relative_times=str.split("-");
+    let relative_times=splitTimePeriodString(str);
     let retval:TimePeriod={
         start:parseRelativeTimeString(relative_times[0]),
         end:parseRelativeTimeString(relative_times[1])
@@ -88,6 +111,14 @@ export function timePeriodToString(period:TimePeriod)
     return relativeTimeToString(period.start) + "-" + relativeTimeToString(period.end);
 }
 
+export interface OverlapPolicy
+{
+    rotations:string[],
+    split:"equal"|"primary"|"weighted",
+    primary?:string,
+    weights?:{[rotation:string]:number}
+}
+
 export interface Baseline
 {
     rotation:string,